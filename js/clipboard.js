@@ -0,0 +1,17 @@
+export function copyText(text) {
+  if (navigator.clipboard && navigator.clipboard.writeText) {
+    return navigator.clipboard.writeText(text);
+  }
+  // Fallback for insecure contexts (e.g. `http://<local-ip>`) without the Clipboard API.
+  return new Promise(function (resolve, reject) {
+    var textarea = document.createElement('textarea');
+    textarea.value = text;
+    textarea.style.position = 'fixed';
+    textarea.style.opacity = '0';
+    document.body.appendChild(textarea);
+    textarea.select();
+    var copied = document.execCommand('copy');
+    document.body.removeChild(textarea);
+    copied ? resolve() : reject(new Error('Copying to the clipboard failed'));
+  });
+}
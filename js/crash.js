@@ -0,0 +1,63 @@
+// The recovery screen is plain DOM, because the app can't render anything after a panic.
+
+import { copyText } from './clipboard.js';
+
+var CRASH_SCREEN_ID = 'crash-screen';
+
+export function showCrashScreen(details) {
+  var existing = document.getElementById(CRASH_SCREEN_ID);
+  if (existing) {
+    existing.remove();
+  }
+  var screen = document.createElement('div');
+  screen.id = CRASH_SCREEN_ID;
+  screen.style.cssText = [
+    'position: fixed', 'top: 0', 'right: 0', 'bottom: 0', 'left: 0', 'z-index: 1000',
+    'display: flex', 'flex-direction: column', 'align-items: center', 'justify-content: center',
+    'padding: 2rem', 'background-color: #0c0b11', 'color: rgba(255, 255, 255, 0.9)', 'font-family: sans-serif',
+  ].join(';');
+
+  var title = document.createElement('h1');
+  title.textContent = 'Something went wrong';
+  title.style.cssText = 'font-size: 1.8rem; margin-bottom: 1rem';
+
+  var description = document.createElement('div');
+  description.textContent = 'Stremio stopped working. Reload the page to continue; with the analytics consent the problem will be reported on the next start.';
+  description.style.cssText = 'margin-bottom: 1rem; text-align: center';
+
+  var pre = document.createElement('pre');
+  pre.textContent = details;
+  pre.style.cssText = [
+    'max-width: 50rem', 'max-height: 40%', 'overflow: auto', 'padding: 1rem', 'margin-bottom: 1rem',
+    'background-color: rgba(255, 255, 255, 0.05)', 'white-space: pre-wrap', 'user-select: text',
+  ].join(';');
+
+  var buttons = document.createElement('div');
+  buttons.style.cssText = 'display: flex; flex-direction: row';
+  buttons.appendChild(button('Reload', function () {
+    window.location.reload();
+  }));
+  var copyButton = button('Copy details', function () {
+    copyText(details).then(function () {
+      copyButton.textContent = 'Copied';
+    }, function () {
+      copyButton.textContent = 'Copying failed';
+    });
+  });
+  buttons.appendChild(copyButton);
+
+  screen.appendChild(title);
+  screen.appendChild(description);
+  screen.appendChild(pre);
+  screen.appendChild(buttons);
+  document.body.appendChild(screen);
+}
+
+function button(title, onClick) {
+  var element = document.createElement('div');
+  element.textContent = title;
+  element.tabIndex = 0;
+  element.style.cssText = 'margin: 0.5rem; padding: 1rem 2rem; cursor: pointer; background-color: #7b5bf5';
+  element.addEventListener('click', onClick);
+  return element;
+}
@@ -0,0 +1,10 @@
+export function downloadText(fileName, text) {
+  var url = URL.createObjectURL(new Blob([text], { type: 'application/json' }));
+  var link = document.createElement('a');
+  link.href = url;
+  link.download = fileName;
+  document.body.appendChild(link);
+  link.click();
+  document.body.removeChild(link);
+  URL.revokeObjectURL(url);
+}
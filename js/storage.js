@@ -0,0 +1,148 @@
+// IndexedDB key-value storage used by `WebEnv::get_storage` and `WebEnv::set_storage`.
+// Values are JSON strings serialized on the Rust side.
+
+const DB_NAME = "stremio";
+const DB_VERSION = 1;
+const STORE_NAME = "storage";
+// set once all `localStorage` items have been copied to IndexedDB
+const MIGRATED_KEY = "__migrated_from_local_storage";
+let dbPromise = null;
+
+// `webEnvKeys` and `webEnvKeyPrefixes` select the items to migrate (`WEB_ENV_STORAGE_KEYS` on the Rust side),
+// they're used only by the first call.
+function openDb(webEnvKeys = [], webEnvKeyPrefixes = []) {
+  if (!dbPromise) {
+    dbPromise = new Promise((resolve, reject) => {
+      if (typeof indexedDB === "undefined") {
+        reject(new Error("IndexedDB is not available"));
+        return;
+      }
+      // `open` throws e.g. in sandboxed iframes
+      let request;
+      try {
+        request = indexedDB.open(DB_NAME, DB_VERSION);
+      } catch (error) {
+        reject(error);
+        return;
+      }
+      request.onupgradeneeded = () => request.result.createObjectStore(STORE_NAME);
+      request.onsuccess = () => resolve(request.result);
+      request.onerror = () => reject(request.error);
+      request.onblocked = () => reject(new Error("IndexedDB is blocked"));
+    }).then((db) => migrateFromLocalStorage(db, (key) => isWebEnvKey(key, webEnvKeys, webEnvKeyPrefixes)));
+  }
+  return dbPromise;
+}
+
+function isWebEnvKey(key, webEnvKeys, webEnvKeyPrefixes) {
+  return webEnvKeys.includes(key) || webEnvKeyPrefixes.some((prefix) => key.startsWith(prefix));
+}
+
+// One-time migration of the `WebEnv` items written by the previous `localStorage` implementation.
+// The migrated items are removed from `localStorage` to free its quota.
+function migrateFromLocalStorage(db, isWebEnvKey) {
+  return new Promise((resolve, reject) => {
+    const transaction = db.transaction(STORE_NAME, "readwrite");
+    const store = transaction.objectStore(STORE_NAME);
+    const migratedKeys = [];
+    const marker = store.get(MIGRATED_KEY);
+    marker.onsuccess = () => {
+      if (marker.result) {
+        return;
+      }
+      const localStorage = getLocalStorage();
+      if (localStorage) {
+        for (let index = 0; index < localStorage.length; index++) {
+          const key = localStorage.key(index);
+          if (isWebEnvKey(key)) {
+            store.put(localStorage.getItem(key), key);
+            migratedKeys.push(key);
+          }
+        }
+      }
+      store.put(true, MIGRATED_KEY);
+    };
+    transaction.oncomplete = () => {
+      const localStorage = getLocalStorage();
+      migratedKeys.forEach((key) => localStorage.removeItem(key));
+      resolve(db);
+    };
+    transaction.onerror = () => reject(transaction.error);
+    transaction.onabort = () => reject(transaction.error);
+  });
+}
+
+function getLocalStorage() {
+  try {
+    return window.localStorage;
+  } catch (_error) {
+    return null;
+  }
+}
+
+function request(mode, operation) {
+  return openDb().then((db) => new Promise((resolve, reject) => {
+    const transaction = db.transaction(STORE_NAME, mode);
+    const request = operation(transaction.objectStore(STORE_NAME));
+    transaction.oncomplete = () => resolve(request.result);
+    transaction.onerror = () => reject(transaction.error);
+    transaction.onabort = () => reject(transaction.error);
+  }));
+}
+
+// Resolves to `false` when IndexedDB can't be used and `localStorage` should be used instead.
+// It's called before every storage access, so it opens the database with the keys to migrate.
+export function idbAvailable(webEnvKeys, webEnvKeyPrefixes) {
+  return openDb(webEnvKeys, webEnvKeyPrefixes).then(() => true, () => false);
+}
+
+// Resolves to the stored string or `null`.
+export function idbGetItem(key) {
+  return request("readonly", (store) => store.get(key))
+    .then((value) => (typeof value === "string" ? value : null));
+}
+
+export function idbSetItem(key, value) {
+  return request("readwrite", (store) => store.put(value, key)).then(() => undefined);
+}
+
+export function idbRemoveItem(key) {
+  return request("readwrite", (store) => store.delete(key)).then(() => undefined);
+}
+
+// Resolves to all stored keys except the migration marker.
+export function idbKeys() {
+  return request("readonly", (store) => store.getAllKeys())
+    .then((keys) => keys.filter((key) => key !== MIGRATED_KEY));
+}
+
+// Resolves to `[[key, size]]`, sizes are approximate (UTF-16 string length in bytes).
+export function idbUsage() {
+  return openDb().then((db) => new Promise((resolve, reject) => {
+    const usage = [];
+    const transaction = db.transaction(STORE_NAME, "readonly");
+    const cursor = transaction.objectStore(STORE_NAME).openCursor();
+    cursor.onsuccess = () => {
+      const current = cursor.result;
+      if (!current) {
+        return;
+      }
+      if (current.key !== MIGRATED_KEY && typeof current.value === "string") {
+        usage.push([current.key, (current.key.length + current.value.length) * 2]);
+      }
+      current.continue();
+    };
+    transaction.oncomplete = () => resolve(usage);
+    transaction.onerror = () => reject(transaction.error);
+  }));
+}
+
+// Resolves to `{ usage, quota }` in bytes or `null` when the estimate isn't supported.
+export function storageEstimate() {
+  if (typeof navigator === "undefined" || !navigator.storage || !navigator.storage.estimate) {
+    return Promise.resolve(null);
+  }
+  return navigator.storage.estimate()
+    .then(({ usage, quota }) => ({ usage, quota }))
+    .catch(() => null);
+}
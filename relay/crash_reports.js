@@ -0,0 +1,66 @@
+// A tiny dependency-free stand-in for the crash report endpoint, for local development.
+//
+// Crash reports POSTed to `http://localhost:8788/crash_reports` are printed
+// and appended to `crash_reports.jsonl` in the current directory.
+//
+// Usage: `node relay/crash_reports.js [port]`
+
+const http = require("http");
+const fs = require("fs");
+
+const PORT = Number(process.argv[2] || process.env.CRASH_REPORTS_PORT || 8788);
+const REPORTS_FILE = "crash_reports.jsonl";
+const MAX_BODY_SIZE = 1024 * 1024;
+
+const CORS_HEADERS = {
+    "Access-Control-Allow-Origin": "*",
+    "Access-Control-Allow-Methods": "POST, OPTIONS",
+    "Access-Control-Allow-Headers": "Content-Type",
+};
+
+const server = http.createServer((request, response) => {
+    if (request.method === "OPTIONS") {
+        response.writeHead(204, CORS_HEADERS);
+        response.end();
+        return;
+    }
+    if (request.method !== "POST" || request.url !== "/crash_reports") {
+        response.writeHead(404, CORS_HEADERS);
+        response.end();
+        return;
+    }
+    let body = "";
+    request.setEncoding("utf8");
+    request.on("data", (chunk) => {
+        body += chunk;
+        if (body.length > MAX_BODY_SIZE) {
+            response.writeHead(413, CORS_HEADERS);
+            response.end();
+            request.destroy();
+        }
+    });
+    request.on("end", () => {
+        let report;
+        try {
+            report = JSON.parse(body);
+        } catch (error) {
+            response.writeHead(400, CORS_HEADERS);
+            response.end();
+            return;
+        }
+        log(`Crash: ${report.message} at ${report.location} (version ${report.appVersion}, route ${report.route})`);
+        fs.appendFile(REPORTS_FILE, JSON.stringify(report) + "\n", (error) => {
+            if (error) {
+                log(`Writing ${REPORTS_FILE} failed: ${error.message}`);
+            }
+        });
+        response.writeHead(204, CORS_HEADERS);
+        response.end();
+    });
+});
+
+function log(message) {
+    console.log(message);
+}
+
+server.listen(PORT, () => log(`Crash reports listening on http://localhost:${PORT}/crash_reports`));
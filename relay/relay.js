@@ -0,0 +1,176 @@
+// A tiny dependency-free WebSocket relay for local development and tests.
+//
+// Clients connect to `ws://localhost:8787/<room>` and every text message
+// is forwarded to all other clients connected to the same room.
+// The relay doesn't understand the forwarded messages - the protocols
+// (watch party, remote control, ...) are implemented by the clients.
+//
+// Usage: `node relay/relay.js [port]`
+
+const http = require("http");
+const crypto = require("crypto");
+
+const PORT = Number(process.argv[2] || process.env.RELAY_PORT || 8787);
+const WEBSOCKET_GUID = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const OPCODE_CONTINUATION = 0x0;
+const OPCODE_TEXT = 0x1;
+const OPCODE_CLOSE = 0x8;
+const OPCODE_PING = 0x9;
+const OPCODE_PONG = 0xa;
+
+// room name -> Set of sockets
+const rooms = new Map();
+
+const server = http.createServer((_request, response) => {
+    response.writeHead(426, { "Content-Type": "text/plain" });
+    response.end("WebSocket connections only\n");
+});
+
+server.on("upgrade", (request, socket) => {
+    const key = request.headers["sec-websocket-key"];
+    if (!key) {
+        socket.destroy();
+        return;
+    }
+    const accept = crypto
+        .createHash("sha1")
+        .update(key + WEBSOCKET_GUID)
+        .digest("base64");
+    socket.write([
+        "HTTP/1.1 101 Switching Protocols",
+        "Upgrade: websocket",
+        "Connection: Upgrade",
+        `Sec-WebSocket-Accept: ${accept}`,
+        "",
+        "",
+    ].join("\r\n"));
+
+    const room = decodeURIComponent(request.url.replace(/^\/+/, "")) || "default";
+    join(room, socket);
+});
+
+function join(room, socket) {
+    if (!rooms.has(room)) {
+        rooms.set(room, new Set());
+    }
+    const clients = rooms.get(room);
+    clients.add(socket);
+    log(`+ ${room} (${clients.size})`);
+
+    let buffer = Buffer.alloc(0);
+    let fragments = [];
+
+    socket.on("data", (chunk) => {
+        buffer = Buffer.concat([buffer, chunk]);
+        let frame;
+        while ((frame = readFrame(buffer))) {
+            buffer = buffer.subarray(frame.size);
+            switch (frame.opcode) {
+                case OPCODE_CONTINUATION:
+                case OPCODE_TEXT:
+                    fragments.push(frame.payload);
+                    if (frame.fin) {
+                        broadcast(clients, socket, Buffer.concat(fragments));
+                        fragments = [];
+                    }
+                    break;
+                case OPCODE_CLOSE:
+                    socket.end(encodeFrame(OPCODE_CLOSE, Buffer.alloc(0)));
+                    break;
+                case OPCODE_PING:
+                    socket.write(encodeFrame(OPCODE_PONG, frame.payload));
+                    break;
+            }
+        }
+    });
+
+    let left = false;
+    const leave = () => {
+        if (left) {
+            return;
+        }
+        left = true;
+        clients.delete(socket);
+        if (clients.size === 0) {
+            rooms.delete(room);
+        }
+        log(`- ${room} (${clients.size})`);
+    };
+    socket.on("close", leave);
+    socket.on("error", leave);
+}
+
+function broadcast(clients, sender, payload) {
+    const frame = encodeFrame(OPCODE_TEXT, payload);
+    for (const client of clients) {
+        if (client !== sender && !client.destroyed) {
+            client.write(frame);
+        }
+    }
+}
+
+// Returns `null` until the buffer contains the whole frame.
+function readFrame(buffer) {
+    if (buffer.length < 2) {
+        return null;
+    }
+    const fin = (buffer[0] & 0x80) !== 0;
+    const opcode = buffer[0] & 0x0f;
+    const masked = (buffer[1] & 0x80) !== 0;
+    let length = buffer[1] & 0x7f;
+    let offset = 2;
+    if (length === 126) {
+        if (buffer.length < 4) {
+            return null;
+        }
+        length = buffer.readUInt16BE(2);
+        offset = 4;
+    } else if (length === 127) {
+        if (buffer.length < 10) {
+            return null;
+        }
+        length = Number(buffer.readBigUInt64BE(2));
+        offset = 10;
+    }
+    const maskOffset = offset;
+    if (masked) {
+        offset += 4;
+    }
+    if (buffer.length < offset + length) {
+        return null;
+    }
+    const payload = Buffer.from(buffer.subarray(offset, offset + length));
+    if (masked) {
+        for (let index = 0; index < payload.length; index++) {
+            payload[index] ^= buffer[maskOffset + (index % 4)];
+        }
+    }
+    return { fin, opcode, payload, size: offset + length };
+}
+
+function encodeFrame(opcode, payload) {
+    let header;
+    if (payload.length < 126) {
+        header = Buffer.from([0x80 | opcode, payload.length]);
+    } else if (payload.length < 65536) {
+        header = Buffer.alloc(4);
+        header[0] = 0x80 | opcode;
+        header[1] = 126;
+        header.writeUInt16BE(payload.length, 2);
+    } else {
+        header = Buffer.alloc(10);
+        header[0] = 0x80 | opcode;
+        header[1] = 127;
+        header.writeBigUInt64BE(BigInt(payload.length), 2);
+    }
+    return Buffer.concat([header, payload]);
+}
+
+function log(message) {
+    if (!process.env.RELAY_QUIET) {
+        console.log(message);
+    }
+}
+
+server.listen(PORT, () => log(`Relay listening on ws://localhost:${PORT}/<room>`));
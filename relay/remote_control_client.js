@@ -0,0 +1,55 @@
+// A command-line stand-in for the remote control page.
+//
+// Connects to the relay room of a paired player, sends one command once
+// the pairing is allowed in the player and prints the player status messages it receives.
+//
+// Usage: `node relay/remote_control_client.js <CODE> <command> [value]`
+// Commands: `hello`, `togglePlay`, `seekBy <seconds>`, `seekTo <seconds>`,
+// `setVolume <percent>`, `nextVideo`
+// Node < 22 needs the `--experimental-websocket` flag.
+
+const RELAY_URL = process.env.RELAY_URL || "ws://localhost:8787";
+const ROOM_PREFIX = "remote";
+
+const CONFIRMATION_TIMEOUT = 30000;
+
+const [code, command = "hello", value] = process.argv.slice(2);
+if (!code) {
+    console.error("Usage: node relay/remote_control_client.js <CODE> <command> [value]");
+    process.exit(1);
+}
+if (typeof WebSocket === "undefined") {
+    console.error("WebSocket is not available, run with `node --experimental-websocket`");
+    process.exit(1);
+}
+
+function message(command, value) {
+    const number = Number(value);
+    switch (command) {
+        case "seekBy": return { type: command, offset: number };
+        case "seekTo": return { type: command, time: number };
+        case "setVolume": return { type: command, volume: number };
+        default: return { type: command };
+    }
+}
+
+const socket = new WebSocket(`${RELAY_URL}/${ROOM_PREFIX}-${code.toUpperCase()}`);
+let sent = command === "hello";
+socket.onopen = () => {
+    socket.send(JSON.stringify({ type: "hello" }));
+    console.log("Allow the pairing in the player");
+};
+// the player sends its status only after the pairing is allowed
+socket.onmessage = (event) => {
+    console.log(event.data);
+    if (!sent) {
+        sent = true;
+        socket.send(JSON.stringify(message(command, value)));
+        setTimeout(() => socket.close(), 2000);
+    }
+};
+socket.onerror = () => {
+    console.error(`Relay ${RELAY_URL} is not available`);
+    process.exit(1);
+};
+setTimeout(() => socket.close(), CONFIRMATION_TIMEOUT);
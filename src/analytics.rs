@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use stremio_core::runtime::msg::{Event, Msg as CoreMsg};
+use stremio_core::types::resource::StreamSource;
+use crate::PageId;
+
+pub const ANALYTICS_CONSENT_STORAGE_KEY: &str = "analytics_consent";
+// ms
+pub const ANALYTICS_FLUSH_INTERVAL: u32 = 30_000;
+
+/// User's answer to the analytics consent prompt.
+/// Nothing is sent to the analytics server until it's `Granted`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AnalyticsConsent {
+    Granted,
+    Denied,
+}
+
+impl AnalyticsConsent {
+    pub fn is_granted(consent: Option<Self>) -> bool {
+        consent == Some(Self::Granted)
+    }
+}
+
+// ------ Events ------
+
+/// Catalogue of the events sent to the analytics server.
+/// Events must not contain personal data, e.g. only the length of search queries is sent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnalyticsEvent {
+    PageView { page: &'static str },
+    Search { query_length: usize },
+    AddonInstalled { addon_id: String },
+    AddonUninstalled { addon_id: String },
+    PlayerStarted { source: StreamSourceType },
+    PlayerStopped { source: StreamSourceType, time: Option<u32> },
+}
+
+impl AnalyticsEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PageView { .. } => "pageView",
+            Self::Search { .. } => "search",
+            Self::AddonInstalled { .. } => "addonInstalled",
+            Self::AddonUninstalled { .. } => "addonUninstalled",
+            Self::PlayerStarted { .. } => "playerStarted",
+            Self::PlayerStopped { .. } => "playerStopped",
+        }
+    }
+
+    pub fn data(&self) -> serde_json::Value {
+        match self {
+            Self::PageView { page } => json!({ "page": page }),
+            Self::Search { query_length } => json!({ "queryLength": query_length }),
+            Self::AddonInstalled { addon_id } | Self::AddonUninstalled { addon_id } => {
+                json!({ "addonId": addon_id })
+            }
+            Self::PlayerStarted { source } => json!({ "sourceType": source }),
+            Self::PlayerStopped { source, time } => json!({ "sourceType": source, "time": time }),
+        }
+    }
+
+    pub fn page_view(page_id: PageId) -> Self {
+        let page = match page_id {
+            PageId::Board => "board",
+            PageId::Detail => "detail",
+            PageId::Discover => "discover",
+            PageId::Intro => "intro",
+            PageId::Library => "library",
+            PageId::Player => "player",
+            PageId::Addons => "addons",
+            PageId::NotFound => "not_found",
+            PageId::Search => "search",
+            PageId::Settings => "settings",
+            PageId::Remote => "remote",
+            PageId::TestLinks => "test_links",
+            PageId::Logs => "logs",
+            PageId::Inspector => "inspector",
+            PageId::BugReport => "bug_report",
+        };
+        Self::PageView { page }
+    }
+
+    /// Events derived from core events, so failed installations aren't counted.
+    pub fn from_core_msg(core_msg: &CoreMsg) -> Option<Self> {
+        match core_msg {
+            CoreMsg::Event(Event::AddonInstalled { id, .. }) => Some(Self::AddonInstalled {
+                addon_id: id.clone(),
+            }),
+            CoreMsg::Event(Event::AddonUninstalled { id, .. }) => Some(Self::AddonUninstalled {
+                addon_id: id.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StreamSourceType {
+    Url,
+    YouTube,
+    Torrent,
+    External,
+    Other,
+}
+
+impl From<&StreamSource> for StreamSourceType {
+    fn from(source: &StreamSource) -> Self {
+        match source {
+            StreamSource::Url { .. } => Self::Url,
+            StreamSource::YouTube { .. } => Self::YouTube,
+            StreamSource::Torrent { .. } => Self::Torrent,
+            StreamSource::External { .. } => Self::External,
+            #[allow(unreachable_patterns)]
+            _ => Self::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stremio_core::runtime::msg::{Action, ActionCtx};
+
+    const ADDON_ID: &str = "com.linvo.cinemeta";
+    const TRANSPORT_URL: &str = "https://v3-cinemeta.strem.io/manifest.json";
+
+    #[test]
+    fn addon_events_are_mapped() {
+        let installed = CoreMsg::Event(Event::AddonInstalled {
+            transport_url: TRANSPORT_URL.parse().unwrap(),
+            id: ADDON_ID.to_owned(),
+        });
+        assert_eq!(
+            AnalyticsEvent::from_core_msg(&installed),
+            Some(AnalyticsEvent::AddonInstalled { addon_id: ADDON_ID.to_owned() }),
+        );
+        let uninstalled = CoreMsg::Event(Event::AddonUninstalled {
+            transport_url: TRANSPORT_URL.parse().unwrap(),
+            id: ADDON_ID.to_owned(),
+        });
+        assert_eq!(
+            AnalyticsEvent::from_core_msg(&uninstalled),
+            Some(AnalyticsEvent::AddonUninstalled { addon_id: ADDON_ID.to_owned() }),
+        );
+    }
+
+    #[test]
+    fn actions_are_not_mapped() {
+        assert_eq!(AnalyticsEvent::from_core_msg(&CoreMsg::Action(Action::Ctx(ActionCtx::Logout))), None);
+    }
+
+    #[test]
+    fn events_are_serialized_without_personal_data() {
+        let search = AnalyticsEvent::Search { query_length: 6 };
+        assert_eq!(search.name(), "search");
+        assert_eq!(search.data(), json!({ "queryLength": 6 }));
+
+        let stopped = AnalyticsEvent::PlayerStopped { source: StreamSourceType::YouTube, time: Some(42) };
+        assert_eq!(stopped.name(), "playerStopped");
+        assert_eq!(stopped.data(), json!({ "sourceType": "youTube", "time": 42 }));
+
+        assert_eq!(AnalyticsEvent::page_view(PageId::Discover), AnalyticsEvent::PageView { page: "discover" });
+    }
+}
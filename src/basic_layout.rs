@@ -5,6 +5,8 @@ use seed_styles::*;
 use crate::styles::{self, themes::{Color, Breakpoint}, global};
 use seed_hooks::{*, topo::nested as view};
 use std::rc::Rc;
+use crate::env::connectivity::ConnectivityStatus;
+use crate::analytics::AnalyticsConsent;
 
 mod menu;
 mod horizontal_nav_bar;
@@ -71,7 +73,137 @@ fn route_content(args: BasicLayoutArgs) -> Node<Msg> {
             ),
             vertical_nav_bar(&args.context.root_base_url, args.page_id),
             nav_content_container(args.page_content),
-        ]
+        ],
+        div![
+            C!["banners-container"],
+            s()
+                .bottom("0")
+                .display(CssDisplay::Flex)
+                .flex_direction(CssFlexDirection::Column)
+                .left("0")
+                .position(CssPosition::Absolute)
+                .right("0")
+                .z_index("1"),
+            IF!(args.context.storage_save_failed => storage_save_error_banner()),
+            offline_banner(args.context.connectivity),
+        ],
+        IF!(args.context.analytics_consent_loaded && args.context.analytics_consent.is_none() => {
+            analytics_consent_prompt()
+        }),
+    ]
+}
+
+#[view]
+fn storage_save_error_banner() -> Node<Msg> {
+    div![
+        C!["storage-save-error-banner"],
+        s()
+            .align_items(CssAlignItems::Center)
+            .background_color(Color::Signal2)
+            .color(Color::SurfaceLight5_90)
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Row)
+            .justify_content(CssJustifyContent::Center)
+            .padding(rem(0.7)),
+        attrs!{
+            At::from("role") => "alert",
+        },
+        "Your changes couldn't be saved because the browser storage is full. Free up space in Settings.",
+        div![
+            C!["button-container"],
+            s()
+                .cursor(CssCursor::Pointer)
+                .margin_left(rem(1))
+                .text_decoration("underline"),
+            attrs!{
+                At::TabIndex => 0,
+                At::Title => "Dismiss",
+            },
+            ev(Ev::Click, |_| Msg::DismissStorageSaveError),
+            "Dismiss",
+        ],
+    ]
+}
+
+#[view]
+fn analytics_consent_prompt() -> Node<Msg> {
+    div![
+        C!["analytics-consent-prompt"],
+        s()
+            .background_color(Color::BackgroundDark3)
+            .bottom(rem(1))
+            .color(Color::SurfaceLight5_90)
+            .max_width(rem(24))
+            .padding(rem(1.5))
+            .position(CssPosition::Absolute)
+            .right(rem(1))
+            .z_index("2"),
+        attrs!{
+            At::from("role") => "dialog",
+            At::from("aria-label") => "Analytics consent",
+        },
+        div![
+            s()
+                .line_height(rem(1.5))
+                .margin_bottom(rem(1)),
+            "Help us improve Stremio by sending anonymous usage data. You can change it anytime in Settings.",
+        ],
+        div![
+            s()
+                .display(CssDisplay::Flex)
+                .flex_direction(CssFlexDirection::Row),
+            consent_button("Allow", true, || Msg::SetAnalyticsConsent(AnalyticsConsent::Granted)),
+            consent_button("Decline", false, || Msg::SetAnalyticsConsent(AnalyticsConsent::Denied)),
+        ],
+    ]
+}
+
+#[view]
+fn consent_button(title: &str, primary: bool, on_click: fn() -> Msg) -> Node<Msg> {
+    div![
+        C!["button-container"],
+        s()
+            .background_color(if primary { Color::Accent3 } else { Color::BackgroundDark1 })
+            .flex("1")
+            .margin_right(rem(0.5))
+            .padding(rem(0.7))
+            .text_align(CssTextAlign::Center)
+            .cursor(CssCursor::Pointer),
+        s()
+            .hover()
+            .background_color(if primary { Color::Accent3Light1 } else { Color::BackgroundLight1 }),
+        attrs!{
+            At::TabIndex => 0,
+            At::Title => title,
+        },
+        ev(Ev::Click, move |_| on_click()),
+        title,
+    ]
+}
+
+#[view]
+fn offline_banner(connectivity: ConnectivityStatus) -> Node<Msg> {
+    let message = match (connectivity.online, connectivity.queued_requests) {
+        (true, 0) => return empty![],
+        (true, _) => "Connection restored, syncing changes...".to_owned(),
+        (false, 0) => "You are offline.".to_owned(),
+        (false, 1) => "You are offline. 1 change will be synced when the connection is restored.".to_owned(),
+        (false, queued_requests) => format!(
+            "You are offline. {} changes will be synced when the connection is restored.",
+            queued_requests,
+        ),
+    };
+    div![
+        C!["offline-banner"],
+        s()
+            .background_color(if connectivity.online { Color::Signal5 } else { Color::Signal2 })
+            .color(Color::SurfaceLight5_90)
+            .padding(rem(0.7))
+            .text_align(CssTextAlign::Center),
+        attrs!{
+            At::from("role") => "status",
+        },
+        message,
     ]
 }
 
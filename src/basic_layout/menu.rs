@@ -253,6 +253,13 @@ fn menu_section_general(root_base_url: &Url) -> Node<Msg> {
             enabled: true,
             on_click: None,
         }),
+        menu_option(MenuOptionArgs { 
+            title: "Report Problem", 
+            link: None, 
+            icon: None, 
+            enabled: true,
+            on_click: Some(ev(Ev::Click, |_| Msg::ReportProblem)),
+        }),
     ]
 }
 
@@ -0,0 +1,240 @@
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::rc::Rc;
+use stremio_core::runtime::{Env, Update};
+use stremio_core::runtime::msg::{Msg as CoreMsg, CtxStorageResponse};
+use crate::{bug_report, CoreEnv, CoreModel};
+
+const MAX_RECORDS: usize = 500;
+// `MAX_RECORDS` is its multiple
+const SNAPSHOT_INTERVAL: usize = 50;
+const DESCRIPTION_LENGTH: usize = 120;
+
+// ------ Change ------
+
+/// Everything that modifies `CoreModel`, so the state can be rebuilt from the recorded changes.
+#[derive(Clone)]
+pub enum Change {
+    CtxLoaded(CtxStorageResponse),
+    CoreMsg(Rc<CoreMsg>),
+    StorageChanged { key: String, value: Option<String> },
+    LoggedOutLocally,
+}
+
+impl Change {
+    fn apply(&self, core_model: &mut CoreModel) {
+        // effects are dropped, the messages they produce are recorded separately
+        match self {
+            Self::CtxLoaded(ctx_storage) => {
+                core_model.load_ctx(ctx_storage.clone());
+            }
+            Self::CoreMsg(core_msg) => {
+                core_model.update(core_msg);
+            }
+            Self::StorageChanged { key, value } => {
+                let _ = core_model.merge_storage_change(key, value.as_deref());
+            }
+            Self::LoggedOutLocally => {
+                core_model.log_out_locally();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Dispatched,
+    Effect,
+    Storage,
+    OtherTab,
+}
+
+impl Source {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dispatched => "dispatched",
+            Self::Effect => "effect",
+            Self::Storage => "storage",
+            Self::OtherTab => "other tab",
+        }
+    }
+}
+
+// ------ Record ------
+
+pub struct Record {
+    pub time: DateTime<Utc>,
+    pub source: Source,
+    pub change: Change,
+    pub has_changed: bool,
+}
+
+impl Record {
+    pub fn details(&self) -> String {
+        match &self.change {
+            Change::CtxLoaded(ctx_storage) => format!("CtxLoaded {:#?}", ctx_storage),
+            Change::CoreMsg(core_msg) => format!("{:#?}", core_msg),
+            Change::StorageChanged { key, value } => format!("StorageChanged {} = {}", key, value.as_deref().unwrap_or("null")),
+            Change::LoggedOutLocally => "LoggedOutLocally".to_owned(),
+        }
+    }
+
+    /// Single-line summary of `details`.
+    pub fn description(&self) -> String {
+        let details = match &self.change {
+            Change::CoreMsg(core_msg) => format!("{:?}", core_msg),
+            Change::CtxLoaded(_) => "CtxLoaded".to_owned(),
+            _ => self.details(),
+        };
+        let mut description = details.chars().take(DESCRIPTION_LENGTH).collect::<String>();
+        if description.len() < details.len() {
+            description.push('…');
+        }
+        description
+    }
+}
+
+// ------ CoreInspector ------
+
+/// Records the changes of `CoreModel` and rebuilds the state after any of them.
+///
+/// Recording starts from the current state when the inspector page is opened for the first time
+/// or when it's toggled on there. The state is copied every `SNAPSHOT_INTERVAL` records, so a replay
+/// applies at most `SNAPSHOT_INTERVAL` changes. At most `MAX_RECORDS` changes are kept, the oldest ones
+/// are dropped together with their snapshot.
+#[derive(Default)]
+pub struct CoreInspector {
+    // `snapshots[n]` is the state before `records[n * SNAPSHOT_INTERVAL]`
+    snapshots: VecDeque<CoreModel>,
+    records: VecDeque<Record>,
+    // messages produced by core effects that haven't reached `update` yet
+    pending_effects: Vec<Rc<CoreMsg>>,
+}
+
+impl CoreInspector {
+    pub fn is_recording(&self) -> bool {
+        !self.snapshots.is_empty()
+    }
+
+    pub fn set_recording(&mut self, recording: bool, core_model: &CoreModel) {
+        self.snapshots = recording.then(|| core_model.clone()).into_iter().collect();
+        self.records.clear();
+        self.pending_effects.clear();
+    }
+
+    pub fn records(&self) -> &VecDeque<Record> {
+        &self.records
+    }
+
+    /// Marks the message as produced by an effect before it's dispatched back to the core.
+    pub fn effect_received(&mut self, core_msg: &Rc<CoreMsg>) {
+        if self.is_recording() {
+            self.pending_effects.push(Rc::clone(core_msg));
+        }
+    }
+
+    /// `core_model` is the state after the message.
+    pub fn record_core_msg(&mut self, core_msg: &Rc<CoreMsg>, has_changed: bool, core_model: &CoreModel) {
+        let effect_index = self
+            .pending_effects
+            .iter()
+            .position(|pending_effect| Rc::ptr_eq(pending_effect, core_msg));
+        let source = match effect_index {
+            Some(index) => {
+                self.pending_effects.remove(index);
+                Source::Effect
+            }
+            None => Source::Dispatched,
+        };
+        self.record(source, Change::CoreMsg(Rc::clone(core_msg)), has_changed, core_model);
+    }
+
+    /// `core_model` is the state after the change, it's copied when a snapshot is due.
+    /// The change is also named for bug reports, even when it isn't recorded.
+    pub fn record(&mut self, source: Source, change: Change, has_changed: bool, core_model: &CoreModel) {
+        bug_report::record_core_message(source, &change, has_changed);
+        if !self.is_recording() {
+            return
+        }
+        self.records.push_back(Record {
+            time: CoreEnv::now(),
+            source,
+            change,
+            has_changed,
+        });
+        if self.records.len() > MAX_RECORDS {
+            self.records.drain(..SNAPSHOT_INTERVAL);
+            self.snapshots.pop_front();
+        }
+        if self.records.len() % SNAPSHOT_INTERVAL == 0 {
+            self.snapshots.push_back(core_model.clone());
+        }
+    }
+
+    /// Rebuilds the state right after the record with the given index from the closest snapshot.
+    ///
+    /// _Note_: Changes are re-applied with the current time and without running their effects.
+    pub fn replay(&self, index: usize) -> Option<CoreModel> {
+        if index >= self.records.len() {
+            return None
+        }
+        let snapshot_index = index / SNAPSHOT_INTERVAL;
+        let mut core_model = self.snapshots.get(snapshot_index)?.clone();
+        for record in self.records.range(snapshot_index * SNAPSHOT_INTERVAL..=index) {
+            record.change.apply(&mut core_model);
+        }
+        Some(core_model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use stremio_core::runtime::msg::{Action, ActionCtx};
+    use stremio_core::types::resource::MetaItemPreview;
+
+    fn add_to_library(id: &str) -> Rc<CoreMsg> {
+        let meta_preview = serde_json::from_value::<MetaItemPreview>(json!({ "id": id, "type": "movie", "name": "Test movie" }))
+            .expect("valid meta preview");
+        Rc::new(CoreMsg::Action(Action::Ctx(ActionCtx::AddToLibrary(meta_preview))))
+    }
+
+    fn dispatch(core_model: &mut CoreModel, inspector: &mut CoreInspector, core_msg: Rc<CoreMsg>) {
+        let effects = core_model.update(&core_msg);
+        inspector.record_core_msg(&core_msg, effects.has_changed, core_model);
+    }
+
+    #[test]
+    fn replay_rebuilds_the_state_after_a_record() {
+        let (mut core_model, _) = CoreModel::new();
+        let mut inspector = CoreInspector::default();
+        inspector.set_recording(true, &core_model);
+        dispatch(&mut core_model, &mut inspector, add_to_library("tt0000001"));
+        dispatch(&mut core_model, &mut inspector, add_to_library("tt0000002"));
+
+        let first = inspector.replay(0).expect("recording inspector");
+        assert!(first.ctx.library.items.contains_key("tt0000001"));
+        assert!(!first.ctx.library.items.contains_key("tt0000002"));
+        let last = inspector.replay(1).expect("recording inspector");
+        assert_eq!(last.ctx.library.items.keys().collect::<Vec<_>>(), core_model.ctx.library.items.keys().collect::<Vec<_>>());
+        assert!(inspector.records().iter().all(|record| record.source == Source::Dispatched && record.has_changed));
+    }
+
+    #[test]
+    fn oldest_records_are_dropped_with_their_snapshot() {
+        let (mut core_model, _) = CoreModel::new();
+        let mut inspector = CoreInspector::default();
+        inspector.set_recording(true, &core_model);
+        dispatch(&mut core_model, &mut inspector, add_to_library("tt0000001"));
+        for _ in 0..MAX_RECORDS {
+            dispatch(&mut core_model, &mut inspector, add_to_library("tt0000002"));
+        }
+
+        assert_eq!(inspector.records().len(), MAX_RECORDS - SNAPSHOT_INTERVAL + 1);
+        let first = inspector.replay(0).expect("recording inspector");
+        assert!(first.ctx.library.items.contains_key("tt0000001"));
+        let last = inspector.replay(inspector.records().len() - 1).expect("recording inspector");
+        assert_eq!(last.ctx.library.items.keys().collect::<Vec<_>>(), core_model.ctx.library.items.keys().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use http::Request;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::panic::{self, PanicInfo};
+use stremio_core::runtime::Env;
+use wasm_bindgen::prelude::wasm_bindgen;
+use crate::bug_report;
+use crate::env::WebEnv;
+use crate::logger::{self, Entry};
+
+/// Endpoint receiving crash reports, set by the `CRASH_REPORT_URL` env variable during the build.
+/// See `/relay/crash_reports.js` for a local stand-in. Without it crashes are only kept locally.
+pub const CRASH_REPORT_URL: Option<&str> = option_env!("CRASH_REPORT_URL");
+const LAST_CRASH_STORAGE_KEY: &str = "last_crash";
+const CRASH_LOG_ENTRIES: usize = 20;
+
+thread_local! {
+    // the consent can be loaded and granted in the same visit, the crash is sent only once
+    static REPORTING: Cell<bool> = Cell::new(false);
+}
+
+#[wasm_bindgen(module = "/js/crash.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = showCrashScreen)]
+    fn show_crash_screen(details: &str);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub time: DateTime<Utc>,
+    pub message: String,
+    pub location: Option<String>,
+    pub app_version: String,
+    pub route: String,
+    // the entries logged right before the panic
+    pub logs: Vec<Entry>,
+}
+
+impl CrashReport {
+    fn new(info: &PanicInfo) -> Self {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| (*message).to_owned())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Unknown panic".to_owned());
+        Self {
+            time: WebEnv::now(),
+            message: bug_report::redact(&message),
+            location: info
+                .location()
+                .map(|location| format!("{}:{}:{}", location.file(), location.line(), location.column())),
+            app_version: WebEnv::app_version(),
+            route: bug_report::current_location(),
+            // `last_entries` doesn't borrow the entries when the panic happened during logging
+            logs: logger::last_entries(CRASH_LOG_ENTRIES)
+                .into_iter()
+                .map(|entry| Entry { message: bug_report::redact(&entry.message), ..entry })
+                .collect(),
+        }
+    }
+
+    fn details(&self) -> String {
+        let mut details = format!(
+            "{}\nat {}\n\nversion: {}\nroute: {}\ntime: {}\n",
+            self.message,
+            self.location.as_deref().unwrap_or("unknown location"),
+            self.app_version,
+            self.route,
+            self.time.to_rfc3339(),
+        );
+        if !self.logs.is_empty() {
+            details.push_str("\nlogs:\n");
+            for entry in &self.logs {
+                details.push_str(&format!("{}\n", entry));
+            }
+        }
+        details
+    }
+}
+
+/// Replaces the default panic behaviour (a frozen app) with the recovery screen.
+/// The crash is stored synchronously, so it can be reported on the next start by `report_last_crash`.
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let crash_report = CrashReport::new(info);
+        let details = crash_report.details();
+        web_sys::console::error_1(&details.clone().into());
+        if let Some(storage) = local_storage() {
+            if let Ok(crash_report) = serde_json::to_string(&crash_report) {
+                let _ = storage.set_item(LAST_CRASH_STORAGE_KEY, &crash_report);
+            }
+        }
+        show_crash_screen(&details);
+    }));
+}
+
+/// Sends the crash stored by the panic hook during the previous visit.
+/// Call it only when the analytics consent is granted.
+/// It stays stored when the endpoint is unavailable or not set, so the next start tries again.
+pub async fn report_last_crash() {
+    if REPORTING.with(|reporting| reporting.replace(true)) {
+        return
+    }
+    send_last_crash().await;
+    REPORTING.with(|reporting| reporting.set(false));
+}
+
+async fn send_last_crash() {
+    let storage = match local_storage() {
+        Some(storage) => storage,
+        None => return,
+    };
+    let crash_report = match storage.get_item(LAST_CRASH_STORAGE_KEY).ok().flatten() {
+        Some(crash_report) => crash_report,
+        None => return,
+    };
+    let crash_report = match serde_json::from_str::<CrashReport>(&crash_report) {
+        Ok(crash_report) => crash_report,
+        Err(error) => {
+            log_error!("crash", "Stored crash report is invalid: {}", error);
+            let _ = storage.remove_item(LAST_CRASH_STORAGE_KEY);
+            return
+        }
+    };
+    log_warn!("crash", "The previous visit crashed: {}", crash_report.message);
+    let crash_report_url = match CRASH_REPORT_URL {
+        Some(crash_report_url) => crash_report_url,
+        None => return,
+    };
+    let request = Request::post(crash_report_url)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(crash_report)
+        .expect("valid request");
+    match WebEnv::fetch::<_, serde_json::Value>(request).await {
+        Ok(_) => {
+            let _ = storage.remove_item(LAST_CRASH_STORAGE_KEY);
+        }
+        Err(error) => log_warn!("crash", "Crash report sending failed: {}", error.message()),
+    }
+}
+
+// The panic hook can't wait for the asynchronous `Env` storage.
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
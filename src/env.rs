@@ -1,21 +1,50 @@
 use chrono::offset::TimeZone;
 use chrono::{DateTime, Utc};
-use futures::future::Either;
-use futures::{future, Future, FutureExt, TryFutureExt};
-use http::{Method, Request};
+use futures::{future, Future, FutureExt};
+use http::Request;
 use serde::{Deserialize, Serialize};
 use lazy_static::lazy_static;
-use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 use stremio_analytics::Analytics;
 use stremio_core::models::ctx::Ctx;
+use crate::analytics::{AnalyticsEvent, ANALYTICS_CONSENT_STORAGE_KEY};
+use crate::page::player::skip_markers::SKIP_MARKERS_STORAGE_KEY_PREFIX;
+use crate::route;
+use crate::storage_schema::{RawStorage, BACKUP_KEY_PREFIX, SCHEMA_VERSION_STORAGE_KEY};
+use stremio_core::constants::{LIBRARY_RECENT_STORAGE_KEY, LIBRARY_STORAGE_KEY, PROFILE_STORAGE_KEY};
 use stremio_core::models::streaming_server::StreamingServer;
 use stremio_core::runtime::{Env, EnvError, EnvFuture, TryEnvFuture};
 use wasm_bindgen::prelude::wasm_bindgen;
-use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen::JsValue;
 use wasm_bindgen_futures::{spawn_local, JsFuture};
 
+mod fetch;
+pub mod connectivity;
+pub mod cross_tab;
+pub mod http_cache;
+pub mod storage_usage;
+pub use fetch::{FetchTimeout, DEFAULT_FETCH_TIMEOUT};
+
 const INSTALLATION_ID_STORAGE_KEY: &str = "installation_id";
+const INSTALLATION_ID_LENGTH: usize = 10;
+
+/// Items written by `WebEnv::set_storage`, they're moved from `localStorage` to IndexedDB once.
+/// Other keys are read synchronously (e.g. `log_level`, `last_crash`) and stay in `localStorage`.
+const WEB_ENV_STORAGE_KEYS: &[&str] = &[
+    PROFILE_STORAGE_KEY,
+    LIBRARY_RECENT_STORAGE_KEY,
+    LIBRARY_STORAGE_KEY,
+    INSTALLATION_ID_STORAGE_KEY,
+    SCHEMA_VERSION_STORAGE_KEY,
+    ANALYTICS_CONSENT_STORAGE_KEY,
+    http_cache::INDEX_STORAGE_KEY,
+];
+const WEB_ENV_STORAGE_KEY_PREFIXES: &[&str] = &[
+    SKIP_MARKERS_STORAGE_KEY_PREFIX,
+    BACKUP_KEY_PREFIX,
+    http_cache::STORAGE_KEY_PREFIX,
+];
 
 #[wasm_bindgen]
 extern "C" {
@@ -27,12 +56,33 @@ extern "C" {
     fn sanitize_location_path(path: &str) -> Result<String, JsValue>;
 }
 
+#[wasm_bindgen(module = "/js/storage.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = idbAvailable)]
+    fn idb_available(web_env_keys: &js_sys::Array, web_env_key_prefixes: &js_sys::Array) -> js_sys::Promise;
+    #[wasm_bindgen(js_name = idbGetItem)]
+    fn idb_get_item(key: &str) -> js_sys::Promise;
+    #[wasm_bindgen(js_name = idbSetItem)]
+    fn idb_set_item(key: &str, value: &str) -> js_sys::Promise;
+    #[wasm_bindgen(js_name = idbRemoveItem)]
+    fn idb_remove_item(key: &str) -> js_sys::Promise;
+    #[wasm_bindgen(js_name = idbKeys)]
+    fn idb_keys() -> js_sys::Promise;
+    #[wasm_bindgen(js_name = idbUsage)]
+    fn idb_usage() -> js_sys::Promise;
+    #[wasm_bindgen(js_name = storageEstimate)]
+    fn storage_estimate() -> js_sys::Promise;
+}
+
 lazy_static! {
     static ref INSTALLATION_ID: RwLock<Option<String>> = Default::default();
     static ref VISIT_ID: String = hex::encode(WebEnv::random_buffer(10));
-    static ref ANALYTICS: Analytics<WebEnv> = Default::default();
+    // replaced when the consent is withdrawn to drop queued events
+    static ref ANALYTICS: RwLock<Arc<Analytics<WebEnv>>> = Default::default();
 }
 
+static ANALYTICS_ALLOWED: AtomicBool = AtomicBool::new(false);
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct AnalyticsContext {
@@ -59,6 +109,82 @@ impl WebEnv {
         getrandom::getrandom(buffer.as_mut_slice()).expect("generate random buffer failed");
         buffer
     }
+
+    /// Loads the installation id used in the analytics context; analytics events emitted
+    /// before it's loaded aren't sent. If the storage is unavailable, the id lives only
+    /// for the current visit. API writes queued before the last reload are sent once online.
+    pub async fn init() {
+        let installation_id = load_installation_id::<WebEnv>().await.unwrap_or_else(|error| {
+            log_error!("env", "Installation id loading failed: {}", error.message());
+            new_installation_id()
+        });
+        *INSTALLATION_ID.write().expect("installation id write failed") = Some(installation_id);
+        spawn_local(fetch::replay_pending_writes());
+    }
+
+    /// Analytics are disabled until the user grants the consent; withdrawing it drops queued events.
+    pub fn set_analytics_allowed(allowed: bool) {
+        let was_allowed = ANALYTICS_ALLOWED.swap(allowed, Ordering::SeqCst);
+        if was_allowed && !allowed {
+            *ANALYTICS.write().expect("analytics write failed") = Default::default();
+        }
+    }
+
+    pub fn analytics_allowed() -> bool {
+        ANALYTICS_ALLOWED.load(Ordering::SeqCst)
+    }
+
+    pub fn app_version() -> String {
+        app_version.to_owned()
+    }
+
+    pub fn shell_version() -> Option<String> {
+        shell_version.to_owned()
+    }
+
+    /// Queues the event; queued events are sent by `flush_analytics`.
+    /// All events are logged at the debug level, including the ones not sent.
+    pub fn emit_analytics(event: &AnalyticsEvent, ctx: &Ctx, streaming_server: &StreamingServer) {
+        // events emitted before `WebEnv::init` has loaded the installation id are dropped
+        let allowed = WebEnv::analytics_allowed() && installation_id().is_some();
+        log_debug!("analytics", "{}{} {}", event.name(), if allowed { "" } else { " (not sent)" }, event.data());
+        if !allowed {
+            return
+        }
+        ANALYTICS
+            .read()
+            .expect("analytics read failed")
+            .emit(event.name().to_owned(), event.data(), ctx, streaming_server);
+    }
+
+    pub async fn storage_keys() -> Result<Vec<String>, EnvError> {
+        if !is_idb_available().await {
+            let storage = local_storage()?;
+            let length = storage.length().map_err(|_| EnvError::StorageUnavailable)?;
+            return Ok((0..length).filter_map(|index| storage.key(index).ok().flatten()).collect())
+        }
+        let keys = JsFuture::from(idb_keys())
+            .await
+            .map_err(|_| EnvError::StorageUnavailable)?;
+        Ok(js_sys::Array::from(&keys).iter().filter_map(|key| key.as_string()).collect())
+    }
+}
+
+impl RawStorage for WebEnv {
+    fn get_raw_storage(key: &str) -> TryEnvFuture<Option<String>> {
+        let key = key.to_owned();
+        async move {
+            if is_idb_available().await {
+                Ok(JsFuture::from(idb_get_item(&key))
+                    .await
+                    .map_err(|_| EnvError::StorageUnavailable)?
+                    .as_string())
+            } else {
+                get_local_storage_item(&key)
+            }
+        }
+        .boxed_local()
+    }
 }
 
 impl Env for WebEnv {
@@ -67,72 +193,52 @@ impl Env for WebEnv {
         IN: Serialize,
         for<'de> OUT: Deserialize<'de> + 'static,
     {
-        let (parts, body) = request.into_parts();
-        let url = parts.uri.to_string();
-        let method = parts.method.as_str();
-        let headers = {
-            let mut headers = HashMap::new();
-            for (key, value) in parts.headers.iter() {
-                let key = key.as_str().to_owned();
-                let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
-                headers.entry(key).or_insert_with(Vec::new).push(value);
-            }
-            JsValue::from_serde(&headers).unwrap()
-        };
-        let body = match serde_json::to_string(&body) {
-            Ok(ref body) if body != "null" && parts.method != Method::GET => {
-                Some(JsValue::from_str(&body))
-            }
-            _ => None,
-        };
-        let mut request_options = web_sys::RequestInit::new();
-        request_options
-            .method(method)
-            .headers(&headers)
-            .body(body.as_ref());
-        let request = web_sys::Request::new_with_str_and_init(&url, &request_options)
-            .expect("request builder failed");
-        let promise = web_sys::window()
-            .expect("window is not available")
-            .fetch_with_request(&request);
-        JsFuture::from(promise)
-            .map_err(|error| {
-                EnvError::Fetch(
-                    error
-                        .dyn_into::<js_sys::Error>()
-                        .map(|error| String::from(error.message()))
-                        .unwrap_or_else(|_| "Unknown Error".to_owned()),
-                )
-            })
-            .and_then(|resp| {
-                let resp = resp.dyn_into::<web_sys::Response>().unwrap();
-                if resp.status() != 200 {
-                    Either::Right(future::err(EnvError::Fetch(format!(
-                        "Unexpected HTTP status code {}",
-                        resp.status(),
-                    ))))
-                } else {
-                    Either::Left(JsFuture::from(resp.json().unwrap()).map_err(|error| {
-                        EnvError::Fetch(
-                            error
-                                .dyn_into::<js_sys::Error>()
-                                .map(|error| String::from(error.message()))
-                                .unwrap_or_else(|_| "Unknown Error".to_owned()),
-                        )
-                    }))
-                }
-            })
-            .and_then(|resp| future::ready(resp.into_serde().map_err(EnvError::from)))
-            .boxed_local()
+        fetch::fetch(request).boxed_local()
     }
     fn get_storage<T>(key: &str) -> TryEnvFuture<Option<T>>
     where
         for<'de> T: Deserialize<'de> + 'static,
     {
-        future::ready(get_storage_sync(key)).boxed_local()
+        let raw_value = WebEnv::get_raw_storage(key);
+        async move {
+            Ok(match raw_value.await? {
+                Some(value) => Some(serde_json::from_str(&value)?),
+                None => None,
+            })
+        }
+        .boxed_local()
     }
     fn set_storage<T: Serialize>(key: &str, value: Option<&T>) -> TryEnvFuture<()> {
-        future::ready(set_storage_sync(key, value)).boxed_local()
+        let key = key.to_owned();
+        let serialized_value = match value.map(serde_json::to_string).transpose() {
+            Ok(serialized_value) => serialized_value,
+            Err(error) => return future::err(EnvError::from(error)).boxed_local(),
+        };
+        async move {
+            let mut result = write_storage_item(&key, serialized_value.as_deref()).await;
+            if let Err(error) = &result {
+                // caches make room for everything else
+                if storage_usage::is_quota_exceeded(error) && !http_cache::is_cache_key(&key) {
+                    http_cache::evict(0).await;
+                    result = write_storage_item(&key, serialized_value.as_deref()).await;
+                }
+            }
+            let is_user_data = cross_tab::SYNCED_STORAGE_KEYS.contains(&key.as_str());
+            if result.is_err() {
+                if is_user_data {
+                    storage_usage::notify_save_failure(&key);
+                }
+                return Err(EnvError::StorageUnavailable)
+            }
+            if is_user_data && !cross_tab::is_received_value(&key, serialized_value.as_deref()) {
+                cross_tab::broadcast(&cross_tab::CrossTabMessage::StorageChanged {
+                    key,
+                    value: serialized_value,
+                });
+            }
+            Ok(())
+        }
+        .boxed_local()
     }
     fn exec<F>(future: F)
     where
@@ -146,15 +252,22 @@ impl Env for WebEnv {
         Utc.timestamp(secs, nsecs as u32)
     }
     fn flush_analytics() -> EnvFuture<()> {
-        ANALYTICS.flush().boxed_local()
+        if !WebEnv::analytics_allowed() {
+            return future::ready(()).boxed_local()
+        }
+        let analytics = ANALYTICS.read().expect("analytics read failed").clone();
+        async move { analytics.flush().await }.boxed_local()
     }
     fn analytics_context(ctx: &Ctx, streaming_server: &StreamingServer) -> serde_json::Value {
-        let location_hash = web_sys::window()
-            .expect("window is not available")
-            .location()
-            .hash()
-            .expect("location hash is not available");
-        let path = location_hash.split('#').last().unwrap_or_default();
+        if !WebEnv::analytics_allowed() {
+            return serde_json::Value::Null
+        }
+        let installation_id = match installation_id() {
+            Some(installation_id) => installation_id,
+            None => return serde_json::Value::Null,
+        };
+        let location = route::location();
+        let path = location.trim_start_matches('#');
         serde_json::to_value(AnalyticsContext {
             app_type: "stremio-web".to_owned(),
             app_version: app_version.to_owned(),
@@ -170,12 +283,7 @@ impl Env for WebEnv {
                 .language()
                 .map(|language| language.to_lowercase()),
             app_language: ctx.profile.settings.interface_language.to_owned(),
-            installation_id: INSTALLATION_ID
-                .read()
-                .expect("installation id read failed")
-                .as_ref()
-                .expect("installation id not available")
-                .to_owned(),
+            installation_id,
             visit_id: VISIT_ID.to_owned(),
             path: sanitize_location_path(path).expect("sanitize location path failed"),
         })
@@ -183,44 +291,90 @@ impl Env for WebEnv {
     }
     #[cfg(debug_assertions)]
     fn log(message: String) {
-        web_sys::console::log_1(&JsValue::from(message));
+        log_debug!("core", "{}", message);
     }
 }
 
-fn get_storage_sync<T>(key: &str) -> Result<Option<T>, EnvError>
-where
-    for<'de> T: Deserialize<'de> + 'static,
-{
-    let storage = web_sys::window()
+/// Returns the persisted installation id or generates and persists a new one on the first run.
+pub async fn load_installation_id<E: Env>() -> Result<String, EnvError> {
+    if let Some(installation_id) = E::get_storage::<String>(INSTALLATION_ID_STORAGE_KEY).await? {
+        return Ok(installation_id)
+    }
+    let installation_id = new_installation_id();
+    E::set_storage(INSTALLATION_ID_STORAGE_KEY, Some(&installation_id)).await?;
+    Ok(installation_id)
+}
+
+fn installation_id() -> Option<String> {
+    INSTALLATION_ID.read().expect("installation id read failed").clone()
+}
+
+fn new_installation_id() -> String {
+    hex::encode(WebEnv::random_buffer(INSTALLATION_ID_LENGTH))
+}
+
+/// IndexedDB is used when available, `localStorage` is the fallback (e.g. in private windows).
+/// Existing `localStorage` items of `WEB_ENV_STORAGE_KEYS` are migrated to IndexedDB once, see `/js/storage.js`.
+async fn is_idb_available() -> bool {
+    let to_array = |keys: &[&str]| keys.iter().map(|key| JsValue::from_str(key)).collect::<js_sys::Array>();
+    JsFuture::from(idb_available(&to_array(WEB_ENV_STORAGE_KEYS), &to_array(WEB_ENV_STORAGE_KEY_PREFIXES)))
+        .await
+        .map_or(false, |available| available.as_bool() == Some(true))
+}
+
+fn local_storage() -> Result<web_sys::Storage, EnvError> {
+    web_sys::window()
         .expect("window is not available")
         .local_storage()
         .map_err(|_| EnvError::StorageUnavailable)?
-        .ok_or(EnvError::StorageUnavailable)?;
-    let value = storage
+        .ok_or(EnvError::StorageUnavailable)
+}
+
+fn get_local_storage_item(key: &str) -> Result<Option<String>, EnvError> {
+    local_storage()?
         .get_item(key)
-        .map_err(|_| EnvError::StorageUnavailable)?;
-    Ok(match value {
-        Some(value) => Some(serde_json::from_str(&value)?),
-        None => None,
-    })
+        .map_err(|_| EnvError::StorageUnavailable)
 }
 
-fn set_storage_sync<T: Serialize>(key: &str, value: Option<&T>) -> Result<(), EnvError> {
-    let storage = web_sys::window()
-        .expect("window is not available")
-        .local_storage()
-        .map_err(|_| EnvError::StorageUnavailable)?
-        .ok_or(EnvError::StorageUnavailable)?;
-    match value {
-        Some(value) => {
-            let serialized_value = serde_json::to_string(value)?;
-            storage
-                .set_item(key, &serialized_value)
-                .map_err(|_| EnvError::StorageUnavailable)?;
-        }
-        None => storage
-            .remove_item(key)
-            .map_err(|_| EnvError::StorageUnavailable)?,
-    };
-    Ok(())
+/// Writes to IndexedDB or `localStorage`; the error is the original JS exception.
+async fn write_storage_item(key: &str, serialized_value: Option<&str>) -> Result<(), JsValue> {
+    if is_idb_available().await {
+        let promise = match serialized_value {
+            Some(serialized_value) => idb_set_item(key, serialized_value),
+            None => idb_remove_item(key),
+        };
+        return JsFuture::from(promise).await.map(|_| ())
+    }
+    let storage = local_storage().map_err(|_| JsValue::NULL)?;
+    match serialized_value {
+        Some(serialized_value) => storage.set_item(key, serialized_value),
+        None => storage.remove_item(key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::TestEnv;
+    use futures::executor::block_on;
+
+    #[test]
+    fn installation_id_is_generated_and_persisted_on_first_run() {
+        let installation_id = block_on(load_installation_id::<TestEnv>()).expect("installation id");
+
+        assert_eq!(installation_id.len(), INSTALLATION_ID_LENGTH * 2);
+        assert_eq!(
+            TestEnv::storage_item::<String>(INSTALLATION_ID_STORAGE_KEY).as_ref(),
+            Some(&installation_id),
+        );
+    }
+
+    #[test]
+    fn persisted_installation_id_is_reused() {
+        TestEnv::set_storage_item(INSTALLATION_ID_STORAGE_KEY, &"0123456789abcdef0123");
+
+        let installation_id = block_on(load_installation_id::<TestEnv>()).expect("installation id");
+        assert_eq!(installation_id, "0123456789abcdef0123");
+        assert_eq!(block_on(load_installation_id::<TestEnv>()).expect("installation id"), installation_id);
+    }
 }
@@ -0,0 +1,159 @@
+//! Online/offline detection and the queue of API writes waiting for the connection.
+
+use futures::channel::oneshot;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+
+/// `localStorage` key of the queued API writes, the oldest first.
+/// They're read synchronously, so they aren't moved to IndexedDB, see `/js/storage.js`.
+pub const PENDING_WRITES_STORAGE_KEY: &str = "pending_api_writes";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectivityStatus {
+    pub online: bool,
+    /// API writes waiting to be replayed once the connection is restored.
+    pub queued_requests: usize,
+}
+
+/// API write persisted until it's sent, so it survives reloads while offline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingWrite {
+    /// Unique in the queue, the waiting request is resumed with the response of the write with its key.
+    pub key: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+#[derive(Default)]
+struct State {
+    // used when the queue can't be saved to `localStorage`
+    unsaved_writes: Option<VecDeque<PendingWrite>>,
+    online_waiters: Vec<oneshot::Sender<()>>,
+    subscribers: Vec<Rc<dyn Fn(ConnectivityStatus)>>,
+    listeners: Option<(Closure<dyn FnMut()>, Closure<dyn FnMut()>)>,
+}
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+pub fn is_online() -> bool {
+    web_sys::window()
+        .expect("window is not available")
+        .navigator()
+        .on_line()
+}
+
+pub fn status() -> ConnectivityStatus {
+    ConnectivityStatus {
+        online: is_online(),
+        queued_requests: pending_writes().len(),
+    }
+}
+
+/// Calls `callback` on every status change.
+pub fn subscribe(callback: impl Fn(ConnectivityStatus) + 'static) {
+    listen();
+    STATE.with(|state| state.borrow_mut().subscribers.push(Rc::new(callback)));
+}
+
+/// Resolves immediately when online, otherwise on the next `online` event.
+pub async fn wait_for_online() {
+    if is_online() {
+        return
+    }
+    listen();
+    let (sender, receiver) = oneshot::channel();
+    STATE.with(|state| state.borrow_mut().online_waiters.push(sender));
+    let _ = receiver.await;
+}
+
+/// Appends the write to the persisted queue.
+pub fn queue_write(write: PendingWrite) {
+    let mut writes = pending_writes();
+    writes.push_back(write);
+    save_pending_writes(writes);
+}
+
+/// Removes the sent (or failed) write from the queue.
+pub fn remove_pending_write(key: &str) {
+    let mut writes = pending_writes();
+    writes.retain(|write| write.key != key);
+    save_pending_writes(writes);
+}
+
+/// The queue is read from the storage every time, other tabs share it.
+pub fn pending_writes() -> VecDeque<PendingWrite> {
+    if let Some(writes) = STATE.with(|state| state.borrow().unsaved_writes.clone()) {
+        return writes
+    }
+    let serialized_writes = match local_storage().and_then(|storage| storage.get_item(PENDING_WRITES_STORAGE_KEY).ok().flatten()) {
+        Some(serialized_writes) => serialized_writes,
+        None => return VecDeque::new(),
+    };
+    serde_json::from_str(&serialized_writes).unwrap_or_else(|error| {
+        log_error!("env", "Queued API writes are invalid and were dropped: {}", error);
+        VecDeque::new()
+    })
+}
+
+fn save_pending_writes(writes: VecDeque<PendingWrite>) {
+    let saved = STATE.with(|state| state.borrow().unsaved_writes.is_none()) && match local_storage() {
+        Some(storage) if writes.is_empty() => storage.remove_item(PENDING_WRITES_STORAGE_KEY).is_ok(),
+        Some(storage) => {
+            let serialized_writes = serde_json::to_string(&writes).expect("serialize pending writes");
+            storage.set_item(PENDING_WRITES_STORAGE_KEY, &serialized_writes).is_ok()
+        }
+        None => false,
+    };
+    if !saved {
+        if STATE.with(|state| state.borrow().unsaved_writes.is_none()) {
+            log_error!("env", "Queued API writes couldn't be saved, they'll be lost on reload");
+        }
+        STATE.with(|state| state.borrow_mut().unsaved_writes = Some(writes));
+    }
+    notify_subscribers();
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+fn notify_subscribers() {
+    let status = status();
+    // callbacks are cloned so they can access `STATE` themselves
+    let subscribers = STATE.with(|state| state.borrow().subscribers.clone());
+    for subscriber in subscribers {
+        subscriber(status);
+    }
+}
+
+fn listen() {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.listeners.is_some() {
+            return
+        }
+        let window = web_sys::window().expect("window is not available");
+        let on_online = Closure::wrap(Box::new(|| {
+            let waiters = STATE.with(|state| std::mem::take(&mut state.borrow_mut().online_waiters));
+            for waiter in waiters {
+                let _ = waiter.send(());
+            }
+            notify_subscribers();
+        }) as Box<dyn FnMut()>);
+        let on_offline = Closure::wrap(Box::new(notify_subscribers) as Box<dyn FnMut()>);
+        window
+            .add_event_listener_with_callback("online", on_online.as_ref().unchecked_ref())
+            .expect("add `online` listener");
+        window
+            .add_event_listener_with_callback("offline", on_offline.as_ref().unchecked_ref())
+            .expect("add `offline` listener");
+        state.listeners = Some((on_online, on_offline));
+    });
+}
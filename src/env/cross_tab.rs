@@ -0,0 +1,119 @@
+//! Messages between tabs of the app, sent through a `BroadcastChannel`.
+//! Browsers without `BroadcastChannel` get them through `storage` events
+//! of a temporary `localStorage` item.
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use stremio_core::constants::{LIBRARY_RECENT_STORAGE_KEY, LIBRARY_STORAGE_KEY, PROFILE_STORAGE_KEY};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+
+const CHANNEL_NAME: &str = "stremio_cross_tab";
+const MESSAGE_STORAGE_KEY: &str = "__cross_tab_message";
+
+/// Storage keys whose changes are broadcasted to other tabs.
+pub const SYNCED_STORAGE_KEYS: [&str; 3] = [PROFILE_STORAGE_KEY, LIBRARY_RECENT_STORAGE_KEY, LIBRARY_STORAGE_KEY];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CrossTabMessage {
+    StorageChanged {
+        key: String,
+        /// Serialized value, `None` when the item was removed.
+        value: Option<String>,
+    },
+    /// The value was too large for the `localStorage` transport, it has to be read from the storage.
+    StorageOutdated {
+        key: String,
+    },
+    LoggedOut,
+}
+
+enum Transport {
+    BroadcastChannel(web_sys::BroadcastChannel),
+    LocalStorage,
+}
+
+thread_local! {
+    static TRANSPORT: Transport = match web_sys::BroadcastChannel::new(CHANNEL_NAME) {
+        Ok(channel) => Transport::BroadcastChannel(channel),
+        Err(_) => Transport::LocalStorage,
+    };
+    static LISTENER: RefCell<Option<Closure<dyn FnMut(JsValue)>>> = RefCell::new(None);
+    // the last storage values received from other tabs
+    static RECEIVED_VALUES: RefCell<HashMap<String, Option<String>>> = RefCell::new(HashMap::new());
+}
+
+pub fn broadcast(message: &CrossTabMessage) {
+    let serialized_message = match serde_json::to_string(message) {
+        Ok(serialized_message) => serialized_message,
+        Err(error) => return log_error!("env::cross_tab", "Cross-tab message serialization failed: {}", error),
+    };
+    TRANSPORT.with(|transport| match transport {
+        Transport::BroadcastChannel(channel) => {
+            let _ = channel.post_message(&JsValue::from(serialized_message));
+        }
+        Transport::LocalStorage => {
+            if let Ok(Some(storage)) = web_sys::window().expect("window is not available").local_storage() {
+                // other tabs get a `storage` event for each change
+                if let Err(error) = storage.set_item(MESSAGE_STORAGE_KEY, &serialized_message) {
+                    log_error!("env::cross_tab", "Cross-tab message sending failed: {:?}", error);
+                    if let CrossTabMessage::StorageChanged { key, .. } = message {
+                        return broadcast(&CrossTabMessage::StorageOutdated { key: key.clone() })
+                    }
+                }
+                let _ = storage.remove_item(MESSAGE_STORAGE_KEY);
+            }
+        }
+    });
+}
+
+/// Calls `callback` with messages from other tabs; replaces the previous callback.
+pub fn subscribe(callback: impl Fn(CrossTabMessage) + 'static) {
+    let on_message = Closure::wrap(Box::new(move |event: JsValue| {
+        let serialized_message = if let Some(event) = event.dyn_ref::<web_sys::MessageEvent>() {
+            event.data().as_string()
+        } else if let Some(event) = event.dyn_ref::<web_sys::StorageEvent>() {
+            match event.key() {
+                Some(key) if key == MESSAGE_STORAGE_KEY => event.new_value(),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        let message = serialized_message.and_then(|message| serde_json::from_str(&message).ok());
+        if let Some(message) = message {
+            callback(message);
+        }
+    }) as Box<dyn FnMut(JsValue)>);
+    TRANSPORT.with(|transport| match transport {
+        Transport::BroadcastChannel(channel) => {
+            channel.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        }
+        Transport::LocalStorage => {
+            web_sys::window()
+                .expect("window is not available")
+                .set_onstorage(Some(on_message.as_ref().unchecked_ref()));
+        }
+    });
+    LISTENER.with(|listener| listener.replace(Some(on_message)));
+}
+
+/// Remembers a storage value received from another tab, see `is_received_value`.
+pub fn value_received(key: &str, value: Option<&str>) {
+    RECEIVED_VALUES.with(|values| values.borrow_mut().insert(key.to_owned(), value.map(ToOwned::to_owned)));
+}
+
+/// Writing back a value received from another tab isn't broadcasted,
+/// otherwise the tabs would keep sending the same change to each other.
+pub fn is_received_value(key: &str, value: Option<&str>) -> bool {
+    RECEIVED_VALUES.with(|values| {
+        let mut values = values.borrow_mut();
+        let received = values.get(key).map_or(false, |received_value| received_value.as_deref() == value);
+        if received {
+            values.remove(key);
+        }
+        received
+    })
+}
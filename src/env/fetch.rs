@@ -0,0 +1,496 @@
+use futures::channel::oneshot;
+use http::header::{HeaderName, HeaderValue};
+use http::{HeaderMap, Method, Request};
+use serde::de::value::SeqDeserializer;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use stremio_core::runtime::EnvError;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use stremio_core::constants::API_URL;
+use super::{connectivity, http_cache, WebEnv};
+use connectivity::PendingWrite;
+
+pub const DEFAULT_FETCH_TIMEOUT: u32 = 30_000;
+const ERROR_BODY_SNIPPET_LENGTH: usize = 200;
+const MAX_RETRIES: u32 = 3;
+// ms, doubled with every retry
+const RETRY_BASE_DELAY: u32 = 500;
+// These endpoints store the sent state (library items with their modification time, the user, the addon list),
+// so sending a write twice leaves the same state.
+const QUEUED_API_METHODS: &[&str] = &["datastorePut", "saveUser", "addonCollectionSet"];
+const PENDING_WRITE_KEY_LENGTH: usize = 8;
+
+thread_local! {
+    // requests waiting for the response of their queued write, by the write's key
+    static WRITE_WAITERS: RefCell<HashMap<String, oneshot::Sender<Result<RawResponse, EnvError>>>> = RefCell::new(HashMap::new());
+    static REPLAYING_WRITES: Cell<bool> = Cell::new(false);
+}
+
+/// Per-request timeout in milliseconds, read from the request extensions.
+///
+/// ```ignore
+/// request.extensions_mut().insert(FetchTimeout(5_000));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FetchTimeout(pub u32);
+
+/// Sends the request and deserializes the response body.
+///
+/// All 2xx responses are successful. An empty body (e.g. 204) is deserialized from `null`,
+/// so `OUT` should be `()` or `Option<_>` for such endpoints. A body that isn't valid JSON
+/// is deserialized as a JSON string, so `OUT = String` accepts text responses.
+/// A binary body (see `is_text_content_type`) is deserialized as a byte sequence, so use `OUT = Vec<u8>`.
+///
+/// Addon resources are served through `http_cache`.
+/// The request is aborted when it times out or when the returned future is dropped.
+pub async fn fetch<IN, OUT>(request: Request<IN>) -> Result<OUT, EnvError>
+where
+    IN: Serialize,
+    for<'de> OUT: Deserialize<'de> + 'static,
+{
+    let request = PreparedRequest::new(request)?;
+    let text = match http_cache::cacheable_resource(&request) {
+        Some(resource) => http_cache::fetch(request.clone(), resource).await?,
+        None => {
+            let response = send_with_retry(&request).await?;
+            response.check_status(&request.url)?;
+            if let Some(bytes) = response.bytes {
+                return deserialize_bytes(bytes)
+                    .map_err(|error| fetch_error(&request.url, &format!("Invalid response body: {}", error)));
+            }
+            response.text
+        }
+    };
+    deserialize_body(&text)
+        .map_err(|error| fetch_error(&request.url, &format!("Invalid response body: {}", error)))
+}
+
+/// Request with the serialized body, so it can be sent repeatedly.
+#[derive(Debug, Clone)]
+pub struct PreparedRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    body: Option<String>,
+    timeout: u32,
+}
+
+impl PreparedRequest {
+    fn new<IN: Serialize>(request: Request<IN>) -> Result<Self, EnvError> {
+        let (parts, body) = request.into_parts();
+        let body = match serde_json::to_string(&body)? {
+            body if body != "null" && parts.method != Method::GET => Some(body),
+            _ => None,
+        };
+        Ok(Self {
+            url: parts.uri.to_string(),
+            timeout: parts
+                .extensions
+                .get::<FetchTimeout>()
+                .map_or(DEFAULT_FETCH_TIMEOUT, |timeout| timeout.0),
+            method: parts.method,
+            headers: parts.headers,
+            body,
+        })
+    }
+
+    fn is_idempotent(&self) -> bool {
+        matches!(self.method, Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS)
+    }
+
+    /// Library and profile sync requests are queued while offline instead of failing.
+    fn is_api_write(&self) -> bool {
+        self.method == Method::POST
+            && self.url.starts_with(API_URL.as_str())
+            && QUEUED_API_METHODS.iter().any(|method| self.url.ends_with(method))
+    }
+
+    fn to_pending_write(&self) -> PendingWrite {
+        PendingWrite {
+            key: hex::encode(WebEnv::random_buffer(PENDING_WRITE_KEY_LENGTH)),
+            url: self.url.clone(),
+            headers: self
+                .headers
+                .iter()
+                .map(|(name, value)| (name.as_str().to_owned(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+                .collect(),
+            body: self.body.clone(),
+        }
+    }
+
+    fn from_pending_write(write: &PendingWrite) -> Self {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &write.headers {
+            match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+                (Ok(name), Ok(value)) => {
+                    headers.append(name, value);
+                }
+                _ => log_warn!("env", "Invalid header '{}' of a queued API write skipped", name),
+            }
+        }
+        Self {
+            method: Method::POST,
+            url: write.url.clone(),
+            headers,
+            body: write.body.clone(),
+            timeout: DEFAULT_FETCH_TIMEOUT,
+        }
+    }
+}
+
+pub struct RawResponse {
+    pub status: u16,
+    pub etag: Option<String>,
+    pub cache_control: Option<String>,
+    /// Empty for binary bodies.
+    pub text: String,
+    /// `Some` for binary bodies.
+    pub bytes: Option<Vec<u8>>,
+}
+
+impl RawResponse {
+    pub fn check_status(&self, url: &str) -> Result<(), EnvError> {
+        if (200..300).contains(&self.status) {
+            return Ok(())
+        }
+        let snippet = self.text.chars().take(ERROR_BODY_SNIPPET_LENGTH).collect::<String>();
+        Err(fetch_error(
+            url,
+            &format!("Unexpected HTTP status code {}: {}", self.status, snippet.trim()),
+        ))
+    }
+}
+
+/// Sends the request and retries it with exponential backoff when it's idempotent
+/// and fails with a network error or a 5xx status code.
+///
+/// API writes are sent through the persisted queue when offline, see `send_api_write`.
+pub async fn send_with_retry(request: &PreparedRequest) -> Result<RawResponse, EnvError> {
+    if request.is_api_write() {
+        return send_api_write(request).await
+    }
+    let retriable = request.is_idempotent();
+    let mut attempt = 0;
+    loop {
+        let result = send(request).await;
+        let failed = match &result {
+            Ok(response) => response.status >= 500,
+            Err(_) => true,
+        };
+        if !failed || !retriable || attempt >= MAX_RETRIES || !connectivity::is_online() {
+            return result
+        }
+        sleep(backoff(attempt)).await;
+        attempt += 1;
+    }
+}
+
+/// Sends the write directly when online and nothing is queued, otherwise it's queued after the others
+/// (so the server receives the writes in order) and the response arrives once the queue reaches it.
+/// Writes are retried only after network errors; a 5xx response may come after the write was stored.
+async fn send_api_write(request: &PreparedRequest) -> Result<RawResponse, EnvError> {
+    if connectivity::is_online() && connectivity::pending_writes().is_empty() {
+        let mut attempt = 0;
+        loop {
+            match send(request).await {
+                Err(_) if !connectivity::is_online() => break,
+                Err(_) if attempt < MAX_RETRIES => {
+                    sleep(backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+    let write = request.to_pending_write();
+    let (sender, receiver) = oneshot::channel();
+    WRITE_WAITERS.with(|waiters| waiters.borrow_mut().insert(write.key.clone(), sender));
+    connectivity::queue_write(write);
+    wasm_bindgen_futures::spawn_local(replay_pending_writes());
+    receiver
+        .await
+        .unwrap_or_else(|_| Err(fetch_error(&request.url, "Queued request was dropped")))
+}
+
+/// Sends the queued API writes one by one, the oldest first, whenever the app is online.
+/// Writes queued before a reload are sent too, their responses are only logged.
+pub async fn replay_pending_writes() {
+    if REPLAYING_WRITES.with(|replaying| replaying.replace(true)) {
+        return
+    }
+    let mut attempt = 0;
+    loop {
+        let writes = connectivity::pending_writes();
+        resolve_writes_sent_elsewhere(&writes);
+        let write = match writes.front() {
+            Some(write) => write.clone(),
+            None => break,
+        };
+        connectivity::wait_for_online().await;
+        let request = PreparedRequest::from_pending_write(&write);
+        let result = match send(&request).await {
+            Err(_) if !connectivity::is_online() => continue,
+            Err(_) if attempt < MAX_RETRIES => {
+                sleep(backoff(attempt)).await;
+                attempt += 1;
+                continue
+            }
+            result => result,
+        };
+        attempt = 0;
+        connectivity::remove_pending_write(&write.key);
+        match WRITE_WAITERS.with(|waiters| waiters.borrow_mut().remove(&write.key)) {
+            Some(waiter) => {
+                let _ = waiter.send(result);
+            }
+            None => match result.and_then(|response| response.check_status(&write.url)) {
+                Ok(()) => log_info!("env", "Queued API write sent: {}", write.url),
+                Err(error) => log_error!("env", "Queued API write failed: {}", error.message()),
+            },
+        }
+    }
+    REPLAYING_WRITES.with(|replaying| replaying.set(false));
+}
+
+/// Another tab sharing the queue has sent the writes, their responses are unknown here.
+fn resolve_writes_sent_elsewhere(writes: &VecDeque<PendingWrite>) {
+    let waiters = WRITE_WAITERS.with(|waiters| {
+        let mut waiters = waiters.borrow_mut();
+        let sent_keys = waiters
+            .keys()
+            .filter(|key| writes.iter().all(|write| &write.key != *key))
+            .cloned()
+            .collect::<Vec<_>>();
+        sent_keys.into_iter().filter_map(|key| waiters.remove(&key)).collect::<Vec<_>>()
+    });
+    for waiter in waiters {
+        let _ = waiter.send(Err(EnvError::Fetch("Queued request was sent by another tab".to_owned())));
+    }
+}
+
+fn backoff(attempt: u32) -> u32 {
+    let jitter = (js_sys::Math::random() * f64::from(RETRY_BASE_DELAY)) as u32;
+    RETRY_BASE_DELAY * 2_u32.pow(attempt) + jitter
+}
+
+pub async fn sleep(ms: u32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .expect("window is not available")
+            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms as i32)
+            .expect("set timeout");
+    });
+    let _ = JsFuture::from(promise).await;
+}
+
+/// Sends the request and reads the body without checking the status code.
+/// Text bodies are read as text, the other ones as an `ArrayBuffer`.
+async fn send(request: &PreparedRequest) -> Result<RawResponse, EnvError> {
+    let url = &request.url;
+    let headers = {
+        let mut headers = HashMap::new();
+        for (key, value) in request.headers.iter() {
+            let key = key.as_str().to_owned();
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            headers.entry(key).or_insert_with(Vec::new).push(value);
+        }
+        JsValue::from_serde(&headers).unwrap()
+    };
+    let body = request.body.as_deref().map(JsValue::from_str);
+    let abort_guard = AbortGuard::new(request.timeout);
+    let mut request_options = web_sys::RequestInit::new();
+    request_options
+        .method(request.method.as_str())
+        .headers(&headers)
+        .body(body.as_ref())
+        .signal(Some(&abort_guard.controller.signal()));
+    let web_request = web_sys::Request::new_with_str_and_init(url, &request_options)
+        .map_err(|error| fetch_error(url, &js_error_message(error)))?;
+    let promise = web_sys::window()
+        .expect("window is not available")
+        .fetch_with_request(&web_request);
+
+    let response = JsFuture::from(promise)
+        .await
+        .map_err(|error| abort_guard.error(url, error))?
+        .dyn_into::<web_sys::Response>()
+        .expect("fetch resolved to a non-Response value");
+    let header = |name: &str| response.headers().get(name).ok().flatten();
+    let (text, bytes) = if is_text_content_type(header("content-type").as_deref()) {
+        let text = match response.text() {
+            Ok(promise) => JsFuture::from(promise)
+                .await
+                .map_err(|error| abort_guard.error(url, error))?
+                .as_string()
+                .unwrap_or_default(),
+            Err(error) => return Err(fetch_error(url, &js_error_message(error))),
+        };
+        (text, None)
+    } else {
+        let array_buffer = match response.array_buffer() {
+            Ok(promise) => JsFuture::from(promise)
+                .await
+                .map_err(|error| abort_guard.error(url, error))?,
+            Err(error) => return Err(fetch_error(url, &js_error_message(error))),
+        };
+        (String::new(), Some(js_sys::Uint8Array::new(&array_buffer).to_vec()))
+    };
+    abort_guard.finish();
+
+    Ok(RawResponse {
+        status: response.status(),
+        etag: header("etag"),
+        cache_control: header("cache-control"),
+        text,
+        bytes,
+    })
+}
+
+/// A missing content type is treated as text, most addons don't set it.
+fn is_text_content_type(content_type: Option<&str>) -> bool {
+    let mime_type = match content_type {
+        Some(content_type) => content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase(),
+        None => return true,
+    };
+    mime_type.is_empty()
+        || mime_type.starts_with("text/")
+        || mime_type.ends_with("+json")
+        || mime_type.ends_with("+xml")
+        || matches!(
+            mime_type.as_str(),
+            "application/json" | "application/javascript" | "application/xml" | "application/x-www-form-urlencoded"
+        )
+}
+
+fn deserialize_bytes<OUT>(bytes: Vec<u8>) -> Result<OUT, serde_json::Error>
+where
+    for<'de> OUT: Deserialize<'de>,
+{
+    OUT::deserialize(SeqDeserializer::new(bytes.into_iter()))
+}
+
+fn deserialize_body<OUT>(text: &str) -> Result<OUT, serde_json::Error>
+where
+    for<'de> OUT: Deserialize<'de>,
+{
+    if text.trim().is_empty() {
+        return serde_json::from_value(serde_json::Value::Null);
+    }
+    serde_json::from_str(text).or_else(|json_error| {
+        serde_json::from_value(serde_json::Value::String(text.to_owned())).map_err(|_| json_error)
+    })
+}
+
+pub fn fetch_error(url: &str, message: &str) -> EnvError {
+    EnvError::Fetch(format!("{} ({})", message, url))
+}
+
+fn js_error_message(error: JsValue) -> String {
+    error
+        .dyn_into::<js_sys::Error>()
+        .map(|error| String::from(error.message()))
+        .unwrap_or_else(|_| "Unknown Error".to_owned())
+}
+
+// ------ AbortGuard ------
+
+/// Owns the request's `AbortController` and the timeout that triggers it.
+/// Dropping the guard before `finish` aborts the request.
+struct AbortGuard {
+    controller: web_sys::AbortController,
+    timeout: u32,
+    timeout_id: Option<i32>,
+    _on_timeout: Closure<dyn FnMut()>,
+    finished: bool,
+}
+
+impl AbortGuard {
+    fn new(timeout: u32) -> Self {
+        let controller = web_sys::AbortController::new().expect("AbortController is not available");
+        let on_timeout = {
+            let controller = controller.clone();
+            Closure::wrap(Box::new(move || controller.abort()) as Box<dyn FnMut()>)
+        };
+        let timeout_id = web_sys::window()
+            .expect("window is not available")
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                on_timeout.as_ref().unchecked_ref(),
+                timeout as i32,
+            )
+            .ok();
+        Self {
+            controller,
+            timeout,
+            timeout_id,
+            _on_timeout: on_timeout,
+            finished: false,
+        }
+    }
+
+    fn error(&self, url: &str, error: JsValue) -> EnvError {
+        if self.controller.signal().aborted() {
+            fetch_error(url, &format!("Request timed out after {} ms", self.timeout))
+        } else {
+            fetch_error(url, &js_error_message(error))
+        }
+    }
+
+    fn finish(mut self) {
+        self.finished = true;
+    }
+}
+
+impl Drop for AbortGuard {
+    fn drop(&mut self) {
+        if let Some(timeout_id) = self.timeout_id {
+            web_sys::window()
+                .expect("window is not available")
+                .clear_timeout_with_handle(timeout_id);
+        }
+        if !self.finished {
+            self.controller.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_and_text_bodies_are_read_as_text() {
+        assert!(is_text_content_type(None));
+        assert!(is_text_content_type(Some("application/json; charset=utf-8")));
+        assert!(is_text_content_type(Some("application/vnd.api+json")));
+        assert!(is_text_content_type(Some("text/vtt")));
+        assert!(!is_text_content_type(Some("application/octet-stream")));
+        assert!(!is_text_content_type(Some("image/png")));
+    }
+
+    #[test]
+    fn binary_bodies_are_deserialized_as_bytes() {
+        let bytes = deserialize_bytes::<Vec<u8>>(vec![0, 159, 255]).unwrap();
+        assert_eq!(bytes, vec![0, 159, 255]);
+        assert!(deserialize_bytes::<String>(vec![0]).is_err());
+    }
+
+    #[test]
+    fn queued_writes_are_sent_unchanged() {
+        let request = Request::post(format!("{}api/datastorePut", API_URL.as_str()))
+            .header("content-type", "application/json")
+            .body(serde_json::json!({ "changes": [] }))
+            .unwrap();
+        let request = PreparedRequest::new(request).unwrap();
+        assert!(request.is_api_write());
+
+        let write = request.to_pending_write();
+        let replayed_request = PreparedRequest::from_pending_write(&serde_json::from_str(&serde_json::to_string(&write).unwrap()).unwrap());
+        assert_eq!(replayed_request.method, Method::POST);
+        assert_eq!(replayed_request.url, request.url);
+        assert_eq!(replayed_request.headers, request.headers);
+        assert_eq!(replayed_request.body, request.body);
+    }
+}
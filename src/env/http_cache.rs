@@ -0,0 +1,436 @@
+//! Cache for addon resources (manifests, catalogs, metas and streams).
+//!
+//! Responses are kept in memory and in the persistent storage (see `WebEnv::set_storage`).
+//! `Cache-Control: max-age` overrides the default TTL of the resource, `no-store` disables caching
+//! and `no-cache` forces revalidation. Stale same-origin entries with an `ETag` are revalidated
+//! with `If-None-Match`; a stale entry is also used when the request fails.
+//! The memory tier keeps `MAX_MEMORY_ENTRIES`, the persistent tier is limited by `max_persistent_size`;
+//! least recently used entries are evicted first from both, manifests last from the persistent one.
+//! The index is saved at most once per `INDEX_SAVE_DELAY` and the storage quota is checked
+//! at most once per `QUOTA_CHECK_INTERVAL`.
+//! _Note:_ `If-None-Match` isn't a CORS-safelisted header, addons would get a preflight request
+//! for every revalidation, so cross-origin entries are downloaded again instead.
+
+use http::header::{HeaderValue, IF_NONE_MATCH};
+use http::Method;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use stremio_core::runtime::{Env, EnvError};
+use super::fetch::{send_with_retry, sleep, PreparedRequest, RawResponse};
+use super::{storage_usage, WebEnv};
+
+pub const STORAGE_KEY_PREFIX: &str = "http_cache:";
+pub const INDEX_STORAGE_KEY: &str = "http_cache_index";
+const MAX_MEMORY_ENTRIES: usize = 200;
+// bytes, IndexedDB
+const MAX_PERSISTENT_SIZE: u64 = 20 * 1024 * 1024;
+// bytes, the `localStorage` fallback has a quota around 5 MB shared with the user data
+const MAX_LOCAL_STORAGE_SIZE: u64 = 1024 * 1024;
+// the eviction frees space down to this part of `max_persistent_size`
+const EVICTION_TARGET_RATIO: f64 = 0.8;
+// ms
+const INDEX_SAVE_DELAY: u32 = 2_000;
+// seconds
+const QUOTA_CHECK_INTERVAL: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Resource {
+    Manifest,
+    Catalog,
+    Meta,
+    Stream,
+}
+
+impl Resource {
+    /// Default time to live in seconds.
+    fn ttl(self) -> u64 {
+        match self {
+            Self::Manifest => 6 * 60 * 60,
+            Self::Catalog => 60 * 60,
+            Self::Meta => 12 * 60 * 60,
+            Self::Stream => 5 * 60,
+        }
+    }
+}
+
+/// Only `GET` requests for addon resources are cached.
+pub fn cacheable_resource(request: &PreparedRequest) -> Option<Resource> {
+    if request.method != Method::GET {
+        return None
+    }
+    let url = url::Url::parse(&request.url).ok()?;
+    let path = url.path();
+    if path.ends_with("/manifest.json") {
+        return Some(Resource::Manifest)
+    }
+    // `/{resource}/{type}/{id}.json` or `/{resource}/{type}/{id}/{extra}.json`
+    path.trim_end_matches(".json").rsplit('/').skip(2).take(2).find_map(|segment| match segment {
+        "catalog" => Some(Resource::Catalog),
+        "meta" => Some(Resource::Meta),
+        "stream" => Some(Resource::Stream),
+        _ => None,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    body: String,
+    etag: Option<String>,
+    // seconds since the Unix epoch
+    stored_at: i64,
+    max_age: u64,
+    revalidate: bool,
+}
+
+impl Entry {
+    fn is_fresh(&self, now: i64) -> bool {
+        !self.revalidate && now - self.stored_at < self.max_age as i64
+    }
+}
+
+#[derive(Default)]
+struct CachePolicy {
+    max_age: Option<u64>,
+    no_store: bool,
+    no_cache: bool,
+}
+
+impl CachePolicy {
+    fn parse(cache_control: Option<&str>) -> Self {
+        let mut policy = Self::default();
+        for directive in cache_control.unwrap_or_default().split(',') {
+            let directive = directive.trim().to_lowercase();
+            match directive.split_once('=') {
+                Some(("max-age", max_age)) => policy.max_age = max_age.trim_matches('"').parse().ok(),
+                _ if directive == "no-store" || directive == "private" => policy.no_store = true,
+                _ if directive == "no-cache" => policy.no_cache = true,
+                _ => (),
+            }
+        }
+        policy
+    }
+}
+
+/// Persistent entry metadata used for the eviction.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexEntry {
+    // bytes
+    size: u64,
+    // seconds since the Unix epoch
+    last_used: i64,
+    resource: Resource,
+}
+
+struct MemoryEntry {
+    entry: Entry,
+    // seconds since the Unix epoch
+    last_used: i64,
+}
+
+thread_local! {
+    static MEMORY: RefCell<HashMap<String, MemoryEntry>> = RefCell::new(HashMap::new());
+    // URL -> entry, loaded from the storage on the first use
+    static INDEX: RefCell<Option<HashMap<String, IndexEntry>>> = RefCell::new(None);
+    static INDEX_SAVE_SCHEDULED: Cell<bool> = Cell::new(false);
+    // (checked at, near quota)
+    static QUOTA_CHECK: Cell<Option<(i64, bool)>> = Cell::new(None);
+}
+
+/// Cache entries are evicted instead of being retried when the storage is full.
+pub fn is_cache_key(key: &str) -> bool {
+    key.starts_with(STORAGE_KEY_PREFIX) || key == INDEX_STORAGE_KEY
+}
+
+pub async fn fetch(mut request: PreparedRequest, resource: Resource) -> Result<String, EnvError> {
+    let url = request.url.clone();
+    let now = WebEnv::now().timestamp();
+    let cached = get_entry(&url, now).await;
+    if cached.is_some() {
+        touch(&url, now).await;
+    }
+    if let Some(entry) = &cached {
+        if entry.is_fresh(now) {
+            return Ok(entry.body.clone())
+        }
+        if let Some(etag) = entry.etag.as_deref().and_then(|etag| HeaderValue::from_str(etag).ok()) {
+            if is_same_origin(&url) {
+                request.headers.insert(IF_NONE_MATCH, etag);
+            }
+        }
+    }
+
+    let response = match send_with_retry(&request).await {
+        Ok(response) => response,
+        Err(error) => return cached.map(|entry| entry.body).ok_or(error),
+    };
+    match cached {
+        Some(mut entry) if response.status == 304 => {
+            let policy = CachePolicy::parse(response.cache_control.as_deref());
+            entry.stored_at = now;
+            entry.max_age = policy.max_age.unwrap_or_else(|| resource.ttl());
+            entry.revalidate = policy.no_cache;
+            let body = entry.body.clone();
+            put_entry(url, entry, resource).await;
+            Ok(body)
+        }
+        _ => {
+            response.check_status(&url)?;
+            let RawResponse { etag, cache_control, text, .. } = response;
+            let policy = CachePolicy::parse(cache_control.as_deref());
+            if !policy.no_store {
+                put_entry(url, Entry {
+                    body: text.clone(),
+                    etag,
+                    stored_at: now,
+                    max_age: policy.max_age.unwrap_or_else(|| resource.ttl()),
+                    revalidate: policy.no_cache,
+                }, resource).await;
+            }
+            Ok(text)
+        }
+    }
+}
+
+/// Conditional requests don't need a CORS preflight only for the app's own origin.
+fn is_same_origin(url: &str) -> bool {
+    let location_origin = match web_sys::window().map(|window| window.location().origin()) {
+        Some(Ok(origin)) => origin,
+        _ => return false,
+    };
+    url::Url::parse(url).map_or(false, |url| url.origin().ascii_serialization() == location_origin)
+}
+
+/// The `localStorage` fallback (e.g. in private windows) gets a smaller cache.
+async fn max_persistent_size() -> u64 {
+    if super::is_idb_available().await {
+        MAX_PERSISTENT_SIZE
+    } else {
+        MAX_LOCAL_STORAGE_SIZE
+    }
+}
+
+/// Removes all cached responses from both tiers.
+pub async fn clear() -> Result<(), EnvError> {
+    MEMORY.with(|memory| memory.borrow_mut().clear());
+    INDEX.with(|index| index.replace(Some(HashMap::new())));
+    for key in WebEnv::storage_keys().await? {
+        if is_cache_key(&key) {
+            WebEnv::set_storage::<()>(&key, None).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes least recently used persistent entries until the cache fits into `target_size` bytes.
+pub async fn evict(target_size: u64) {
+    load_index().await;
+    let urls = INDEX.with(|index| {
+        index.borrow().as_ref().map_or_else(Vec::new, |index| eviction_plan(index, target_size))
+    });
+    for url in urls {
+        if let Err(error) = WebEnv::set_storage::<()>(&storage_key(&url), None).await {
+            log_error!("env::http_cache", "HTTP cache eviction failed: {}", error.message());
+            continue
+        }
+        MEMORY.with(|memory| memory.borrow_mut().remove(&url));
+        INDEX.with(|index| index.borrow_mut().as_mut().map(|index| index.remove(&url)));
+    }
+    save_index().await;
+}
+
+/// URLs of the entries to remove so the rest fits into `target_size` bytes,
+/// least recently used first and manifests last.
+fn eviction_plan(index: &HashMap<String, IndexEntry>, target_size: u64) -> Vec<String> {
+    let mut entries = index.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(_, entry)| (entry.resource == Resource::Manifest, entry.last_used));
+    let mut cache_size = entries.iter().map(|(_, entry)| entry.size).sum::<u64>();
+    entries
+        .into_iter()
+        .take_while(|(_, entry)| {
+            let evicted = cache_size > target_size;
+            cache_size = cache_size.saturating_sub(entry.size);
+            evicted
+        })
+        .map(|(url, _)| url.clone())
+        .collect()
+}
+
+async fn load_index() {
+    if INDEX.with(|index| index.borrow().is_some()) {
+        return
+    }
+    let stored_index = WebEnv::get_storage::<HashMap<String, IndexEntry>>(INDEX_STORAGE_KEY)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    INDEX.with(|index| index.borrow_mut().get_or_insert(stored_index));
+}
+
+async fn save_index() {
+    let index = INDEX.with(|index| index.borrow().clone().unwrap_or_default());
+    if let Err(error) = WebEnv::set_storage(INDEX_STORAGE_KEY, Some(&index)).await {
+        log_error!("env::http_cache", "HTTP cache index write failed: {}", error.message());
+    }
+}
+
+/// Saves the index once the writes in quick succession (e.g. a catalog page of metas) are done.
+fn schedule_index_save() {
+    if INDEX_SAVE_SCHEDULED.with(|scheduled| scheduled.replace(true)) {
+        return
+    }
+    WebEnv::exec(async {
+        sleep(INDEX_SAVE_DELAY).await;
+        INDEX_SAVE_SCHEDULED.with(|scheduled| scheduled.set(false));
+        save_index().await;
+    });
+}
+
+/// `storage_usage::is_near_quota` is cached, the estimate is too slow to be requested per write.
+async fn is_near_quota(now: i64) -> bool {
+    if let Some((checked_at, near_quota)) = QUOTA_CHECK.with(Cell::get) {
+        if now - checked_at < QUOTA_CHECK_INTERVAL {
+            return near_quota
+        }
+    }
+    let near_quota = storage_usage::is_near_quota().await;
+    QUOTA_CHECK.with(|quota_check| quota_check.set(Some((now, near_quota))));
+    near_quota
+}
+
+/// Updates the last use in memory; it's persisted with the next write.
+async fn touch(url: &str, now: i64) {
+    load_index().await;
+    INDEX.with(|index| {
+        if let Some(entry) = index.borrow_mut().as_mut().and_then(|index| index.get_mut(url)) {
+            entry.last_used = now;
+        }
+    });
+}
+
+fn storage_key(url: &str) -> String {
+    format!("{}{}", STORAGE_KEY_PREFIX, url)
+}
+
+async fn get_entry(url: &str, now: i64) -> Option<Entry> {
+    if let Some(entry) = get_memory_entry(url, now) {
+        return Some(entry)
+    }
+    let entry = WebEnv::get_storage::<Entry>(&storage_key(url)).await.ok().flatten()?;
+    insert_to_memory(url.to_owned(), entry.clone(), now);
+    Some(entry)
+}
+
+fn get_memory_entry(url: &str, now: i64) -> Option<Entry> {
+    MEMORY.with(|memory| {
+        let mut memory = memory.borrow_mut();
+        let memory_entry = memory.get_mut(url)?;
+        memory_entry.last_used = now;
+        Some(memory_entry.entry.clone())
+    })
+}
+
+async fn put_entry(url: String, entry: Entry, resource: Resource) {
+    load_index().await;
+    let size = ((url.len() + entry.body.len()) * 2) as u64;
+    let cache_size = INDEX.with(|index| {
+        index.borrow().iter().flatten().filter(|(entry_url, _)| **entry_url != url).map(|(_, entry)| entry.size).sum::<u64>()
+    });
+    let max_size = max_persistent_size().await;
+    if is_near_quota(entry.stored_at).await {
+        // the space is needed for the user data
+        evict(cache_size / 2).await;
+    } else if cache_size + size > max_size {
+        let target_size = (max_size as f64 * EVICTION_TARGET_RATIO) as u64;
+        evict(target_size.saturating_sub(size)).await;
+    }
+    match WebEnv::set_storage(&storage_key(&url), Some(&entry)).await {
+        Ok(()) => {
+            let index_entry = IndexEntry { size, last_used: entry.stored_at, resource };
+            INDEX.with(|index| index.borrow_mut().get_or_insert_with(HashMap::new).insert(url.clone(), index_entry));
+            schedule_index_save();
+        }
+        Err(error) => {
+            log_error!("env::http_cache", "HTTP cache write failed: {}", error.message());
+        }
+    }
+    let now = entry.stored_at;
+    insert_to_memory(url, entry, now);
+}
+
+fn insert_to_memory(url: String, entry: Entry, now: i64) {
+    MEMORY.with(|memory| {
+        let mut memory = memory.borrow_mut();
+        if memory.len() >= MAX_MEMORY_ENTRIES && !memory.contains_key(&url) {
+            let least_recently_used_url = memory
+                .iter()
+                .min_by_key(|(_, memory_entry)| memory_entry.last_used)
+                .map(|(url, _)| url.clone());
+            if let Some(least_recently_used_url) = least_recently_used_url {
+                memory.remove(&least_recently_used_url);
+            }
+        }
+        memory.insert(url, MemoryEntry { entry, last_used: now });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(entries: &[(&str, u64, i64, Resource)]) -> HashMap<String, IndexEntry> {
+        entries
+            .iter()
+            .map(|(url, size, last_used, resource)| {
+                (url.to_string(), IndexEntry { size: *size, last_used: *last_used, resource: *resource })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn least_recently_used_entries_are_evicted_first() {
+        let index = index(&[
+            ("catalog", 100, 3, Resource::Catalog),
+            ("old_meta", 100, 1, Resource::Meta),
+            ("meta", 100, 2, Resource::Meta),
+        ]);
+        assert_eq!(eviction_plan(&index, 150), vec!["old_meta".to_owned(), "meta".to_owned()]);
+    }
+
+    #[test]
+    fn manifests_are_evicted_last() {
+        let index = index(&[
+            ("manifest", 100, 1, Resource::Manifest),
+            ("stream", 100, 2, Resource::Stream),
+        ]);
+        assert_eq!(eviction_plan(&index, 100), vec!["stream".to_owned()]);
+        assert_eq!(eviction_plan(&index, 0), vec!["stream".to_owned(), "manifest".to_owned()]);
+    }
+
+    #[test]
+    fn nothing_is_evicted_when_the_cache_fits() {
+        let index = index(&[("meta", 100, 1, Resource::Meta)]);
+        assert!(eviction_plan(&index, 100).is_empty());
+        assert!(eviction_plan(&HashMap::new(), 0).is_empty());
+    }
+
+    #[test]
+    fn least_recently_used_entries_leave_the_memory_first() {
+        let entry = |stored_at| Entry { body: String::new(), etag: None, stored_at, max_age: 0, revalidate: false };
+        for time in 0..MAX_MEMORY_ENTRIES as i64 {
+            insert_to_memory(time.to_string(), entry(time), time);
+        }
+        // the oldest entry is read again
+        let now = MAX_MEMORY_ENTRIES as i64;
+        assert!(get_memory_entry("0", now).is_some());
+        insert_to_memory("new".to_owned(), entry(now), now);
+
+        assert!(get_memory_entry("0", now).is_some());
+        assert!(get_memory_entry("1", now).is_none());
+        assert!(get_memory_entry("new", now).is_some());
+    }
+}
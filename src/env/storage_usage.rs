@@ -0,0 +1,94 @@
+//! Storage usage and quota monitoring.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use stremio_core::runtime::EnvError;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use super::{idb_usage, is_idb_available, local_storage, storage_estimate};
+
+/// Caches are evicted when the origin uses more than this part of its quota.
+const NEAR_QUOTA_RATIO: f64 = 0.9;
+
+#[derive(Debug, Clone, Default)]
+pub struct StorageUsage {
+    /// Approximate size of stored items in bytes, the largest first.
+    pub items: Vec<(String, u64)>,
+    /// Browser estimate for the whole origin in bytes.
+    pub usage: Option<u64>,
+    pub quota: Option<u64>,
+}
+
+thread_local! {
+    static SAVE_FAILURE_SUBSCRIBERS: RefCell<Vec<Rc<dyn Fn(String)>>> = RefCell::new(Vec::new());
+}
+
+pub async fn storage_usage() -> Result<StorageUsage, EnvError> {
+    let mut items = if is_idb_available().await {
+        let usage = JsFuture::from(idb_usage())
+            .await
+            .map_err(|_| EnvError::StorageUnavailable)?;
+        js_sys::Array::from(&usage)
+            .iter()
+            .filter_map(|item| {
+                let item = js_sys::Array::from(&item);
+                Some((item.get(0).as_string()?, item.get(1).as_f64()? as u64))
+            })
+            .collect::<Vec<_>>()
+    } else {
+        let storage = local_storage()?;
+        let length = storage.length().map_err(|_| EnvError::StorageUnavailable)?;
+        (0..length)
+            .filter_map(|index| {
+                let key = storage.key(index).ok()??;
+                let value = storage.get_item(&key).ok()??;
+                let size = ((key.len() + value.len()) * 2) as u64;
+                Some((key, size))
+            })
+            .collect()
+    };
+    items.sort_by(|(_, size_a), (_, size_b)| size_b.cmp(size_a));
+    let (usage, quota) = estimate().await.unwrap_or_default();
+    Ok(StorageUsage { items, usage, quota })
+}
+
+pub async fn is_near_quota() -> bool {
+    match estimate().await {
+        Some((Some(usage), Some(quota))) if quota > 0 => usage as f64 / quota as f64 > NEAR_QUOTA_RATIO,
+        _ => false,
+    }
+}
+
+async fn estimate() -> Option<(Option<u64>, Option<u64>)> {
+    let estimate = JsFuture::from(storage_estimate()).await.ok()?;
+    if estimate.is_null() {
+        return None
+    }
+    let field = |name: &str| {
+        js_sys::Reflect::get(&estimate, &JsValue::from_str(name))
+            .ok()
+            .and_then(|value| value.as_f64())
+            .map(|value| value as u64)
+    };
+    Some((field("usage"), field("quota")))
+}
+
+pub fn is_quota_exceeded(error: &JsValue) -> bool {
+    let name = js_sys::Reflect::get(error, &JsValue::from_str("name"))
+        .ok()
+        .and_then(|name| name.as_string());
+    // Firefox reports `NS_ERROR_DOM_QUOTA_REACHED` for `localStorage`
+    matches!(name.as_deref(), Some("QuotaExceededError") | Some("NS_ERROR_DOM_QUOTA_REACHED"))
+}
+
+/// Calls `callback` with the key of user data that couldn't be saved.
+pub fn subscribe_save_failures(callback: impl Fn(String) + 'static) {
+    SAVE_FAILURE_SUBSCRIBERS.with(|subscribers| subscribers.borrow_mut().push(Rc::new(callback)));
+}
+
+pub(super) fn notify_save_failure(key: &str) {
+    let subscribers = SAVE_FAILURE_SUBSCRIBERS.with(|subscribers| subscribers.borrow().clone());
+    for subscriber in subscribers {
+        subscriber(key.to_owned());
+    }
+}
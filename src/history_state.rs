@@ -0,0 +1,100 @@
+use indexmap::IndexMap;
+use seed::prelude::*;
+use std::cell::Cell;
+
+const MAX_STATES: usize = 50;
+/// `history.state` property with the id of the history entry.
+const ENTRY_ID_KEY: &str = "historyEntryId";
+
+thread_local! {
+    static NEXT_ENTRY_ID: Cell<u32> = Cell::new(0);
+}
+
+/// Page states (scroll offsets, selected items, ...) remembered per history entry, see `current_entry_id`.
+/// Pages restore them when the entry is opened again with the back or forward button,
+/// see `route::take_history_traversal`.
+pub struct HistoryStates<T> {
+    states: IndexMap<String, T>,
+}
+
+impl<T> Default for HistoryStates<T> {
+    fn default() -> Self {
+        Self { states: IndexMap::new() }
+    }
+}
+
+impl<T> HistoryStates<T> {
+    /// The least recently saved states are dropped over `MAX_STATES`.
+    pub fn save(&mut self, entry_id: &str, state: T) {
+        self.states.shift_remove(entry_id);
+        if self.states.len() == MAX_STATES {
+            self.states.shift_remove_index(0);
+        }
+        self.states.insert(entry_id.to_owned(), state);
+    }
+
+    pub fn get(&self, entry_id: &str) -> Option<&T> {
+        self.states.get(entry_id)
+    }
+}
+
+/// Id of the current history entry, kept in `history.state`.
+/// An entry without it gets a new one, so the same URL opened twice doesn't share the state.
+/// The id survives reloads; Seed falls back to the current URL for a non-string `history.state`.
+pub fn current_entry_id() -> String {
+    let history = match window().history() {
+        Ok(history) => history,
+        Err(_) => return Url::current().to_string(),
+    };
+    let state = history.state().unwrap_or(JsValue::NULL);
+    let stored_id = js_sys::Reflect::get(&state, &JsValue::from_str(ENTRY_ID_KEY))
+        .ok()
+        .and_then(|entry_id| entry_id.as_string());
+    if let Some(entry_id) = stored_id {
+        return entry_id
+    }
+    let entry_id = format!(
+        "{:x}-{:x}",
+        js_sys::Date::now() as u64,
+        NEXT_ENTRY_ID.with(|next_id| next_id.replace(next_id.get() + 1)),
+    );
+    let state = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(&state, &JsValue::from_str(ENTRY_ID_KEY), &JsValue::from_str(&entry_id));
+    if let Err(error) = history.replace_state(&state, "") {
+        log_error!("history_state", "History entry id saving failed: {:?}", error);
+    }
+    entry_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn states_are_restored_per_entry() {
+        let mut history_states = HistoryStates::default();
+        history_states.save("a", 1);
+        history_states.save("b", 2);
+        history_states.save("a", 3);
+
+        assert_eq!(history_states.get("a"), Some(&3));
+        assert_eq!(history_states.get("b"), Some(&2));
+        assert_eq!(history_states.get("c"), None);
+    }
+
+    #[test]
+    fn least_recently_saved_states_are_dropped() {
+        let mut history_states = HistoryStates::default();
+        for entry_id in 0..MAX_STATES {
+            history_states.save(&entry_id.to_string(), entry_id);
+        }
+        // saving again makes the state the most recent one
+        history_states.save("0", 0);
+        history_states.save("new", MAX_STATES);
+
+        assert_eq!(history_states.states.len(), MAX_STATES);
+        assert_eq!(history_states.get("0"), Some(&0));
+        assert_eq!(history_states.get("1"), None);
+        assert_eq!(history_states.get("new"), Some(&MAX_STATES));
+    }
+}
@@ -12,13 +12,37 @@
     unused_variables,
 )]
 
+#[macro_use]
+mod logger;
+
+mod analytics;
 mod basic_layout;
+mod bug_report;
+mod core_inspector;
+mod crash;
 mod multi_select;
 mod page;
 mod styles;
 mod env;
+mod history_state;
+mod relay;
+mod remote_control;
+mod route;
+mod storage_schema;
+mod toast;
+#[cfg(test)]
+mod test_env;
 
 use env::WebEnv;
+use route::Route;
+use storage_schema::{RawStorage, StorageRecovery};
+use toast::{Toast, Toasts, ToastAction, ToastId, Severity};
+use core_inspector::CoreInspector;
+use bug_report::BugReport;
+use analytics::{AnalyticsConsent, AnalyticsEvent, ANALYTICS_CONSENT_STORAGE_KEY, ANALYTICS_FLUSH_INTERVAL};
+use env::connectivity::{self, ConnectivityStatus};
+use env::cross_tab::{self, CrossTabMessage};
+use env::storage_usage;
 use futures::compat::Future01CompatExt;
 use seed::{prelude::*, *};
 use seed_styles::pc;
@@ -32,10 +56,10 @@ use stremio_core::models::library_with_filters::{LibraryWithFilters, NotRemovedF
 use stremio_core::models::addon_details::AddonDetails;
 use stremio_core::runtime::{Update, Effect, Effects, UpdateWithCtx};
 use stremio_core::runtime::{Env, EnvError};
-use stremio_core::runtime::msg::{Msg as CoreMsg, Action, ActionCtx, Event, CtxStorageResponse};
+use stremio_core::runtime::msg::{Msg as CoreMsg, Action, ActionCtx, Event, Internal, CtxStorageResponse};
 use stremio_core::types::addon::DescriptorPreview;
 use stremio_core::types::resource::MetaItemPreview;
-use stremio_core::types::profile::Profile;
+use stremio_core::types::profile::{Profile, UID};
 use stremio_core::types::library::LibraryBucket;
 use stremio_core::constants::{
     LIBRARY_RECENT_STORAGE_KEY, LIBRARY_STORAGE_KEY, PROFILE_STORAGE_KEY,
@@ -44,17 +68,7 @@ use stremio_derive::Model;
 use seed_hooks::{*, topo::nested as view};
 use std::ops::Deref;
 
-// ---- url parts ----
-
-const DISCOVER: &str = "discover";
-const DETAIL: &str = "metadetails";
-const INTRO: &str = "intro";
-const LIBRARY: &str = "library";
-const PLAYER: &str = "player";
-const ADDONS: &str = "addons";
-const SEARCH: &str = "search";
-const SETTINGS: &str = "settings";
-const TEST_LINKS: &str = "test_links";
+const APP_ELEMENT_ID: &str = "app";
 
 // ------ ------
 //    Actions
@@ -64,6 +78,9 @@ const TEST_LINKS: &str = "test_links";
 pub enum Actions {
     UpdateCoreModel(Rc<CoreMsg>),
     ToggleFullscreen,
+    SetAnalyticsConsent(AnalyticsConsent),
+    EmitAnalyticsEvent(AnalyticsEvent),
+    ShowToast(Toast),
 }
 #[derive(Clone, Copy)]
 pub enum Events {
@@ -79,47 +96,42 @@ pub enum Events {
 fn init(url: Url, orders: &mut impl Orders<Msg>) -> Model {
     styles::global::init();
 
-    let root_base_url = url.to_hash_base_url();
+    let root_base_url = route::base_url(&url);
+    let msg_sender = orders.msg_sender();
+    connectivity::subscribe(move |status| msg_sender(Some(Msg::ConnectivityChanged(status))));
+    let msg_sender = orders.msg_sender();
+    cross_tab::subscribe(move |message| msg_sender(Some(Msg::CrossTabMessageReceived(message))));
+    let msg_sender = orders.msg_sender();
+    storage_usage::subscribe_save_failures(move |key| msg_sender(Some(Msg::StorageSaveFailed(key))));
     orders
         .subscribe(Msg::UrlChanged)
-        .subscribe(|action: Actions| {
-            match action {
-                Actions::UpdateCoreModel(core_msg) => Msg::CoreMsg(core_msg),
-                Actions::ToggleFullscreen => Msg::ToggleFullscreen,
-            }
-        })
+        .subscribe(action_msg)
         .subscribe(Msg::CoreMsg)
         .stream(streams::window_event(Ev::Click, |_| Msg::WindowClicked))
+        .stream(streams::interval(ANALYTICS_FLUSH_INTERVAL, || Msg::FlushAnalytics))
         .notify(subs::UrlChanged(url))
-        .perform_cmd(async { Msg::CtxStorageResponse(futures::try_join!(
-            WebEnv::get_storage::<Profile>(PROFILE_STORAGE_KEY),
-            WebEnv::get_storage::<LibraryBucket>(LIBRARY_RECENT_STORAGE_KEY),
-            WebEnv::get_storage::<LibraryBucket>(LIBRARY_STORAGE_KEY),
-        ))});
+        .perform_cmd(async {
+            // the installation id has to be ready before the analytics context is used
+            WebEnv::init().await;
+            Msg::CtxStorageResponse(storage_schema::load_ctx_storage::<CoreEnv>().await)
+        })
+        .perform_cmd(async {
+            Msg::AnalyticsConsentLoaded(WebEnv::get_storage(ANALYTICS_CONSENT_STORAGE_KEY).await)
+        });
         // @TODO listen for `fullscreenchange` once it's implemented in Safari
 
     let (core_model, effects) = CoreModel::new();
     handle_core_effects(effects, orders);
+    Model::new(Context::new(core_model, root_base_url))
+}
 
-    Model {
-        context: Context {
-            core_model,
-            ctx_loaded: false,
-            root_base_url,
-            menu_visible: false,
-            fullscreen: false,
-        },
-        page_id: None,
-        // ---- page models ----
-        board_model: None,
-        detail_model: None,
-        intro_model: None,
-        library_model: None,
-        player_model: None,
-        discover_model: None,
-        addons_model: None,
-        search_model: None,
-        settings_model: None,
+fn action_msg(action: Actions) -> Msg {
+    match action {
+        Actions::UpdateCoreModel(core_msg) => Msg::CoreMsg(core_msg),
+        Actions::ToggleFullscreen => Msg::ToggleFullscreen,
+        Actions::SetAnalyticsConsent(consent) => Msg::SetAnalyticsConsent(consent),
+        Actions::EmitAnalyticsEvent(event) => Msg::EmitAnalyticsEvent(event),
+        Actions::ShowToast(toast) => Msg::ShowToast(toast),
     }
 }
 
@@ -141,16 +153,67 @@ pub struct Model {
     addons_model: Option<page::addons::Model>,
     search_model: Option<page::search::Model>,
     settings_model: Option<page::settings::Model>,
+    remote_model: Option<page::remote::Model>,
+    logs_model: Option<page::logs::Model>,
+    inspector_model: Option<page::inspector::Model>,
+    bug_report_model: Option<page::bug_report::Model>,
+}
+
+impl Model {
+    fn new(context: Context) -> Self {
+        Self {
+            context,
+            page_id: None,
+            // ---- page models ----
+            board_model: None,
+            detail_model: None,
+            intro_model: None,
+            library_model: None,
+            player_model: None,
+            discover_model: None,
+            addons_model: None,
+            search_model: None,
+            settings_model: None,
+            remote_model: None,
+            logs_model: None,
+            inspector_model: None,
+            bug_report_model: None,
+        }
+    }
 }
 
 // ------ Context ------
 
 pub struct Context {
     core_model: CoreModel,
+    core_inspector: CoreInspector,
     ctx_loaded: bool,
     root_base_url: Url,
     menu_visible: bool,
     fullscreen: bool,
+    connectivity: ConnectivityStatus,
+    analytics_consent: Option<AnalyticsConsent>,
+    analytics_consent_loaded: bool,
+    storage_save_failed: bool,
+    toasts: Toasts,
+}
+
+impl Context {
+    fn new(core_model: CoreModel, root_base_url: Url) -> Self {
+        Self {
+            core_model,
+            core_inspector: CoreInspector::default(),
+            ctx_loaded: false,
+            root_base_url,
+            menu_visible: false,
+            fullscreen: false,
+            connectivity: connectivity::status(),
+            analytics_consent: None,
+            analytics_consent_loaded: false,
+            storage_save_failed: false,
+            toasts: Toasts::default(),
+        }
+    }
 }
 
 // ------ PageId ------
@@ -167,13 +230,23 @@ pub enum PageId {
     NotFound,
     Search,
     Settings,
+    Remote,
     TestLinks,
+    Logs,
+    Inspector,
+    BugReport,
 }
 
 // ------ CoreModel  ------
 
-#[derive(Model)]
-#[model(WebEnv)]
+/// `Env` used by `CoreModel`; native tests run it against the in-memory `TestEnv`.
+#[cfg(not(test))]
+type CoreEnv = WebEnv;
+#[cfg(test)]
+type CoreEnv = test_env::TestEnv;
+
+#[derive(Model, Clone)]
+#[model(CoreEnv)]
 struct CoreModel {
     ctx: Ctx,
     catalog: CatalogWithFilters<MetaItemPreview>,
@@ -188,7 +261,7 @@ struct CoreModel {
 
 impl CoreModel {
     fn new() -> (Self, Effects) {
-        let (streaming_server, effects) = StreamingServer::new::<WebEnv>(&Profile::default());
+        let (streaming_server, effects) = StreamingServer::new::<CoreEnv>(&Profile::default());
         (
             Self {
                 ctx: Ctx::default(),
@@ -204,6 +277,70 @@ impl CoreModel {
             effects
         )        
     }
+
+    /// Merges a storage change made in another tab and updates the models derived from the ctx.
+    /// The returned flag is `true` when the user has been logged out there.
+    fn merge_storage_change(&mut self, key: &str, value: Option<&str>) -> Result<(Effects, bool), serde_json::Error> {
+        match key {
+            PROFILE_STORAGE_KEY => {
+                let profile = value.map(serde_json::from_str::<Profile>).transpose()?.unwrap_or_default();
+                let logged_out = self.ctx.profile.auth.is_some() && profile.auth.is_none();
+                let uid_changed = profile.uid() != self.ctx.profile.uid();
+                if uid_changed {
+                    self.ctx.library = LibraryBucket::new(profile.uid(), vec![]);
+                }
+                self.ctx.profile = profile;
+                let mut effects = self.update(&CoreMsg::Internal(Internal::ProfileChanged));
+                if uid_changed {
+                    effects = effects.join(self.library_changed());
+                }
+                Ok((effects, logged_out))
+            }
+            LIBRARY_RECENT_STORAGE_KEY | LIBRARY_STORAGE_KEY => {
+                if let Some(bucket) = value.map(serde_json::from_str::<LibraryBucket>).transpose()? {
+                    // buckets of another user are ignored until the profile change arrives
+                    if bucket.uid == self.ctx.library.uid {
+                        self.ctx.library.merge_bucket(bucket);
+                        return Ok((self.library_changed(), false))
+                    }
+                }
+                Ok((Effects::none().unchanged(), false))
+            }
+            _ => Ok((Effects::none().unchanged(), false)),
+        }
+    }
+
+    /// Resets the user data without API calls; the logout has been done in another tab.
+    fn log_out_locally(&mut self) -> Effects {
+        self.ctx.profile = Profile::default();
+        self.ctx.library = LibraryBucket::new(self.ctx.profile.uid(), vec![]);
+        self.update(&CoreMsg::Internal(Internal::ProfileChanged))
+            .join(self.library_changed())
+    }
+
+    // the library has already been persisted by the other tab
+    fn library_changed(&mut self) -> Effects {
+        self.update(&CoreMsg::Internal(Internal::LibraryChanged(true)))
+    }
+
+    /// Applies the profile and library buckets read from the storage.
+    fn load_ctx(&mut self, (profile, recent_bucket, other_bucket): CtxStorageResponse) -> Effects {
+        let mut effects = Effects::none().unchanged();
+        if let Some(profile) = profile {
+            let (streaming_server, streaming_server_effects) = StreamingServer::new::<CoreEnv>(&profile);
+            effects = effects.join(streaming_server_effects);
+            self.streaming_server = streaming_server;
+            self.ctx.profile = profile;
+        }
+        self.ctx.library.uid = self.ctx.profile.uid();
+        if let Some(recent_bucket) = recent_bucket {
+            self.ctx.library.merge_bucket(recent_bucket);
+        };
+        if let Some(other_bucket) = other_bucket {
+            self.ctx.library.merge_bucket(other_bucket);
+        };
+        effects
+    }
 }
 
 
@@ -214,34 +351,49 @@ impl CoreModel {
 struct_urls!();
 impl<'a> Urls<'a> {
     pub fn root(self) -> Url {
-        self.base_url()
+        Route::Board.to_url(&self.base_url())
     }
     pub fn discover_urls(self) -> page::discover::Urls<'a> {
-        page::discover::Urls::new(self.base_url().add_hash_path_part(DISCOVER))
+        page::discover::Urls::new(self.base_url())
     }
     pub fn detail_urls(self) -> page::detail::Urls<'a> {
-        page::detail::Urls::new(self.base_url().add_hash_path_part(DETAIL))
+        page::detail::Urls::new(self.base_url())
     }
     pub fn intro(self) -> Url {
-        self.base_url().add_hash_path_part(INTRO)
+        page::intro::Urls::new(self.base_url()).root()
+    }
+    pub fn intro_urls(self) -> page::intro::Urls<'a> {
+        page::intro::Urls::new(self.base_url())
     }
     pub fn library(self) -> Url {
-        self.base_url().add_hash_path_part(LIBRARY)
+        page::library::Urls::new(self.base_url()).root()
     }
     pub fn player(self) -> page::player::Urls<'a> {
-        page::player::Urls::new(self.base_url().add_hash_path_part(PLAYER))
+        page::player::Urls::new(self.base_url())
     }
     pub fn addons_urls(self) -> page::addons::Urls<'a> {
-        page::addons::Urls::new(self.base_url().add_hash_path_part(ADDONS))
+        page::addons::Urls::new(self.base_url())
     }
     pub fn search_urls(self) -> page::search::Urls<'a> {
-        page::search::Urls::new(self.base_url().add_hash_path_part(SEARCH))
+        page::search::Urls::new(self.base_url())
     }
     pub fn settings(self) -> Url {
-        self.base_url().add_hash_path_part(SETTINGS)
+        Route::Settings.to_url(&self.base_url())
+    }
+    pub fn remote_urls(self) -> page::remote::Urls<'a> {
+        page::remote::Urls::new(self.base_url())
     }
     pub fn test_links(self) -> Url {
-        self.base_url().add_hash_path_part(TEST_LINKS)
+        Route::TestLinks.to_url(&self.base_url())
+    }
+    pub fn logs(self) -> Url {
+        Route::Logs.to_url(&self.base_url())
+    }
+    pub fn inspector(self) -> Url {
+        Route::Inspector.to_url(&self.base_url())
+    }
+    pub fn bug_report(self) -> Url {
+        Route::BugReport.to_url(&self.base_url())
     }
 }
 
@@ -251,7 +403,7 @@ impl<'a> Urls<'a> {
 
 #[allow(clippy::enum_variant_names, clippy::large_enum_variant)]
 pub enum Msg {
-    CtxStorageResponse(Result<CtxStorageResponse, EnvError>),
+    CtxStorageResponse(Result<(CtxStorageResponse, Vec<StorageRecovery>), EnvError>),
     UrlChanged(subs::UrlChanged),
     CoreMsg(Rc<CoreMsg>),
     HandleEffectMsg(Rc<CoreMsg>),
@@ -268,97 +420,170 @@ pub enum Msg {
     AddonsMsg(page::addons::Msg),
     SearchMsg(page::search::Msg),
     SettingsMsg(page::settings::Msg),
+    RemoteMsg(page::remote::Msg),
+    LogsMsg(page::logs::Msg),
+    InspectorMsg(page::inspector::Msg),
+    BugReportMsg(page::bug_report::Msg),
     ToggleFullscreen,
+    ConnectivityChanged(ConnectivityStatus),
+    CrossTabMessageReceived(CrossTabMessage),
+    StorageSaveFailed(String),
+    DismissStorageSaveError,
+    AnalyticsConsentLoaded(Result<Option<AnalyticsConsent>, EnvError>),
+    SetAnalyticsConsent(AnalyticsConsent),
+    AnalyticsConsentSaved(Result<(), EnvError>),
+    EmitAnalyticsEvent(AnalyticsEvent),
+    FlushAnalytics,
+    ReportProblem,
     Logout,
+    ShowToast(Toast),
+    DismissToast(ToastId),
+    RunToastAction(ToastId),
 }
 
 fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
-        Msg::CtxStorageResponse(Ok((profile, recent_bucket, other_bucket))) => {
-            let core_model = &mut model.context.core_model;
-            let ctx = &mut core_model.ctx;
-
-            if let Some(profile) = profile {
-                let (streaming_server, effects) =  StreamingServer::new::<WebEnv>(&profile);
-                handle_core_effects(effects, orders);
-                core_model.streaming_server = streaming_server;
-                ctx.profile = profile;
+        Msg::CtxStorageResponse(Ok((ctx_storage, recoveries))) => {
+            for recovery in &recoveries {
+                match &recovery.backup_key {
+                    Some(backup_key) => log_warn!("core", "Storage entry '{}' was backed up to '{}': {}", recovery.key, backup_key, recovery.description),
+                    None => log_warn!("core", "Storage entry '{}' was recovered without a backup: {}", recovery.key, recovery.description),
+                }
             }
-            ctx.library.uid = ctx.profile.uid();
-            if let Some(recent_bucket) = recent_bucket {
-                ctx.library.merge_bucket(recent_bucket);
-            };
-            if let Some(other_bucket) = other_bucket {
-                ctx.library.merge_bucket(other_bucket);
-            };
+            if !recoveries.is_empty() {
+                orders.send_msg(Msg::ShowToast(
+                    Toast::new(Severity::Warning, "Part of your profile or library was invalid and has been reset.")
+                        .with_button("Details", ToastAction::OpenUrl(Urls::new(&model.context.root_base_url).logs()))
+                ));
+            }
+            let effects = model.context.core_model.load_ctx(ctx_storage.clone());
+            model.context.core_inspector.record(
+                core_inspector::Source::Storage,
+                core_inspector::Change::CtxLoaded(ctx_storage),
+                true,
+                &model.context.core_model,
+            );
+            handle_core_effects(effects, orders);
             model.context.ctx_loaded = true;
             orders.notify(Events::CtxLoaded);
+            // the route opened before the profile was known is guarded again
+            let url = Url::current();
+            if Route::parse(&url).map_or(false, |route| route.requires_auth()) && model.context.core_model.ctx.profile.auth.is_none() {
+                orders.notify(subs::UrlChanged(url));
+            }
         }
         Msg::CtxStorageResponse(Err(error)) => {
-            log!(error.message());
+            log_error!("core", "Ctx loading failed: {}", error.message());
+            orders.send_msg(Msg::ShowToast(
+                Toast::new(Severity::Error, "Your profile and library couldn't be loaded.")
+                    .with_button("Reload", ToastAction::Reload)
+            ));
         }
-        Msg::UrlChanged(subs::UrlChanged(mut url)) => {
-            let page_id = match url.next_hash_path_part() {
-                None => page::board::init(
+        Msg::UrlChanged(subs::UrlChanged(url)) => {
+            let history_traversal = route::take_history_traversal();
+            let url = route::redirect_hash_url(url);
+            let context = &model.context;
+            let authenticated = context.core_model.ctx.profile.auth.is_some();
+            let page_id = match guard_route(Route::parse(&url), context.ctx_loaded, authenticated, &context.root_base_url) {
+                Some(Route::Board) => page::board::init(
                     url,
                     &mut model.board_model,
                     &mut model.context,
                     &mut orders.proxy(Msg::BoardMsg),
                 ),
-                Some(DISCOVER) => page::discover::init(
+                Some(Route::Discover(resource_request)) => page::discover::init(
                     url,
+                    resource_request,
+                    history_traversal,
                     &mut model.discover_model,
+                    &model.context,
                     &mut orders.proxy(Msg::DiscoverMsg),
                 ),
-                Some(DETAIL) => page::detail::init(
+                Some(Route::Detail { type_name, id, video_id }) => page::detail::init(
                     url,
+                    type_name,
+                    id,
+                    video_id,
                     &mut model.detail_model,
                     &mut model.context,
                     &mut orders.proxy(Msg::DetailMsg),
                 ),
-                Some(INTRO) => page::intro::init(
+                Some(Route::Intro { form_type, return_to }) => page::intro::init(
                     url,
+                    form_type,
+                    return_to.map(|return_to| *return_to),
                     &mut model.intro_model,
                     &mut model.context,
                     &mut orders.proxy(Msg::IntroMsg),
                 ),
-                Some(LIBRARY) => page::library::init(
+                Some(Route::Library(library_request)) => page::library::init(
                     url,
+                    library_request,
+                    history_traversal,
                     &mut model.library_model,
                     &mut model.context,
                     &mut orders.proxy(Msg::LibraryMsg),
                 ),
-                Some(PLAYER) => page::player::init(
+                Some(Route::Player { stream, video_path }) => page::player::init(
                     url,
+                    stream,
+                    video_path,
                     &mut model.player_model,
                     &mut model.context,
                     &mut orders.proxy(Msg::PlayerMsg),
                 ),
-                Some(ADDONS) => page::addons::init(
+                Some(Route::Addons(addon_request)) => page::addons::init(
                     url,
+                    addon_request,
                     &mut model.addons_model,
                     &mut model.context,
                     &mut orders.proxy(Msg::AddonsMsg),
                 ),
-                Some(SEARCH) => page::search::init(
+                Some(Route::Search(search_query)) => page::search::init(
                     url,
+                    search_query,
                     &mut model.search_model,
                     &mut orders.proxy(Msg::SearchMsg),
                 ),
-                Some(SETTINGS) => page::settings::init(
+                Some(Route::Settings) => page::settings::init(
                     url,
                     &mut model.settings_model,
                     &mut model.context,
                     &mut orders.proxy(Msg::SettingsMsg),
                 ),
-                Some(TEST_LINKS) => Some(PageId::TestLinks),
+                Some(Route::Remote(code)) => page::remote::init(
+                    url,
+                    code,
+                    &mut model.remote_model,
+                    &mut orders.proxy(Msg::RemoteMsg),
+                ),
+                Some(Route::TestLinks) => Some(PageId::TestLinks),
+                Some(Route::Logs) => page::logs::init(
+                    url,
+                    &mut model.logs_model,
+                    &mut orders.proxy(Msg::LogsMsg),
+                ),
+                Some(Route::Inspector) => page::inspector::init(
+                    url,
+                    &mut model.inspector_model,
+                    &mut model.context,
+                    &mut orders.proxy(Msg::InspectorMsg),
+                ),
+                // bug reports are imported only in development builds
+                Some(Route::BugReport) if cfg!(debug_assertions) => page::bug_report::init(
+                    url,
+                    &mut model.bug_report_model,
+                    &mut orders.proxy(Msg::BugReportMsg),
+                ),
                 _ => None,
             };
             model.page_id = page_id.or(Some(PageId::NotFound));
             orders.notify(Events::PageChanged(model.page_id.unwrap()));
+            orders.send_msg(Msg::EmitAnalyticsEvent(AnalyticsEvent::page_view(model.page_id.unwrap())));
         }
         Msg::CoreMsg(core_msg) => {
             let effects = model.context.core_model.update(&core_msg);
+            model.context.core_inspector.record_core_msg(&core_msg, effects.has_changed, &model.context.core_model);
             if !effects.has_changed {
                 orders.skip();
                 return
@@ -366,7 +591,16 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
             handle_core_effects(effects, orders);
         }
         Msg::HandleEffectMsg(core_msg) => {
+            model.context.core_inspector.effect_received(&core_msg);
+            let context = &model.context;
+            if let Some(toast) = Toast::from_core_msg(&core_msg, &context.root_base_url, context.connectivity.online) {
+                orders.send_msg(Msg::ShowToast(toast));
+            }
+            if let Some(event) = AnalyticsEvent::from_core_msg(&core_msg) {
+                orders.send_msg(Msg::EmitAnalyticsEvent(event));
+            }
             if let CoreMsg::Event(Event::UserLoggedOut {..}) = core_msg.as_ref() {
+                cross_tab::broadcast(&CrossTabMessage::LoggedOut);
                 orders.request_url(Urls::new(&model.context.root_base_url).root());
             }
             orders.notify(core_msg);
@@ -472,6 +706,31 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
                 );
             }
         }
+        Msg::RemoteMsg(page_msg) => {
+            if let Some(page_model) = &mut model.remote_model {
+                page::remote::update(page_msg, page_model, &mut orders.proxy(Msg::RemoteMsg));
+            }
+        }
+        Msg::LogsMsg(page_msg) => {
+            if let Some(page_model) = &mut model.logs_model {
+                page::logs::update(page_msg, page_model, &mut orders.proxy(Msg::LogsMsg));
+            }
+        }
+        Msg::InspectorMsg(page_msg) => {
+            if let Some(page_model) = &mut model.inspector_model {
+                page::inspector::update(
+                    page_msg,
+                    page_model,
+                    &mut model.context,
+                    &mut orders.proxy(Msg::InspectorMsg),
+                );
+            }
+        }
+        Msg::BugReportMsg(page_msg) => {
+            if let Some(page_model) = &mut model.bug_report_model {
+                page::bug_report::update(page_msg, page_model, &model.context, &mut orders.proxy(Msg::BugReportMsg));
+            }
+        }
         Msg::ToggleFullscreen => {
             if model.context.fullscreen {
                 close_fullscreen();
@@ -481,11 +740,136 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
                 model.context.fullscreen = true;
             }
         }
+        Msg::ConnectivityChanged(status) => {
+            model.context.connectivity = status;
+        }
+        Msg::StorageSaveFailed(key) => {
+            log_error!("env", "Storage entry '{}' couldn't be saved", key);
+            model.context.storage_save_failed = true;
+        }
+        Msg::DismissStorageSaveError => {
+            model.context.storage_save_failed = false;
+        }
+        Msg::CrossTabMessageReceived(CrossTabMessage::StorageChanged { key, value }) => {
+            cross_tab::value_received(&key, value.as_deref());
+            let uid = model.context.core_model.ctx.profile.uid();
+            let result = model.context.core_model.merge_storage_change(&key, value.as_deref());
+            model.context.core_inspector.record(
+                core_inspector::Source::OtherTab,
+                core_inspector::Change::StorageChanged { key: key.clone(), value },
+                result.is_ok(),
+                &model.context.core_model,
+            );
+            match result {
+                Ok((effects, logged_out)) => {
+                    handle_core_effects(effects, orders);
+                    if logged_out {
+                        notify_logged_out_locally(uid, &model.context.root_base_url, orders);
+                    }
+                }
+                Err(error) => log_error!("env", "Storage change of '{}' from another tab is invalid: {}", key, error),
+            }
+        }
+        Msg::CrossTabMessageReceived(CrossTabMessage::StorageOutdated { key }) => {
+            orders.skip().perform_cmd(async move {
+                match WebEnv::get_raw_storage(&key).await {
+                    Ok(value) => Some(Msg::CrossTabMessageReceived(CrossTabMessage::StorageChanged { key, value })),
+                    Err(error) => {
+                        log_error!("env", "Storage entry '{}' changed in another tab couldn't be read: {}", key, error.message());
+                        None
+                    }
+                }
+            });
+        }
+        Msg::CrossTabMessageReceived(CrossTabMessage::LoggedOut) => {
+            if model.context.core_model.ctx.profile.auth.is_some() {
+                let uid = model.context.core_model.ctx.profile.uid();
+                let effects = model.context.core_model.log_out_locally();
+                model.context.core_inspector.record(
+                    core_inspector::Source::OtherTab,
+                    core_inspector::Change::LoggedOutLocally,
+                    true,
+                    &model.context.core_model,
+                );
+                handle_core_effects(effects, orders);
+                notify_logged_out_locally(uid, &model.context.root_base_url, orders);
+            }
+        }
+        Msg::AnalyticsConsentLoaded(Ok(consent)) => {
+            WebEnv::set_analytics_allowed(AnalyticsConsent::is_granted(consent));
+            model.context.analytics_consent = consent;
+            model.context.analytics_consent_loaded = true;
+            if AnalyticsConsent::is_granted(consent) {
+                orders.perform_cmd(crash::report_last_crash());
+            }
+        }
+        Msg::AnalyticsConsentLoaded(Err(error)) => {
+            log_error!("analytics", "Analytics consent loading failed: {}", error.message());
+        }
+        Msg::SetAnalyticsConsent(consent) => {
+            WebEnv::set_analytics_allowed(AnalyticsConsent::is_granted(Some(consent)));
+            model.context.analytics_consent = Some(consent);
+            model.context.analytics_consent_loaded = true;
+            if AnalyticsConsent::is_granted(Some(consent)) {
+                orders.perform_cmd(crash::report_last_crash());
+            }
+            orders.perform_cmd(async move {
+                Msg::AnalyticsConsentSaved(WebEnv::set_storage(ANALYTICS_CONSENT_STORAGE_KEY, Some(&consent)).await)
+            });
+        }
+        Msg::AnalyticsConsentSaved(Ok(())) => (),
+        Msg::AnalyticsConsentSaved(Err(error)) => {
+            log_error!("analytics", "Analytics consent saving failed: {}", error.message());
+        }
+        Msg::EmitAnalyticsEvent(event) => {
+            orders.skip();
+            let core_model = &model.context.core_model;
+            WebEnv::emit_analytics(&event, &core_model.ctx, &core_model.streaming_server);
+        }
+        Msg::FlushAnalytics => {
+            orders.skip().perform_cmd(WebEnv::flush_analytics());
+        }
+        Msg::ReportProblem => {
+            let route = Route::parse(&Url::current());
+            let bug_report = BugReport::new(&model.context, route.as_ref(), WebEnv::app_version(), WebEnv::shell_version());
+            match serde_json::to_string_pretty(&bug_report) {
+                Ok(bug_report_json) => download_text(&bug_report.file_name(), &bug_report_json),
+                Err(error) => log_error!("core", "Bug report serialization failed: {}", error),
+            }
+        }
         Msg::Logout => {
             orders.notify(Actions::UpdateCoreModel(Rc::new(CoreMsg::Action(Action::Ctx(
                 ActionCtx::Logout
             )))));
         }
+        Msg::ShowToast(toast) => {
+            let (id, timeout) = model.context.toasts.push(toast);
+            if let Some(timeout) = timeout {
+                orders.perform_cmd(cmds::timeout(timeout, move || Msg::DismissToast(id)));
+            }
+        }
+        Msg::DismissToast(id) => {
+            if model.context.toasts.dismiss(id).is_none() {
+                orders.skip();
+            }
+        }
+        Msg::RunToastAction(id) => {
+            let button = model.context.toasts.dismiss(id).and_then(|toast| toast.button);
+            match button.map(|button| button.action) {
+                Some(ToastAction::OpenUrl(url)) => {
+                    orders.request_url(url);
+                }
+                Some(ToastAction::Notify(action)) => {
+                    orders.notify(*action);
+                }
+                Some(ToastAction::Reload) => {
+                    if let Err(error) = window().location().reload() {
+                        log_error!("core", "Reload failed: {:?}", error);
+                    }
+                }
+                None => (),
+            }
+        }
     }
 }
 
@@ -504,6 +888,27 @@ fn handle_core_effects(effects: Effects, orders: &mut impl Orders<Msg>) {
     }
 }
 
+/// Pages get the same event as after a logout in this tab, without it being broadcasted again.
+fn notify_logged_out_locally(uid: UID, root_base_url: &Url, orders: &mut impl Orders<Msg>) {
+    orders
+        .notify(Rc::new(CoreMsg::Event(Event::UserLoggedOut { uid })))
+        .request_url(Urls::new(root_base_url).root());
+}
+
+/// Redirects unauthenticated users from routes requiring authentication to the login form.
+/// Routes aren't guarded until the ctx is loaded, see `Msg::CtxStorageResponse`.
+fn guard_route(route: Option<Route>, ctx_loaded: bool, authenticated: bool, root_base_url: &Url) -> Option<Route> {
+    match route {
+        Some(route) if route.requires_auth() && ctx_loaded && !authenticated => {
+            let login_route = route.login_redirect();
+            // going back shouldn't return to the guarded route
+            route::replace_url(&login_route.to_url(root_base_url));
+            Some(login_route)
+        }
+        route => route,
+    }
+}
+
 // ------ ------
 //     View
 // ------ ------
@@ -597,11 +1002,48 @@ fn view(model: &Model) -> Node<Msg> {
                             vec![]
                         }
                     }
+                    PageId::Remote => {
+                        if let Some(page_model) = &model.remote_model {
+                            page::remote::view(page_model)
+                                .map_msg(Msg::RemoteMsg)
+                                .into_nodes()
+                        } else {
+                            vec![]
+                        }
+                    }
+                    PageId::Logs => {
+                        if let Some(page_model) = &model.logs_model {
+                            page::logs::view(page_model)
+                                .map_msg(Msg::LogsMsg)
+                                .into_nodes()
+                        } else {
+                            vec![]
+                        }
+                    }
+                    PageId::Inspector => {
+                        if let Some(page_model) = &model.inspector_model {
+                            page::inspector::view(page_model, &model.context)
+                                .map_msg(Msg::InspectorMsg)
+                                .into_nodes()
+                        } else {
+                            vec![]
+                        }
+                    }
+                    PageId::BugReport => {
+                        if let Some(page_model) = &model.bug_report_model {
+                            page::bug_report::view(page_model)
+                                .map_msg(Msg::BugReportMsg)
+                                .into_nodes()
+                        } else {
+                            vec![]
+                        }
+                    }
                     PageId::TestLinks => page::test_links::view(&model.context.root_base_url).into_nodes(),
                     PageId::NotFound => page::not_found::view().into_nodes(),
                 }
             })
-        ]
+        ],
+        toast::view(&model.context.toasts),
     ]
 }
 
@@ -611,7 +1053,10 @@ fn view(model: &Model) -> Node<Msg> {
 
 #[wasm_bindgen(start)]
 pub fn start() {
-    App::start("app", init, update, view);
+    crash::install_panic_hook();
+    route::init_mode(APP_ELEMENT_ID);
+    route::listen_to_history_traversal();
+    App::start(APP_ELEMENT_ID, init, update, view);
 }
 
 // ------ ------
@@ -625,3 +1070,132 @@ extern "C" {
     #[wasm_bindgen(js_name = closeFullscreen)]
     fn close_fullscreen();
 }
+
+#[wasm_bindgen(module = "/js/download.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = downloadText)]
+    fn download_text(file_name: &str, text: &str);
+}
+
+// ------ ------
+//     Tests
+// ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use stremio_core::types::profile::Settings;
+    use stremio_core::models::library_with_filters::Selected as LibraryWithFiltersSelected;
+    use stremio_core::runtime::msg::ActionLoad;
+    use test_env::{TestEnv, dispatch, meta_preview, new_core_model, run_effects};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn guarded_route_is_opened_after_login() {
+        route::set_mode(route::RoutingMode::Hash);
+        let root_base_url = route::base_url(&Url::current());
+        let library = Some(Route::Library(None));
+
+        // the ctx isn't loaded yet, so the auth is unknown
+        assert_eq!(guard_route(library.clone(), false, false, &root_base_url), library);
+        assert_eq!(guard_route(library.clone(), true, true, &root_base_url), library);
+        assert_eq!(guard_route(Some(Route::Settings), true, false, &root_base_url), Some(Route::Settings));
+
+        let login_route = Route::Library(None).login_redirect();
+        assert_eq!(guard_route(library.clone(), true, false, &root_base_url), Some(login_route.clone()));
+        // the history entry is replaced by the login form
+        assert_eq!(Route::parse(&Url::current()), Some(login_route));
+
+        // `page::intro` opens `return_to` on `Event::UserAuthenticated`
+        let return_to = match Route::parse(&Url::current()) {
+            Some(Route::Intro { return_to, .. }) => return_to.map(|return_to| *return_to),
+            _ => None,
+        };
+        assert_eq!(return_to, library);
+        assert_eq!(guard_route(return_to, true, true, &root_base_url), library);
+    }
+
+    #[test]
+    fn streaming_server_settings_are_requested_on_init() {
+        new_core_model();
+        assert!(TestEnv::fetch_requests().iter().any(|request| request.url.ends_with("settings")));
+    }
+
+    #[test]
+    fn ctx_is_loaded_from_storage() {
+        let mut profile = Profile::default();
+        profile.settings.binge_watching = !Settings::default().binge_watching;
+        TestEnv::set_storage_item(PROFILE_STORAGE_KEY, &profile);
+
+        let mut core_model = new_core_model();
+        let (ctx_storage, _) = block_on(storage_schema::load_ctx_storage::<TestEnv>()).expect("readable storage");
+        let effects = core_model.load_ctx(ctx_storage);
+        run_effects(&mut core_model, effects);
+
+        assert_eq!(core_model.ctx.profile.settings.binge_watching, profile.settings.binge_watching);
+        assert_eq!(core_model.ctx.library.uid, profile.uid());
+    }
+
+    #[test]
+    fn added_library_item_is_persisted() {
+        let mut core_model = new_core_model();
+        dispatch(&mut core_model, ActionCtx::AddToLibrary(meta_preview("tt0000001")));
+
+        assert!(core_model.ctx.library.items.contains_key("tt0000001"));
+        let recent_bucket = TestEnv::storage_item::<LibraryBucket>(LIBRARY_RECENT_STORAGE_KEY)
+            .expect("persisted library bucket");
+        assert!(recent_bucket.items.contains_key("tt0000001"));
+    }
+
+    #[test]
+    fn library_changes_from_another_tab_are_merged() {
+        let mut other_tab_core_model = new_core_model();
+        dispatch(&mut other_tab_core_model, ActionCtx::AddToLibrary(meta_preview("tt0000001")));
+        let recent_bucket = TestEnv::storage_item::<LibraryBucket>(LIBRARY_RECENT_STORAGE_KEY)
+            .expect("persisted library bucket");
+
+        let mut core_model = new_core_model();
+        let selected_library = LibraryWithFiltersSelected { request: page::library::default_library_request() };
+        test_env::update(&mut core_model, &CoreMsg::Action(Action::Load(ActionLoad::LibraryWithFilters(selected_library))));
+        let value = serde_json::to_string(&recent_bucket).unwrap();
+        let (effects, logged_out) = core_model
+            .merge_storage_change(LIBRARY_RECENT_STORAGE_KEY, Some(&value))
+            .expect("valid storage change");
+        run_effects(&mut core_model, effects);
+
+        assert!(!logged_out);
+        assert!(core_model.ctx.library.items.contains_key("tt0000001"));
+        // the Library page reads the derived catalog
+        assert!(core_model.library.catalog.iter().any(|item| item.id == "tt0000001"));
+    }
+
+    #[test]
+    fn logout_in_another_tab_resets_the_library() {
+        let mut core_model = new_core_model();
+        dispatch(&mut core_model, ActionCtx::AddToLibrary(meta_preview("tt0000001")));
+        let selected_library = LibraryWithFiltersSelected { request: page::library::default_library_request() };
+        test_env::update(&mut core_model, &CoreMsg::Action(Action::Load(ActionLoad::LibraryWithFilters(selected_library))));
+        assert!(!core_model.library.catalog.is_empty());
+
+        let effects = core_model.log_out_locally();
+        run_effects(&mut core_model, effects);
+
+        assert!(core_model.ctx.library.items.is_empty());
+        assert!(core_model.library.catalog.is_empty());
+    }
+
+    #[test]
+    fn updated_settings_are_persisted() {
+        let mut core_model = new_core_model();
+        let mut settings = core_model.ctx.profile.settings.to_owned();
+        settings.binge_watching = !settings.binge_watching;
+        dispatch(&mut core_model, ActionCtx::UpdateSettings(settings.clone()));
+
+        assert_eq!(core_model.ctx.profile.settings.binge_watching, settings.binge_watching);
+        let profile = TestEnv::storage_item::<Profile>(PROFILE_STORAGE_KEY).expect("persisted profile");
+        assert_eq!(profile.settings.binge_watching, settings.binge_watching);
+    }
+}
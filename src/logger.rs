@@ -0,0 +1,272 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt;
+use stremio_core::runtime::Env;
+use crate::CoreEnv;
+
+pub const LOG_LEVEL_STORAGE_KEY: &str = "log_level";
+const RING_BUFFER_CAPACITY: usize = 1000;
+
+#[cfg(debug_assertions)]
+const DEFAULT_LEVEL: Level = Level::Debug;
+#[cfg(not(debug_assertions))]
+const DEFAULT_LEVEL: Level = Level::Info;
+
+/// Logs an entry with the target (`env`, `core`, `player`, `page::*`, ...) and a `format!` message.
+///
+/// ```ignore
+/// log_error!("env", "HTTP cache write failed: {}", error.message());
+/// ```
+macro_rules! log_error {
+    ($target:expr, $($arg:tt)+) => {
+        if $crate::logger::enabled($crate::logger::Level::Error) {
+            $crate::logger::log($crate::logger::Level::Error, $target, format!($($arg)+))
+        }
+    };
+}
+
+macro_rules! log_warn {
+    ($target:expr, $($arg:tt)+) => {
+        if $crate::logger::enabled($crate::logger::Level::Warn) {
+            $crate::logger::log($crate::logger::Level::Warn, $target, format!($($arg)+))
+        }
+    };
+}
+
+macro_rules! log_info {
+    ($target:expr, $($arg:tt)+) => {
+        if $crate::logger::enabled($crate::logger::Level::Info) {
+            $crate::logger::log($crate::logger::Level::Info, $target, format!($($arg)+))
+        }
+    };
+}
+
+macro_rules! log_debug {
+    ($target:expr, $($arg:tt)+) => {
+        if $crate::logger::enabled($crate::logger::Level::Debug) {
+            $crate::logger::log($crate::logger::Level::Debug, $target, format!($($arg)+))
+        }
+    };
+}
+
+macro_rules! log_trace {
+    ($target:expr, $($arg:tt)+) => {
+        if $crate::logger::enabled($crate::logger::Level::Trace) {
+            $crate::logger::log($crate::logger::Level::Trace, $target, format!($($arg)+))
+        }
+    };
+}
+
+// ------ Level ------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub const ALL: [Level; 5] = [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        }
+    }
+
+    pub fn parse(level: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|candidate| candidate.as_str() == level)
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// ------ Entry ------
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub time: DateTime<Utc>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<5} [{}] {}",
+            self.time.format("%H:%M:%S%.3f"),
+            self.level.as_str().to_uppercase(),
+            self.target,
+            self.message,
+        )
+    }
+}
+
+// ------ Logger ------
+
+thread_local! {
+    static ENTRIES: RefCell<VecDeque<Entry>> = RefCell::new(VecDeque::with_capacity(RING_BUFFER_CAPACITY));
+    static MAX_LEVEL: Cell<Option<Level>> = Cell::new(None);
+}
+
+/// Records the entry in the ring buffer and prints it to the console
+/// when its level is within the configured verbosity.
+pub fn log(level: Level, target: &str, message: String) {
+    if !enabled(level) {
+        return
+    }
+    let entry = Entry {
+        time: CoreEnv::now(),
+        level,
+        target: target.to_owned(),
+        message,
+    };
+    #[cfg(not(test))]
+    print(&entry);
+    ENTRIES.with(|entries| {
+        let mut entries = entries.borrow_mut();
+        if entries.len() == RING_BUFFER_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    });
+}
+
+/// Recent entries, the oldest first.
+pub fn entries() -> Vec<Entry> {
+    ENTRIES.with(|entries| entries.borrow().iter().cloned().collect())
+}
+
+/// At most `count` most recent entries; empty when the buffer is being modified (e.g. in a panic hook).
+pub fn last_entries(count: usize) -> Vec<Entry> {
+    ENTRIES.with(|entries| {
+        entries
+            .try_borrow()
+            .map(|entries| entries.iter().skip(entries.len().saturating_sub(count)).cloned().collect())
+            .unwrap_or_default()
+    })
+}
+
+/// Adds entries from another session (e.g. an imported bug report) under the `report::` target prefix.
+pub fn import(imported_entries: impl IntoIterator<Item = Entry>) {
+    ENTRIES.with(|entries| {
+        let mut entries = entries.borrow_mut();
+        for entry in imported_entries {
+            if entries.len() == RING_BUFFER_CAPACITY {
+                entries.pop_front();
+            }
+            entries.push_back(Entry { target: format!("report::{}", entry.target), ..entry });
+        }
+    });
+}
+
+pub fn clear() {
+    ENTRIES.with(|entries| entries.borrow_mut().clear());
+}
+
+/// Whether entries of the level are recorded, the macros check it before formatting the message.
+pub fn enabled(level: Level) -> bool {
+    level <= max_level()
+}
+
+/// The most verbose level that is recorded.
+/// It's `debug` in development builds and `info` in release builds unless changed by `set_max_level`.
+pub fn max_level() -> Level {
+    MAX_LEVEL.with(|max_level| {
+        max_level.get().unwrap_or_else(|| {
+            let level = stored_max_level().unwrap_or(DEFAULT_LEVEL);
+            max_level.set(Some(level));
+            level
+        })
+    })
+}
+
+/// Changes the verbosity and persists it, so it survives reloads (also in release builds).
+pub fn set_max_level(level: Level) {
+    MAX_LEVEL.with(|max_level| max_level.set(Some(level)));
+    #[cfg(not(test))]
+    if let Some(storage) = local_storage() {
+        let _ = storage.set_item(LOG_LEVEL_STORAGE_KEY, level.as_str());
+    }
+}
+
+#[cfg(not(test))]
+fn stored_max_level() -> Option<Level> {
+    let level = local_storage()?.get_item(LOG_LEVEL_STORAGE_KEY).ok()??;
+    Level::parse(&level)
+}
+
+#[cfg(test)]
+fn stored_max_level() -> Option<Level> {
+    None
+}
+
+// The level has to be known synchronously, so it's kept in `localStorage` instead of the `Env` storage.
+#[cfg(not(test))]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(not(test))]
+fn print(entry: &Entry) {
+    let message = wasm_bindgen::JsValue::from(format!("[{}] {}", entry.target, entry.message));
+    match entry.level {
+        Level::Error => web_sys::console::error_1(&message),
+        Level::Warn => web_sys::console::warn_1(&message),
+        Level::Info => web_sys::console::info_1(&message),
+        Level::Debug | Level::Trace => web_sys::console::debug_1(&message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_buffer_keeps_the_most_recent_entries() {
+        set_max_level(Level::Trace);
+        for index in 0..RING_BUFFER_CAPACITY + 5 {
+            log(Level::Info, "test", index.to_string());
+        }
+        let entries = entries();
+        assert_eq!(entries.len(), RING_BUFFER_CAPACITY);
+        assert_eq!(entries.first().unwrap().message, "5");
+        assert_eq!(entries.last().unwrap().message, (RING_BUFFER_CAPACITY + 4).to_string());
+    }
+
+    #[test]
+    fn entries_above_the_max_level_are_dropped() {
+        set_max_level(Level::Warn);
+        log_error!("env", "failed: {}", 1);
+        log_info!("env", "ignored");
+        let entries = entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].level, Level::Error);
+        assert_eq!(entries[0].target, "env");
+        assert_eq!(entries[0].message, "failed: 1");
+    }
+
+    #[test]
+    fn messages_above_the_max_level_are_not_formatted() {
+        set_max_level(Level::Info);
+        let formatted = Cell::new(false);
+        log_debug!("env", "{}", { formatted.set(true); "ignored" });
+        assert!(!formatted.get());
+    }
+}
@@ -1,11 +1,15 @@
 pub mod addons;
 pub mod board;
+pub mod bug_report;
 pub mod detail;
+pub mod inspector;
 pub mod discover;
 pub mod intro;
 pub mod library;
+pub mod logs;
 pub mod not_found;
 pub mod player;
+pub mod remote;
 pub mod search;
 pub mod settings;
 pub mod test_links;
@@ -1,4 +1,4 @@
-use crate::{multi_select, Msg as RootMsg, Context, PageId, Actions, Events, Urls as RootUrls};
+use crate::{multi_select, Msg as RootMsg, Context, CoreModel, PageId, Actions, Events, Urls as RootUrls};
 use enclose::enc;
 use seed::{prelude::*, *};
 use std::rc::Rc;
@@ -13,6 +13,7 @@ use seed_styles::*;
 use crate::styles::{self, themes::{Color, Breakpoint}, global};
 use seed_hooks::{*, topo::nested as view};
 use crate::basic_layout::{basic_layout, BasicLayoutArgs};
+use crate::route::{self, Route};
 
 mod catalog_selector;
 mod type_selector;
@@ -24,7 +25,7 @@ const DEFAULT_TYPE: &str = "official";
 const DEFAULT_ID: &str = "all";
 const BASE: &str = "https://v4-cinemeta.strem.io/manifest.json";
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum AddonRequest {
     Remote(ResourceRequest),
     Installed(InstalledAddonsRequest)
@@ -45,33 +46,19 @@ fn on_click_not_implemented() -> EventHandler<Msg> {
 // ------ ------
 
 pub fn init(
-    mut url: Url,
+    url: Url,
+    addon_request: AddonRequest,
     model: &mut Option<Model>,
     context: &mut Context,
     orders: &mut impl Orders<Msg>,
 ) -> Option<PageId> {
-    let base_url = url.to_hash_base_url();
-
-    let addon_request = match url.remaining_hash_path_parts().as_slice() {
-        [base, path] => {
-            (|| Some(AddonRequest::Remote(ResourceRequest::new(
-                base.parse().map_err(|error| error!(error)).ok()?,
-                serde_json::from_str(path).map_err(|error| error!(error)).ok()?
-            ))))()
-        },
-        [type_] => {
-            Some(AddonRequest::Installed(InstalledAddonsRequest {
-                r#type: Some(type_.to_string())
-            }))
-        }
-        _ => None,
-    };
+    let base_url = route::base_url(&url);
 
     load_catalog(addon_request.clone(), context, orders);
 
     let model = model.get_or_insert_with(move || Model {
         base_url,
-        addon_request: None,
+        addon_request: AddonRequest::default(),
         search_query: String::new(),
         modal: None,
         add_addon_url: String::new(),
@@ -87,40 +74,42 @@ pub fn init(
     Some(PageId::Addons)
 }
 
-fn load_catalog(addon_request: Option<AddonRequest>, context: &mut Context, orders: &mut impl Orders<Msg>) {
-    let addon_request = addon_request.unwrap_or_default();
+fn load_catalog(addon_request: AddonRequest, context: &mut Context, orders: &mut impl Orders<Msg>) {
+    for core_msg in load_catalog_msgs(addon_request, &mut context.core_model) {
+        orders.notify(Actions::UpdateCoreModel(Rc::new(core_msg)));
+    }
+}
+
+/// The other catalog is unselected, so only one of them is displayed.
+fn load_catalog_msgs(addon_request: AddonRequest, core_model: &mut CoreModel) -> Vec<CoreMsg> {
     match addon_request {
         AddonRequest::Remote(res_req) => {
-            let installed_addons = &mut context.core_model.installed_addons;
+            let installed_addons = &mut core_model.installed_addons;
             installed_addons.selected = None;
 
             let selected_catalog = CatalogWithFiltersSelected {
                 request: res_req
             };
-            orders.notify(Actions::UpdateCoreModel(Rc::new(CoreMsg::Action(Action::Load(
-                ActionLoad::CatalogWithFilters(selected_catalog)
-            )))));
+            vec![CoreMsg::Action(Action::Load(ActionLoad::CatalogWithFilters(selected_catalog)))]
         }
         AddonRequest::Installed(installed_addons_request) => {
-            let addon_catalog = &mut context.core_model.addon_catalog;
+            let mut core_msgs = Vec::new();
+            let addon_catalog = &mut core_model.addon_catalog;
             addon_catalog.selected = None;
             if addon_catalog.selectable.catalogs.is_empty() {
                 let default_catalog = CatalogWithFiltersSelected {
                     request: default_resource_request()
                 };
-                orders.notify(Actions::UpdateCoreModel(Rc::new(CoreMsg::Action(Action::Load(
-                    ActionLoad::CatalogWithFilters(default_catalog)
-                )))));
+                core_msgs.push(CoreMsg::Action(Action::Load(ActionLoad::CatalogWithFilters(default_catalog))));
             }
 
             let selected_catalog = InstalledAddonsWithFiltersSelected {
                 request: installed_addons_request
             };
-            orders.notify(Actions::UpdateCoreModel(Rc::new(CoreMsg::Action(Action::Load(
-                ActionLoad::InstalledAddonsWithFilters(selected_catalog)
-            )))));
+            core_msgs.push(CoreMsg::Action(Action::Load(ActionLoad::InstalledAddonsWithFilters(selected_catalog))));
+            core_msgs
         }
-    }   
+    }
 }
 
 pub fn default_resource_request() -> ResourceRequest {
@@ -136,7 +125,7 @@ pub fn default_resource_request() -> ResourceRequest {
 
 pub struct Model {
     base_url: Url,
-    addon_request: Option<AddonRequest>,
+    addon_request: AddonRequest,
     search_query: String,
     modal: Option<Modal>,
     add_addon_url: String,
@@ -158,25 +147,10 @@ pub enum Modal {
 struct_urls!();
 impl<'a> Urls<'a> {
     pub fn root(self) -> Url {
-        self.base_url()
+        Route::Addons(AddonRequest::default()).to_url(&self.base_url())
     }
     pub fn addon_request(self, addon_request: &AddonRequest) -> Url {
-        match addon_request{
-            AddonRequest::Remote(res_req) => {
-                self.base_url()
-                    .add_hash_path_part(res_req.base.to_string())
-                    .add_hash_path_part(serde_json::to_string(&res_req.path).unwrap())
-            }
-            AddonRequest::Installed(installed_addons_request) => {
-                if let Some(type_) = &installed_addons_request.r#type {
-                    self.base_url()
-                        .add_hash_path_part(type_)
-                } else {
-                    self.base_url()
-                }
-            }
-        }
-        
+        Route::Addons(addon_request.clone()).to_url(&self.base_url())
     }
 }
 
@@ -227,12 +201,16 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
                                     return;
                                 }
                             }
-                            _ => error!("remote_addon not ready")
+                            _ => log_error!("page::addons", "remote_addon not ready")
                         }
                     } 
                 }
-                CoreMsg::Event(Event::AddonUninstalled {id, ..}) => log!("addon uninstalled:", id),
-                CoreMsg::Event(Event::AddonInstalled {id, ..}) => log!("addon installed:", id),
+                CoreMsg::Event(Event::AddonUninstalled {id, ..}) => {
+                    log_info!("page::addons", "addon uninstalled: {}", id);
+                }
+                CoreMsg::Event(Event::AddonInstalled {id, ..}) => {
+                    log_info!("page::addons", "addon installed: {}", id);
+                }
                 _ => ()
             }
         }
@@ -276,7 +254,7 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
                             return;
                         }
                     }
-                    _ => error!("remote_addon not ready")
+                    _ => log_error!("page::addons", "remote_addon not ready")
                 }
             } 
 
@@ -821,3 +799,61 @@ fn share_button() -> Node<Msg> {
         ]
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::{self, TestApp, TestEnv};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[test]
+    fn installed_addons_are_loaded_with_the_catalog_selector() {
+        let mut core_model = test_env::new_core_model();
+        let core_msgs = load_catalog_msgs(AddonRequest::default(), &mut core_model);
+        assert_eq!(core_msgs.len(), 2);
+        for core_msg in &core_msgs {
+            test_env::update(&mut core_model, core_msg);
+        }
+
+        assert!(core_model.installed_addons.selected.is_some());
+        assert!(core_model.addon_catalog.selected.is_none());
+    }
+
+    #[test]
+    fn remote_catalog_unselects_installed_addons() {
+        let mut core_model = test_env::new_core_model();
+        for core_msg in &load_catalog_msgs(AddonRequest::default(), &mut core_model) {
+            test_env::update(&mut core_model, core_msg);
+        }
+        let core_msgs = load_catalog_msgs(AddonRequest::Remote(default_resource_request()), &mut core_model);
+
+        assert_eq!(core_msgs.len(), 1);
+        assert!(core_model.installed_addons.selected.is_none());
+    }
+
+    #[wasm_bindgen_test]
+    async fn addon_is_installed_from_its_url() {
+        let app = TestApp::start().await;
+        let manifest = serde_json::json!({
+            "id": "com.example.addon",
+            "version": "1.0.0",
+            "name": "Example addon",
+            "resources": [],
+            "types": [],
+            "catalogs": [],
+        });
+        TestEnv::add_fetch_response("https://example.com/manifest.json", &manifest);
+        app.open(Route::Addons(AddonRequest::default())).await;
+        app.send(RootMsg::AddonsMsg(Msg::AddAddonUrlChanged("https://example.com/manifest.json".to_owned()))).await;
+        app.send(RootMsg::AddonsMsg(Msg::InstallAddon)).await;
+
+        let (installed, install_pending) = app.read(|model| (
+            model.model.context.core_model.ctx.profile.addons.iter().any(|addon| addon.manifest.id == "com.example.addon"),
+            model.model.addons_model.as_ref().expect("addons model").install_addon.is_some(),
+        ));
+        assert!(installed);
+        assert!(!install_pending);
+    }
+}
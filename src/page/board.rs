@@ -1,4 +1,4 @@
-use crate::{PageId, Msg as RootMsg, Urls as RootUrls, Context};
+use crate::{CoreEnv, PageId, Msg as RootMsg, Urls as RootUrls, Context};
 use seed::{prelude::*, *};
 use seed_styles::{pc, rem, em};
 use seed_styles::*;
@@ -10,6 +10,9 @@ use indexmap::{IndexMap, indexmap};
 use stremio_core::types::resource::{MetaItemPreview, PosterShape};
 use stremio_core::types::addon::{ResourceRequest, ResourceResponse, ResourcePath};
 use crate::basic_layout::{basic_layout, BasicLayoutArgs};
+use crate::route::{self, Route};
+use http::Request;
+use stremio_core::runtime::{Env, EnvError};
 
 const SEARCH_DEBOUNCE_TIME: u32 = 0;
 
@@ -28,7 +31,7 @@ pub fn init(
     orders: &mut impl Orders<Msg>,
 ) -> Option<PageId> {
     let root_url_base = &context.root_base_url;
-    let base_url = url.to_hash_base_url();
+    let base_url = route::base_url(&url);
 
     // @TODO load dynamically? (together with the `let resources` below)
 
@@ -90,18 +93,16 @@ pub fn init(
         ];
         for (video_group_id, url) in resources.into_iter() {
             orders.perform_cmd(async move { 
-                Msg::VideosReceived(video_group_id, get_videos(url).await.unwrap()) 
+                Msg::VideosReceived(video_group_id, get_videos(url).await) 
             });
         }
     }
     Some(PageId::Board)
 }
 
-async fn get_videos(url: &str) -> Result<Vec<MetaItemPreview>, FetchError> {
-    fetch(url)
-        .await?
-        .check_status()?
-        .json::<FetchedResourceResponse>()
+async fn get_videos(url: &str) -> Result<Vec<MetaItemPreview>, EnvError> {
+    let request = Request::get(url).body(()).expect("valid request");
+    CoreEnv::fetch::<_, FetchedResourceResponse>(request)
         .await
         .map(|response| response.metas.into_iter().take(10).collect())
 }
@@ -142,7 +143,7 @@ struct VideoGroup {
 struct_urls!();
 impl<'a> Urls<'a> {
     pub fn root(self) -> Url {
-        self.base_url()
+        Route::Board.to_url(&self.base_url())
     }
 }
 
@@ -151,14 +152,17 @@ impl<'a> Urls<'a> {
 // ------ ------
 
 pub enum Msg {
-    VideosReceived(VideoGroupId, Vec<MetaItemPreview>),
+    VideosReceived(VideoGroupId, Result<Vec<MetaItemPreview>, EnvError>),
 }
 
 pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut impl Orders<Msg>) {
     match msg {
-        Msg::VideosReceived(video_group_id, videos) => {
+        Msg::VideosReceived(video_group_id, Ok(videos)) => {
             model.video_groups.get_mut(&video_group_id).unwrap().videos = videos;
         }
+        Msg::VideosReceived(_, Err(error)) => {
+            log_error!("page::board", "Board videos loading failed: {}", error.message());
+        }
     }
 }
 
@@ -423,3 +427,27 @@ fn poster_container(poster: &String) -> Node<Msg> {
         ]
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::{self, TestApp, TestEnv};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn board_rows_are_filled_from_catalogs() {
+        let app = TestApp::start().await;
+        let metas = serde_json::json!({ "metas": [test_env::meta_preview("tt0000001")] });
+        TestEnv::add_fetch_response("https://v4-cinemeta.strem.io/catalog/movie/top.json", &metas);
+        app.open(Route::Board).await;
+
+        let video_counts = app.read(|model| {
+            let board_model = model.model.board_model.as_ref().expect("board model");
+            board_model.video_groups.values().map(|group| group.videos.len()).collect::<Vec<_>>()
+        });
+        // the other catalogs failed, their rows stay empty
+        assert_eq!(video_counts, vec![1, 0, 0, 0, 0]);
+    }
+}
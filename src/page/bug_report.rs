@@ -0,0 +1,337 @@
+use http::Request;
+use seed::{prelude::*, *};
+use seed_hooks::{*, topo::nested as view};
+use seed_styles::{em, pc, rem, Style};
+use seed_styles::*;
+use std::rc::Rc;
+use stremio_core::runtime::{Env, EnvError};
+use stremio_core::runtime::msg::{Msg as CoreMsg, Action, ActionCtx};
+use stremio_core::types::addon::{Descriptor, DescriptorFlags, Manifest};
+use stremio_core::types::profile::Settings;
+use wasm_bindgen_futures::JsFuture;
+use crate::{Actions, Context, PageId};
+use crate::bug_report::{self, BugReport, BUG_REPORT_VERSION};
+use crate::env::WebEnv;
+use crate::logger;
+use crate::styles::{self, themes::Color, global};
+
+// ------ ------
+//     Init
+// ------ ------
+
+pub fn init(
+    _url: Url,
+    model: &mut Option<Model>,
+    _orders: &mut impl Orders<Msg>,
+) -> Option<PageId> {
+    model.get_or_insert_with(|| Model {
+        bug_report: None,
+        error: None,
+        logs_imported: false,
+        loaded: false,
+        skipped_addon_urls: Vec::new(),
+    });
+    Some(PageId::BugReport)
+}
+
+// ------ ------
+//     Model
+// ------ ------
+
+pub struct Model {
+    bug_report: Option<BugReport>,
+    error: Option<String>,
+    logs_imported: bool,
+    loaded: bool,
+    // addons whose configuration has been redacted, so they can't be installed
+    skipped_addon_urls: Vec<String>,
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+pub enum Msg {
+    FileSelected(Option<web_sys::File>),
+    FileRead(Result<String, String>),
+    ImportLogs,
+    OpenRoute,
+    /// Applies the report to this build: addons, the streaming server URL, logs and the route.
+    Load,
+    ManifestLoaded(url::Url, Result<Manifest, EnvError>),
+}
+
+pub fn update(msg: Msg, model: &mut Model, context: &Context, orders: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::FileSelected(Some(file)) => {
+            orders.perform_cmd(async move {
+                let text = JsFuture::from(file.text())
+                    .await
+                    .map(|text| text.as_string().unwrap_or_default())
+                    .map_err(|error| format!("{:?}", error));
+                Msg::FileRead(text)
+            });
+        }
+        Msg::FileSelected(None) => {
+            orders.skip();
+        }
+        Msg::FileRead(Ok(text)) => {
+            let bug_report = serde_json::from_str::<BugReport>(&text)
+                .map_err(|error| format!("Invalid bug report: {}", error))
+                .and_then(|bug_report| {
+                    if bug_report.version > BUG_REPORT_VERSION {
+                        return Err(format!("Unsupported bug report version {}", bug_report.version))
+                    }
+                    Ok(bug_report)
+                });
+            match bug_report {
+                Ok(bug_report) => {
+                    model.bug_report = Some(bug_report);
+                    model.error = None;
+                    model.logs_imported = false;
+                    model.loaded = false;
+                    model.skipped_addon_urls.clear();
+                }
+                Err(error) => {
+                    log_error!("page::bug_report", "{}", error);
+                    model.error = Some(error);
+                }
+            }
+        }
+        Msg::FileRead(Err(error)) => {
+            log_error!("page::bug_report", "Bug report reading failed: {}", error);
+            model.error = Some("The file couldn't be read".to_owned());
+        }
+        Msg::ImportLogs => {
+            if let Some(bug_report) = &model.bug_report {
+                logger::import(bug_report.logs.iter().cloned());
+                model.logs_imported = true;
+            }
+        }
+        Msg::OpenRoute => {
+            orders.skip();
+            if let Some(bug_report) = &model.bug_report {
+                open_route(bug_report, orders);
+            }
+        }
+        Msg::Load => {
+            let bug_report = match &model.bug_report {
+                Some(bug_report) => bug_report,
+                None => return,
+            };
+            let profile = &context.core_model.ctx.profile;
+            model.skipped_addon_urls.clear();
+            for addon_url in &bug_report.addon_urls {
+                let transport_url = match addon_url.parse::<url::Url>() {
+                    Ok(transport_url) if !bug_report::is_redacted_url(&transport_url) => transport_url,
+                    _ => {
+                        model.skipped_addon_urls.push(addon_url.clone());
+                        continue
+                    }
+                };
+                if profile.addons.iter().any(|addon| addon.transport_url == transport_url) {
+                    continue
+                }
+                orders.perform_cmd(async move {
+                    let request = Request::get(transport_url.as_str()).body(()).expect("valid request");
+                    let manifest = WebEnv::fetch::<_, Manifest>(request).await;
+                    Msg::ManifestLoaded(transport_url, manifest)
+                });
+            }
+            match bug_report.streaming_server.url.parse::<url::Url>() {
+                Ok(streaming_server_url) if streaming_server_url != profile.settings.streaming_server_url => {
+                    let settings = Settings { streaming_server_url, ..profile.settings.clone() };
+                    orders.notify(Actions::UpdateCoreModel(Rc::new(CoreMsg::Action(Action::Ctx(
+                        ActionCtx::UpdateSettings(settings)
+                    )))));
+                }
+                Ok(_) => (),
+                Err(error) => log_error!("page::bug_report", "Invalid streaming server URL {}: {}", bug_report.streaming_server.url, error),
+            }
+            if !model.logs_imported {
+                logger::import(bug_report.logs.iter().cloned());
+                model.logs_imported = true;
+            }
+            model.loaded = true;
+            open_route(bug_report, orders);
+        }
+        Msg::ManifestLoaded(transport_url, Ok(manifest)) => {
+            let descriptor = Descriptor { manifest, transport_url, flags: DescriptorFlags::default() };
+            orders.notify(Actions::UpdateCoreModel(Rc::new(CoreMsg::Action(Action::Ctx(
+                ActionCtx::InstallAddon(descriptor)
+            )))));
+        }
+        Msg::ManifestLoaded(transport_url, Err(error)) => {
+            log_error!("page::bug_report", "Addon {} couldn't be installed: {}", transport_url, error.message());
+        }
+    }
+}
+
+// hash routes are redirected in the path routing mode
+fn open_route(bug_report: &BugReport, orders: &mut impl Orders<Msg>) {
+    match bug_report.route.parse::<Url>() {
+        Ok(url) => {
+            orders.request_url(url);
+        }
+        Err(error) => log_error!("page::bug_report", "Invalid route {}: {}", bug_report.route, error),
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+#[view]
+pub fn view(model: &Model) -> Node<Msg> {
+    div![
+        C!["bug-report-container"],
+        s()
+            .background_color(Color::BackgroundDark2)
+            .color(Color::SurfaceLight5_90)
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Column)
+            .height(pc(100))
+            .overflow_y(CssOverflowY::Auto)
+            .padding(rem(2))
+            .width(pc(100)),
+        h1![
+            s()
+                .font_size(rem(1.8))
+                .margin_bottom(rem(1)),
+            "Import bug report",
+        ],
+        input![
+            C!["bug-report-input"],
+            s()
+                .margin_bottom(rem(1)),
+            attrs!{
+                At::Type => "file",
+                At::Accept => "application/json,.json",
+            },
+            ev(Ev::Change, |event| {
+                let file = event
+                    .target()
+                    .and_then(|target| target.dyn_into::<web_sys::HtmlInputElement>().ok())
+                    .and_then(|input| input.files())
+                    .and_then(|files| files.get(0));
+                Msg::FileSelected(file)
+            }),
+        ],
+        model.error.as_ref().map(|error| {
+            div![
+                s()
+                    .color(Color::Signal2)
+                    .margin_bottom(rem(1)),
+                error,
+            ]
+        }),
+        model.bug_report.as_ref().map(|bug_report| summary(bug_report, model)),
+    ]
+}
+
+#[view]
+fn summary(bug_report: &BugReport, model: &Model) -> Node<Msg> {
+    let streaming_server = &bug_report.streaming_server;
+    div![
+        C!["bug-report-summary"],
+        property("Created", &bug_report.created.to_rfc3339()),
+        property("App version", &bug_report.app_version),
+        property("Shell version", bug_report.shell_version.as_deref().unwrap_or("-")),
+        property("Route", &bug_report.route),
+        property(
+            "Streaming server",
+            &format!(
+                "{} ({}, version {})",
+                streaming_server.url,
+                streaming_server.status,
+                streaming_server.server_version.as_deref().unwrap_or("-"),
+            )
+        ),
+        property("Addons", &bug_report.addon_urls.join("\n")),
+        div![
+            s()
+                .display(CssDisplay::Flex)
+                .flex_direction(CssFlexDirection::Row)
+                .margin("1rem 0"),
+            bug_report_button(if model.loaded { "Loaded" } else { "Load" }, || Msg::Load),
+            bug_report_button("Open route", || Msg::OpenRoute),
+            bug_report_button(
+                if model.logs_imported { "Logs imported" } else { "Import logs" },
+                || Msg::ImportLogs
+            ),
+        ],
+        IF!(not(model.skipped_addon_urls.is_empty()) => {
+            property("Not installed", &model.skipped_addon_urls.join("\n"))
+        }),
+        h2![
+            s()
+                .font_size(rem(1.3))
+                .margin_bottom(rem(0.5)),
+            "Core messages",
+        ],
+        pre![
+            s()
+                .font_family("monospace")
+                .user_select("text")
+                .white_space(CssWhiteSpace::PreWrap),
+            bug_report
+                .core_messages
+                .iter()
+                .map(|message| {
+                    format!(
+                        "{} [{}] {}{}",
+                        message.time.format("%H:%M:%S%.3f"),
+                        message.source,
+                        message.name,
+                        if message.has_changed { "" } else { " (unchanged)" },
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ],
+    ]
+}
+
+#[view]
+fn property(name: &str, value: &str) -> Node<Msg> {
+    div![
+        s()
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Row)
+            .padding("0.3rem 0"),
+        div![
+            s()
+                .flex("0 0 10rem")
+                .opacity("0.6"),
+            name,
+        ],
+        div![
+            s()
+                .flex("1")
+                .user_select("text")
+                .white_space(CssWhiteSpace::PreWrap),
+            value,
+        ],
+    ]
+}
+
+#[view]
+fn bug_report_button(title: &str, on_click: impl FnOnce() -> Msg + Clone + 'static) -> Node<Msg> {
+    div![
+        C!["bug-report-button", "button-container"],
+        s()
+            .background_color(Color::Accent3)
+            .cursor(CssCursor::Pointer)
+            .margin_right(rem(0.5))
+            .padding("0.5rem 1rem"),
+        s()
+            .hover()
+            .background_color(Color::Accent3Light1),
+        attrs!{
+            At::TabIndex => 0,
+            At::Title => title,
+        },
+        ev(Ev::Click, move |_| on_click()),
+        title,
+    ]
+}
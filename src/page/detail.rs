@@ -1,4 +1,5 @@
 use crate::{PageId, Actions, Context, Events, Urls as RootUrls};
+use crate::route::{self, Route};
 use crate::styles::global;
 use seed::{prelude::*, *};
 use std::rc::Rc;
@@ -23,21 +24,17 @@ fn on_click_not_implemented() -> EventHandler<Msg> {
 // ------ ------
 
 pub fn init(
-    mut url: Url,
+    url: Url,
+    type_name: String,
+    id: String,
+    video_id: Option<String>,
     model: &mut Option<Model>,
     context: &mut Context,
     orders: &mut impl Orders<Msg>,
 ) -> Option<PageId> {
-    let base_url = url.to_hash_base_url();
+    let base_url = route::base_url(&url);
 
-    let type_name = url.next_hash_path_part()?.to_owned();
-    let id = url.next_hash_path_part()?.to_owned();
-    let video_id = url.next_hash_path_part();
-
-    let selected_meta_details = MetaDetailsSelected {
-        meta_path: ResourcePath::without_extra("meta", &type_name, &id),
-        stream_path: video_id.map(|video_id| ResourcePath::without_extra("stream", &type_name, video_id)),
-    };
+    let selected_meta_details = meta_details_selected(&type_name, &id, video_id.as_deref());
 
     if context.ctx_loaded {
         orders.send_msg(Msg::LoadDetails(selected_meta_details.clone()));
@@ -54,6 +51,14 @@ pub fn init(
     Some(PageId::Detail)
 }
 
+/// Streams are loaded for the selected video only.
+fn meta_details_selected(type_name: &str, id: &str, video_id: Option<&str>) -> MetaDetailsSelected {
+    MetaDetailsSelected {
+        meta_path: ResourcePath::without_extra("meta", type_name, id),
+        stream_path: video_id.map(|video_id| ResourcePath::without_extra("stream", type_name, video_id)),
+    }
+}
+
 // ------ ------
 //     Model
 // ------ ------
@@ -71,15 +76,20 @@ pub struct Model {
 struct_urls!();
 impl<'a> Urls<'a> {
     pub fn without_video_id(self, type_name: &str, id: &str) -> Url {
-        self.base_url()
-            .add_hash_path_part(type_name)
-            .add_hash_path_part(id)
+        Route::Detail {
+            type_name: type_name.to_owned(),
+            id: id.to_owned(),
+            video_id: None,
+        }
+        .to_url(&self.base_url())
     }
     pub fn with_video_id(self, type_name: &str, id: &str, video_id: &str) -> Url {
-        self.base_url()
-            .add_hash_path_part(type_name)
-            .add_hash_path_part(id)
-            .add_hash_path_part(video_id)
+        Route::Detail {
+            type_name: type_name.to_owned(),
+            id: id.to_owned(),
+            video_id: Some(video_id.to_owned()),
+        }
+        .to_url(&self.base_url())
     }
 }
 
@@ -241,7 +251,9 @@ fn video_container(video: &Video, meta_item: &MetaItem, base_url: &Url, root_bas
 
 fn video_container_url(video: &Video, meta_item: &MetaItem, base_url: &Url, root_base_url: &Url) -> Url {
     if video.streams.len() == 1 {
-        RootUrls::new(root_base_url).player().stream(&video.streams[0])
+        RootUrls::new(root_base_url)
+            .player()
+            .video_stream(&video.streams[0], &meta_item.r#type, &meta_item.id, &video.id)
     } else {
         Urls::new(base_url).with_video_id(&meta_item.r#type, &meta_item.id, &video.id)
     }
@@ -980,3 +992,47 @@ fn action_button(
         ]
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Msg as RootMsg;
+    use crate::test_env::{self, TestApp};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[test]
+    fn streams_are_selected_for_the_video() {
+        let selected = meta_details_selected("series", "tt0000001", Some("tt0000001:1:2"));
+        assert_eq!(selected.meta_path.id, "tt0000001");
+        assert_eq!(selected.stream_path.as_ref().map(|path| path.id.as_str()), Some("tt0000001:1:2"));
+        assert!(meta_details_selected("movie", "tt0000001", None).stream_path.is_none());
+    }
+
+    #[test]
+    fn meta_details_are_loaded() {
+        let mut core_model = test_env::new_core_model();
+        let selected = meta_details_selected("movie", "tt0000001", None);
+        test_env::update(&mut core_model, &CoreMsg::Action(Action::Load(ActionLoad::MetaDetails(selected.clone()))));
+
+        assert_eq!(
+            core_model.meta_details.selected.as_ref().map(|selected| &selected.meta_path),
+            Some(&selected.meta_path),
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn details_are_loaded_after_the_ctx() {
+        let app = TestApp::start().await;
+        app.open(Route::Detail { type_name: "movie".to_owned(), id: "tt0000001".to_owned(), video_id: None }).await;
+        assert!(app.read(|model| model.model.context.core_model.meta_details.selected.is_none()));
+
+        app.send(RootMsg::CtxStorageResponse(Ok(((None, None, None), Vec::new())))).await;
+
+        let meta_path = app.read(|model| {
+            model.model.context.core_model.meta_details.selected.as_ref().map(|selected| selected.meta_path.clone())
+        });
+        assert_eq!(meta_path, Some(ResourcePath::without_extra("meta", "movie", "tt0000001")));
+    }
+}
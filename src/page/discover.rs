@@ -1,8 +1,11 @@
-use crate::{multi_select, Msg as RootMsg, Context, PageId, Actions, Urls as RootUrls};
+use crate::{multi_select, Msg as RootMsg, Context, PageId, Actions, Events, Urls as RootUrls};
+use crate::history_state::{self, HistoryStates};
+use crate::route::{self, Route};
 use enclose::enc;
 use seed::{prelude::*, *};
 use std::rc::Rc;
 use std::collections::HashMap;
+use web_sys::HtmlElement;
 use stremio_core::runtime::msg::{Msg as CoreMsg, Action, Internal, Event, ActionLoad, ActionCtx};
 use stremio_core::models::common::{Loadable, ResourceError};
 use stremio_core::models::catalog_with_filters::{Selected as CatalogWithFiltersSelected, CatalogWithFilters};
@@ -34,39 +37,66 @@ fn on_click_not_implemented() -> EventHandler<Msg> {
 // ------ ------
 
 pub fn init(
-    mut url: Url,
+    url: Url,
+    resource_request: Option<ResourceRequest>,
+    history_traversal: bool,
     model: &mut Option<Model>,
+    context: &Context,
     orders: &mut impl Orders<Msg>,
 ) -> Option<PageId> {
-    let base_url = url.to_hash_base_url();
-
-    let resource_request = match url.remaining_hash_path_parts().as_slice() {
-        [base, path] => {
-            (|| Some(ResourceRequest::new(
-                base.parse().map_err(|error| error!(error)).ok()?,
-                serde_json::from_str(path).map_err(|error| error!(error)).ok()?
-            )))()
-        }
-        _ => None,
-    };
+    let base_url = route::base_url(&url);
+    let history_entry_id = history_state::current_entry_id();
+    let resource_request = resource_request.unwrap_or_else(default_resource_request);
 
-    load_catalog(resource_request, orders);
+    // the previous catalog is still rendered when the page changes only its request
+    if let Some(model) = model.as_mut().filter(|model| model.active) {
+        save_history_state(model);
+    }
 
-    model.get_or_insert_with(move || Model {
+    let model = model.get_or_insert_with(|| Model {
         base_url,
+        history_entry_id: history_entry_id.clone(),
+        active: true,
         _core_msg_sub_handle: orders.subscribe_with_handle(Msg::CoreMsg),
+        _events_sub_handle: orders.subscribe_with_handle(|events| {
+            matches!(events, Events::PageChanged(page_id) if page_id != PageId::Discover)
+                .then(|| Msg::PageLeft)
+        }),
         selected_meta_preview: None,
+        meta_items_ref: ElRef::new(),
+        history_states: HistoryStates::default(),
     });
+
+    let history_state = history_traversal
+        .then(|| model.history_states.get(&history_entry_id).cloned())
+        .flatten();
+    let catalog_loaded = context
+        .core_model
+        .catalog
+        .selected
+        .as_ref()
+        .map_or(false, |selected| selected.request == resource_request);
+    match history_state {
+        Some(history_state) if catalog_loaded => {
+            model.selected_meta_preview = history_state.selected_meta_preview;
+            orders.after_next_render(move |_| Msg::RestoreScroll(history_state.scroll_top));
+        }
+        _ => load_catalog(resource_request, orders),
+    }
+    model.history_entry_id = history_entry_id;
+    model.active = true;
     Some(PageId::Discover)
 }
 
-fn load_catalog(resource_request: Option<ResourceRequest>, orders: &mut impl Orders<Msg>) {
+fn load_catalog(resource_request: ResourceRequest, orders: &mut impl Orders<Msg>) {
+    orders.notify(Actions::UpdateCoreModel(Rc::new(load_catalog_msg(resource_request))));
+}
+
+fn load_catalog_msg(resource_request: ResourceRequest) -> CoreMsg {
     let selected_catalog = CatalogWithFiltersSelected {
-        request: resource_request.unwrap_or_else(default_resource_request)
+        request: resource_request
     };
-    orders.notify(Actions::UpdateCoreModel(Rc::new(CoreMsg::Action(Action::Load(
-        ActionLoad::CatalogWithFilters(selected_catalog),
-    )))));
+    CoreMsg::Action(Action::Load(ActionLoad::CatalogWithFilters(selected_catalog)))
 }
 
 pub fn default_resource_request() -> ResourceRequest {
@@ -82,8 +112,29 @@ pub fn default_resource_request() -> ResourceRequest {
 
 pub struct Model {
     base_url: Url,
+    // the page's history state is saved under it
+    history_entry_id: String,
+    // the page is displayed
+    active: bool,
     _core_msg_sub_handle: SubHandle,
+    _events_sub_handle: SubHandle,
     selected_meta_preview: Option<MetaItemPreview>,
+    meta_items_ref: ElRef<HtmlElement>,
+    history_states: HistoryStates<HistoryState>,
+}
+
+#[derive(Clone)]
+struct HistoryState {
+    scroll_top: i32,
+    selected_meta_preview: Option<MetaItemPreview>,
+}
+
+fn save_history_state(model: &mut Model) {
+    let history_state = HistoryState {
+        scroll_top: model.meta_items_ref.get().map_or(0, |meta_items| meta_items.scroll_top()),
+        selected_meta_preview: model.selected_meta_preview.clone(),
+    };
+    model.history_states.save(&model.history_entry_id, history_state);
 }
 
 // ------ ------
@@ -93,12 +144,10 @@ pub struct Model {
 struct_urls!();
 impl<'a> Urls<'a> {
     pub fn root(self) -> Url {
-        self.base_url()
+        Route::Discover(None).to_url(&self.base_url())
     }
     pub fn res_req(self, res_req: &ResourceRequest) -> Url {
-        self.base_url()
-            .add_hash_path_part(res_req.base.to_string())
-            .add_hash_path_part(serde_json::to_string(&res_req.path).unwrap())
+        Route::Discover(Some(res_req.clone())).to_url(&self.base_url())
     }
 }
 
@@ -113,6 +162,8 @@ pub enum Msg {
     SendResourceRequest(ResourceRequest),
     AddToLibrary,
     RemoveFromLibrary,
+    PageLeft,
+    RestoreScroll(i32),
 }
 
 pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut impl Orders<Msg>) {
@@ -143,6 +194,19 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
                 ActionCtx::RemoveFromLibrary(id)
             )))));
         }
+        Msg::PageLeft => {
+            orders.skip();
+            if model.active {
+                save_history_state(model);
+                model.active = false;
+            }
+        }
+        Msg::RestoreScroll(scroll_top) => {
+            orders.skip();
+            if let Some(meta_items) = model.meta_items_ref.get() {
+                meta_items.set_scroll_top(scroll_top);
+            }
+        }
     }
 }
 
@@ -184,6 +248,7 @@ fn discover_content(model: &Model, context: &Context) -> Node<Msg> {
                 meta_items(
                     &resource_loadable.content,
                     model.selected_meta_preview.as_ref(),
+                    &model.meta_items_ref,
                     &context.root_base_url,
                 )
             }),
@@ -668,6 +733,7 @@ fn pagination_next_button(next_page_request: Option<&ResourceRequest>) -> Node<M
 fn meta_items(
     content: &Loadable<Vec<MetaItemPreview>, ResourceError>,
     selected_meta_preview: Option<&MetaItemPreview>,
+    meta_items_ref: &ElRef<HtmlElement>,
     root_base_url: &Url,
 ) -> Node<Msg> {
     let message_container_style = s()
@@ -683,6 +749,7 @@ fn meta_items(
         Loadable::Ready(meta_previews) if meta_previews.is_empty() => empty![],
         Loadable::Ready(meta_previews) => div![
             C!["meta-items-container",],
+            el_ref(meta_items_ref),
             s()
                 .align_items(CssAlignItems::Center)
                 .align_self(CssAlignSelf::Stretch)
@@ -885,3 +952,39 @@ fn play_icon() -> Node<Msg> {
         ],
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::{self, TestApp, TestEnv};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[test]
+    fn default_catalog_is_loaded() {
+        let mut core_model = test_env::new_core_model();
+        let request = default_resource_request();
+        test_env::update(&mut core_model, &load_catalog_msg(request.clone()));
+
+        assert_eq!(core_model.catalog.selected.as_ref().map(|selected| &selected.request), Some(&request));
+        assert!(TestEnv::fetch_requests().iter().any(|request| request.url.contains("/catalog/movie/top")));
+    }
+
+    #[wasm_bindgen_test]
+    async fn first_catalog_item_is_selected_and_added_to_library() {
+        let app = TestApp::start().await;
+        let metas = serde_json::json!({ "metas": [test_env::meta_preview("tt0000001"), test_env::meta_preview("tt0000002")] });
+        TestEnv::add_fetch_response("https://v4-cinemeta.strem.io/catalog/movie/top.json", &metas);
+        app.open(Route::Discover(None)).await;
+
+        let selected_id = app.read(|model| {
+            let discover_model = model.model.discover_model.as_ref().expect("discover model");
+            discover_model.selected_meta_preview.as_ref().map(|meta_preview| meta_preview.id.clone())
+        });
+        assert_eq!(selected_id.as_deref(), Some("tt0000001"));
+
+        app.send(RootMsg::DiscoverMsg(Msg::AddToLibrary)).await;
+        assert!(app.read(|model| model.model.context.core_model.ctx.library.items.contains_key("tt0000001")));
+    }
+}
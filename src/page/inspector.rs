@@ -0,0 +1,363 @@
+use seed::{prelude::*, *};
+use seed_hooks::{*, topo::nested as view};
+use seed_styles::{em, pc, rem, Style};
+use seed_styles::*;
+use serde::Serialize;
+use crate::{Context, CoreModel, PageId};
+use crate::core_inspector::Record;
+use crate::styles::{self, themes::Color, global};
+
+// ------ ------
+//     Init
+// ------ ------
+
+pub fn init(
+    _url: Url,
+    model: &mut Option<Model>,
+    context: &mut Context,
+    _orders: &mut impl Orders<Msg>,
+) -> Option<PageId> {
+    if model.is_none() {
+        context.core_inspector.set_recording(true, &context.core_model);
+    }
+    model.get_or_insert_with(|| Model {
+        selected_record: None,
+        replayed_core_model: None,
+        field: Field::Ctx,
+    });
+    Some(PageId::Inspector)
+}
+
+// ------ ------
+//     Model
+// ------ ------
+
+pub struct Model {
+    // `None` shows the live state
+    selected_record: Option<usize>,
+    replayed_core_model: Option<CoreModel>,
+    field: Field,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Ctx,
+    Catalog,
+    MetaDetails,
+    Library,
+    StreamingServer,
+    Player,
+}
+
+impl Field {
+    const ALL: [Field; 6] = [
+        Field::Ctx,
+        Field::Catalog,
+        Field::MetaDetails,
+        Field::Library,
+        Field::StreamingServer,
+        Field::Player,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Ctx => "ctx",
+            Self::Catalog => "catalog",
+            Self::MetaDetails => "meta_details",
+            Self::Library => "library",
+            Self::StreamingServer => "streaming_server",
+            Self::Player => "player",
+        }
+    }
+
+    fn json(self, core_model: &CoreModel) -> String {
+        fn to_json(value: &impl Serialize) -> String {
+            serde_json::to_string_pretty(value).unwrap_or_else(|error| format!("Serialization failed: {}", error))
+        }
+        match self {
+            Self::Ctx => to_json(&core_model.ctx),
+            Self::Catalog => to_json(&core_model.catalog),
+            Self::MetaDetails => to_json(&core_model.meta_details),
+            Self::Library => to_json(&core_model.library),
+            Self::StreamingServer => to_json(&core_model.streaming_server),
+            Self::Player => to_json(&core_model.player),
+        }
+    }
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+pub enum Msg {
+    SelectRecord(usize),
+    StepBack,
+    StepForward,
+    GoLive,
+    SelectField(Field),
+    ToggleRecording,
+}
+
+pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut impl Orders<Msg>) {
+    let record_count = context.core_inspector.records().len();
+    match msg {
+        Msg::SelectRecord(index) => {
+            select_record(index, model, context);
+        }
+        Msg::StepBack => {
+            if record_count == 0 {
+                return
+            }
+            let index = model.selected_record.unwrap_or(record_count).saturating_sub(1);
+            select_record(index, model, context);
+        }
+        Msg::StepForward => {
+            match model.selected_record {
+                Some(index) if index + 1 < record_count => select_record(index + 1, model, context),
+                _ => {
+                    orders.skip().send_msg(Msg::GoLive);
+                }
+            }
+        }
+        Msg::GoLive => {
+            model.selected_record = None;
+            model.replayed_core_model = None;
+        }
+        Msg::SelectField(field) => {
+            model.field = field;
+        }
+        Msg::ToggleRecording => {
+            let recording = !context.core_inspector.is_recording();
+            context.core_inspector.set_recording(recording, &context.core_model);
+            orders.send_msg(Msg::GoLive);
+        }
+    }
+}
+
+fn select_record(index: usize, model: &mut Model, context: &Context) {
+    model.replayed_core_model = context.core_inspector.replay(index);
+    model.selected_record = model.replayed_core_model.as_ref().map(|_| index);
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+#[view]
+pub fn view(model: &Model, context: &Context) -> Node<Msg> {
+    let core_model = model.replayed_core_model.as_ref().unwrap_or(&context.core_model);
+    let selected_record = model
+        .selected_record
+        .and_then(|index| context.core_inspector.records().get(index));
+    div![
+        C!["inspector-container"],
+        s()
+            .background_color(Color::BackgroundDark2)
+            .color(Color::SurfaceLight5_90)
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Column)
+            .height(pc(100))
+            .padding(rem(2))
+            .width(pc(100)),
+        h1![
+            s()
+                .font_size(rem(1.8))
+                .margin_bottom(rem(1)),
+            "Core inspector",
+        ],
+        controls(model, context.core_inspector.is_recording()),
+        div![
+            s()
+                .display(CssDisplay::Flex)
+                .flex("1")
+                .flex_direction(CssFlexDirection::Row)
+                .min_height("0"),
+            records(context.core_inspector.records(), model.selected_record),
+            div![
+                s()
+                    .display(CssDisplay::Flex)
+                    .flex("2")
+                    .flex_direction(CssFlexDirection::Column)
+                    .min_width("0"),
+                selected_record.map(|record| code_block(&record.details(), "30%")),
+                field_buttons(model.field),
+                code_block(&model.field.json(core_model), "none"),
+            ],
+        ],
+    ]
+}
+
+#[view]
+fn controls(model: &Model, recording: bool) -> Node<Msg> {
+    div![
+        C!["inspector-controls"],
+        s()
+            .align_items(CssAlignItems::Center)
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Row)
+            .margin_bottom(rem(1)),
+        inspector_button("◀ Step back", false, || Msg::StepBack),
+        inspector_button("Step forward ▶", false, || Msg::StepForward),
+        inspector_button("Live", model.selected_record.is_none(), || Msg::GoLive),
+        inspector_button(
+            if recording { "Stop recording" } else { "Start recording" },
+            recording,
+            || Msg::ToggleRecording
+        ),
+        span![
+            s().margin_left(rem(0.5)),
+            match model.selected_record {
+                Some(index) => format!("Replayed state after #{}", index),
+                None => "Live state".to_owned(),
+            }
+        ],
+    ]
+}
+
+#[view]
+fn records(records: &std::collections::VecDeque<Record>, selected_record: Option<usize>) -> Node<Msg> {
+    div![
+        C!["inspector-records"],
+        s()
+            .flex("1")
+            .font_family("monospace")
+            .margin_right(rem(1))
+            .overflow_y(CssOverflowY::Auto),
+        records.iter().enumerate().rev().map(|(index, record)| {
+            let selected = selected_record == Some(index);
+            div![
+                C!["inspector-record", IF!(selected => "selected")],
+                s()
+                    .background_color(if selected { Color::Accent3 } else { Color::Transparent })
+                    .cursor(CssCursor::Pointer)
+                    .opacity(if record.has_changed { "1" } else { "0.6" })
+                    .padding("0.2rem 0.5rem"),
+                s()
+                    .hover()
+                    .background_color(Color::Accent3Light1),
+                attrs!{
+                    At::Title => if record.has_changed { "Changed the state" } else { "Didn't change the state" },
+                },
+                ev(Ev::Click, move |_| Msg::SelectRecord(index)),
+                format!(
+                    "#{} {} [{}] {}",
+                    index,
+                    record.time.format("%H:%M:%S%.3f"),
+                    record.source.as_str(),
+                    record.description(),
+                ),
+            ]
+        })
+    ]
+}
+
+#[view]
+fn field_buttons(active_field: Field) -> Node<Msg> {
+    div![
+        C!["inspector-fields"],
+        s()
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Row)
+            .flex_wrap(CssFlexWrap::Wrap)
+            .margin("0.5rem 0"),
+        Field::ALL.iter().map(|field| {
+            let field = *field;
+            inspector_button(field.name(), field == active_field, move || Msg::SelectField(field))
+        }),
+    ]
+}
+
+#[view]
+fn code_block(code: &str, max_height: &str) -> Node<Msg> {
+    pre![
+        C!["inspector-code"],
+        s()
+            .background_color(Color::BackgroundDark1)
+            .flex("1")
+            .font_family("monospace")
+            .max_height(max_height)
+            .overflow_y(CssOverflowY::Auto)
+            .padding(rem(0.5))
+            .user_select("text")
+            .white_space(CssWhiteSpace::PreWrap),
+        code,
+    ]
+}
+
+#[view]
+fn inspector_button(title: &str, active: bool, on_click: impl FnOnce() -> Msg + Clone + 'static) -> Node<Msg> {
+    div![
+        C!["inspector-button", "button-container", IF!(active => "active")],
+        s()
+            .background_color(if active { Color::Accent3 } else { Color::BackgroundLight1 })
+            .cursor(CssCursor::Pointer)
+            .margin_right(rem(0.5))
+            .padding("0.3rem 0.8rem"),
+        s()
+            .hover()
+            .background_color(Color::Accent3Light1),
+        attrs!{
+            At::TabIndex => 0,
+            At::Title => title,
+        },
+        ev(Ev::Click, move |_| on_click()),
+        title,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use stremio_core::runtime::msg::{Action, ActionCtx, Msg as CoreMsg};
+    use crate::Msg as RootMsg;
+    use crate::route::Route;
+    use crate::test_env::{self, TestApp};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    async fn add_to_library(app: &TestApp, id: &str) {
+        app.send(RootMsg::CoreMsg(Rc::new(CoreMsg::Action(Action::Ctx(
+            ActionCtx::AddToLibrary(test_env::meta_preview(id))
+        ))))).await;
+    }
+
+    fn selected_record(app: &TestApp) -> Option<usize> {
+        app.read(|model| model.model.inspector_model.as_ref().expect("inspector model").selected_record)
+    }
+
+    #[wasm_bindgen_test]
+    async fn stepping_moves_between_records_and_live() {
+        let app = TestApp::start().await;
+        app.open(Route::Inspector).await;
+        add_to_library(&app, "tt0000001").await;
+        add_to_library(&app, "tt0000002").await;
+        let record_count = app.read(|model| model.model.context.core_inspector.records().len());
+
+        app.send(RootMsg::InspectorMsg(Msg::StepBack)).await;
+        assert_eq!(selected_record(&app), Some(record_count - 1));
+        app.send(RootMsg::InspectorMsg(Msg::StepBack)).await;
+        assert_eq!(selected_record(&app), Some(record_count - 2));
+        app.send(RootMsg::InspectorMsg(Msg::StepForward)).await;
+        app.send(RootMsg::InspectorMsg(Msg::StepForward)).await;
+        assert_eq!(selected_record(&app), None);
+    }
+
+    #[wasm_bindgen_test]
+    async fn selected_record_shows_the_replayed_state() {
+        let app = TestApp::start().await;
+        app.open(Route::Inspector).await;
+        add_to_library(&app, "tt0000001").await;
+        add_to_library(&app, "tt0000002").await;
+        app.send(RootMsg::InspectorMsg(Msg::SelectRecord(0))).await;
+
+        let library_ids = app.read(|model| {
+            let inspector_model = model.model.inspector_model.as_ref().expect("inspector model");
+            let replayed_core_model = inspector_model.replayed_core_model.as_ref().expect("replayed core model");
+            replayed_core_model.ctx.library.items.keys().cloned().collect::<Vec<_>>()
+        });
+        assert_eq!(library_ids, vec!["tt0000001".to_owned()]);
+        assert!(app.read(|model| model.model.context.core_model.ctx.library.items.contains_key("tt0000002")));
+    }
+}
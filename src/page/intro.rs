@@ -10,45 +10,40 @@ use stremio_core::models::ctx::CtxError;
 use stremio_core::types::api::{AuthRequest, APIError};
 use crate::{multi_select, Msg as RootMsg, Context, PageId, Actions, Urls as RootUrls};
 use crate::basic_layout::{basic_layout, BasicLayoutArgs};
+use crate::route::{self, Route};
 use crate::styles::{self, themes::{Color, Breakpoint}, global};
 
 fn on_click_not_implemented() -> EventHandler<Msg> {
     ev(Ev::Click, |_| { window().alert_with_message("Not implemented!").unwrap(); })
 }
 
-// ---- url parts ----
-
-const LOGIN: &str = "login";
-
 // ------ ------
 //     Init
 // ------ ------
 
 pub fn init(
-    mut url: Url,
+    url: Url,
+    form_type: FormType,
+    return_to: Option<Route>,
     model: &mut Option<Model>,
     context: &mut Context,
     orders: &mut impl Orders<Msg>,
 ) -> Option<PageId> {
-    let base_url = url.to_hash_base_url();
+    let base_url = route::base_url(&url);
 
     orders.after_next_render(|_| Msg::FocusEmail);
 
-    let form_type = match url.next_hash_path_part() {
-        None => FormType::SignUp,
-        Some(LOGIN) => FormType::LogIn,
-        _ => return None,
-    };
-
     let model = model.get_or_insert_with(move || Model {
         base_url,
         _core_msg_sub_handle: orders.subscribe_with_handle(Msg::CoreMsg),
         form_type,
+        return_to: None,
         form_data: FormData::default(),
         email_input: ElRef::new(),
         form_error: None,
     });
     model.form_type = form_type;
+    model.return_to = return_to;
     Some(PageId::Intro)
 }
 
@@ -60,13 +55,15 @@ pub struct Model {
     base_url: Url,
     _core_msg_sub_handle: SubHandle,
     form_type: FormType,
+    // opened after authentication instead of the board
+    return_to: Option<Route>,
     form_data: FormData,
     email_input: ElRef<HtmlElement>,
     form_error: Option<FormError>,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum FormType {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormType {
     SignUp,
     LogIn,
 } 
@@ -81,6 +78,7 @@ struct FormData {
     marketing_checked: bool,
 }
 
+#[derive(Debug)]
 enum FormError {
     InvalidEmail,
     InvalidPassword,
@@ -94,10 +92,17 @@ enum FormError {
 struct_urls!();
 impl<'a> Urls<'a> {
     pub fn root(self) -> Url {
-        self.base_url()
+        self.form(FormType::SignUp, None)
     }
     pub fn login(self) -> Url {
-        self.base_url().add_hash_path_part(LOGIN)
+        self.form(FormType::LogIn, None)
+    }
+    pub fn form(self, form_type: FormType, return_to: Option<&Route>) -> Url {
+        Route::Intro {
+            form_type,
+            return_to: return_to.cloned().map(Box::new),
+        }
+        .to_url(&self.base_url())
     }
 }
 
@@ -123,7 +128,8 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
             match core_msg.as_ref() {
                 CoreMsg::Event(Event::UserAuthenticated {..}) => {
                     model.form_data = FormData::default();
-                    orders.request_url(RootUrls::new(&context.root_base_url).root());
+                    let return_to = model.return_to.take().unwrap_or(Route::Board);
+                    orders.request_url(return_to.to_url(&context.root_base_url));
                 }
                 CoreMsg::Event(Event::Error {error: CtxError::API(api_error), ..}) => {
                     model.form_error = Some(FormError::APIError(api_error.to_owned()));
@@ -153,27 +159,12 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
             model.email_input.get().map(|input| input.focus().expect("focus email input"));
         }
         Msg::Login => {
-            let email = &model.form_data.email;
-            let password = &model.form_data.password;
-
-            // Basic regex from https://www.w3schools.com/tags/att_input_pattern.asp
-            // @TODO replace with RFC 5321/5322? ; compile the regex only once in a lazy static?
-            let email_regex = Regex::new(r"^[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}$").unwrap();
-
-            if email.len() == 0 || not(email_regex.is_match(email)) {
-                model.form_error = Some(FormError::InvalidEmail);
-                return
-            }
-
-            if password.len() == 0 {
-                model.form_error = Some(FormError::InvalidPassword);
-                return
-            }
-
-            let auth_request = AuthRequest::Login {
-                email: email.to_owned(),
-                password: password.to_owned(),
-                facebook: false,
+            let auth_request = match login_request(&model.form_data) {
+                Ok(auth_request) => auth_request,
+                Err(form_error) => {
+                    model.form_error = Some(form_error);
+                    return
+                }
             };
             orders.notify(Actions::UpdateCoreModel(Rc::new(CoreMsg::Action(Action::Ctx(
                 ActionCtx::Authenticate(auth_request)
@@ -182,6 +173,29 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
     }
 }
 
+fn login_request(form_data: &FormData) -> Result<AuthRequest, FormError> {
+    let email = &form_data.email;
+    let password = &form_data.password;
+
+    // Basic regex from https://www.w3schools.com/tags/att_input_pattern.asp
+    // @TODO replace with RFC 5321/5322? ; compile the regex only once in a lazy static?
+    let email_regex = Regex::new(r"^[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}$").unwrap();
+
+    if email.len() == 0 || not(email_regex.is_match(email)) {
+        return Err(FormError::InvalidEmail)
+    }
+
+    if password.len() == 0 {
+        return Err(FormError::InvalidPassword)
+    }
+
+    Ok(AuthRequest::Login {
+        email: email.to_owned(),
+        password: password.to_owned(),
+        facebook: false,
+    })
+}
+
 // ------ ------
 //     View
 // ------ ------
@@ -228,7 +242,7 @@ fn form_container(model: &Model) -> Node<Msg> {
         logo_container(),
         facebook_button(),
         IF!(model.form_type == FormType::SignUp => {
-            login_form_button(&model.base_url)
+            login_form_button(&model.base_url, model.return_to.as_ref())
         }),
         email_input(&model.form_data.email, &model.email_input),
         password_input(&model.form_data.password),
@@ -247,7 +261,7 @@ fn form_container(model: &Model) -> Node<Msg> {
                 forgot_password_button(),
                 model.form_error.as_ref().map(error_message),
                 login_button(),
-                sign_up_with_email_button(&model.base_url),
+                sign_up_with_email_button(&model.base_url, model.return_to.as_ref()),
             ]
         }),
     ]
@@ -380,7 +394,7 @@ fn facebook_button() -> Node<Msg> {
 }
 
 #[view]
-fn login_form_button(base_url: &Url) -> Node<Msg> {
+fn login_form_button(base_url: &Url, return_to: Option<&Route>) -> Node<Msg> {
     a![
         C!["form-button", "login-form-button", "button-container"],
         s()
@@ -395,7 +409,7 @@ fn login_form_button(base_url: &Url) -> Node<Msg> {
             .text_decoration(CssTextDecoration::Underline),
         attrs!{
             At::TabIndex => 0,
-            At::Href => Urls::new(base_url).login(),
+            At::Href => Urls::new(base_url).form(FormType::LogIn, return_to),
         },
         "Already have an account? ",
         span![
@@ -827,7 +841,7 @@ fn login_button() -> Node<Msg> {
 }
 
 #[view]
-fn sign_up_with_email_button(base_url: &Url)-> Node<Msg> {
+fn sign_up_with_email_button(base_url: &Url, return_to: Option<&Route>)-> Node<Msg> {
     a![
         C!["form-button", "guest-login-button", "button-container"],
         s()
@@ -840,7 +854,7 @@ fn sign_up_with_email_button(base_url: &Url)-> Node<Msg> {
             .cursor(CssCursor::Pointer),
         attrs!{
             At::TabIndex => 0,
-            At::Href => Urls::new(base_url).root(),
+            At::Href => Urls::new(base_url).form(FormType::SignUp, return_to),
         },
         div![
             C!["label"],
@@ -859,3 +873,81 @@ fn sign_up_with_email_button(base_url: &Url)-> Node<Msg> {
         ]
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::{TestApp, TestEnv};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    fn form_data(email: &str, password: &str) -> FormData {
+        FormData {
+            email: email.to_owned(),
+            password: password.to_owned(),
+            ..FormData::default()
+        }
+    }
+
+    #[test]
+    fn valid_form_is_sent() {
+        let auth_request = login_request(&form_data("user@example.com", "secret"));
+        assert!(matches!(
+            auth_request,
+            Ok(AuthRequest::Login { email, password, facebook: false }) if email == "user@example.com" && password == "secret"
+        ));
+    }
+
+    #[test]
+    fn invalid_form_is_rejected() {
+        assert!(matches!(login_request(&form_data("", "secret")), Err(FormError::InvalidEmail)));
+        assert!(matches!(login_request(&form_data("user@example", "secret")), Err(FormError::InvalidEmail)));
+        assert!(matches!(login_request(&form_data("user@example.com", "")), Err(FormError::InvalidPassword)));
+    }
+
+    async fn fill_login_form(app: &TestApp, email: &str, password: &str) {
+        app.open(Route::Library(None).login_redirect()).await;
+        app.send(RootMsg::IntroMsg(Msg::EmailChanged(email.to_owned()))).await;
+        app.send(RootMsg::IntroMsg(Msg::PasswordChanged(password.to_owned()))).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn invalid_login_form_shows_an_error() {
+        let app = TestApp::start().await;
+        fill_login_form(&app, "user@example", "secret").await;
+        app.send(RootMsg::IntroMsg(Msg::Login)).await;
+
+        let invalid_email = app.read(|model| {
+            let intro_model = model.model.intro_model.as_ref().expect("intro model");
+            matches!(intro_model.form_error, Some(FormError::InvalidEmail))
+        });
+        assert!(invalid_email);
+        assert!(TestEnv::fetch_requests().iter().all(|request| !request.url.ends_with("/login")));
+    }
+
+    #[wasm_bindgen_test]
+    async fn valid_login_form_is_sent_to_the_api() {
+        let app = TestApp::start().await;
+        fill_login_form(&app, "user@example.com", "secret").await;
+        app.send(RootMsg::IntroMsg(Msg::Login)).await;
+
+        let login_request = TestEnv::fetch_requests()
+            .into_iter()
+            .find(|request| request.url.ends_with("/login"))
+            .expect("login request");
+        assert!(login_request.body.to_string().contains("user@example.com"));
+    }
+
+    #[wasm_bindgen_test]
+    async fn guarded_route_is_opened_after_authentication() {
+        let app = TestApp::start().await;
+        fill_login_form(&app, "user@example.com", "secret").await;
+        let auth_request = login_request(&form_data("user@example.com", "secret")).expect("valid form");
+        app.send(RootMsg::HandleEffectMsg(Rc::new(CoreMsg::Event(Event::UserAuthenticated { auth_request })))).await;
+
+        let requested_routes = app.read(|model| model.requested_urls.iter().map(Route::parse).collect::<Vec<_>>());
+        assert_eq!(requested_routes, vec![Some(Route::Library(None))]);
+        assert!(app.read(|model| model.model.intro_model.as_ref().expect("intro model").form_data.email.is_empty()));
+    }
+}
@@ -3,8 +3,12 @@ use seed_hooks::{*, topo::nested as view};
 use seed_styles::{em, pc, rem, Style};
 use seed_styles::*;
 use std::rc::Rc;
+use web_sys::HtmlElement;
 use crate::{multi_select, Msg as RootMsg, Context, PageId, Actions, Events, Urls as RootUrls, ActionCtx};
+use crate::history_state::{self, HistoryStates};
 use crate::basic_layout::{basic_layout, BasicLayoutArgs};
+use crate::route::{self, Route};
+use crate::page::intro::FormType;
 use crate::styles::{self, themes::{Color, Breakpoint}, global};
 use stremio_core::runtime::msg::{Action, ActionLoad, Msg as CoreMsg};
 use stremio_core::types::library::LibraryItem;
@@ -25,54 +29,71 @@ mod sort_selector;
 // ------ ------
 
 pub fn init(
-    mut url: Url,
+    url: Url,
+    library_request: Option<LibraryRequest>,
+    history_traversal: bool,
     model: &mut Option<Model>,
     context: &mut Context,
     orders: &mut impl Orders<Msg>,
 ) -> Option<PageId> {
-    let base_url = url.to_hash_base_url();
-
-    let library_request = match url.remaining_hash_path_parts().as_slice() {
-        [type_, sort, page] => {
-            (|| Some(LibraryRequest {
-                r#type: if *type_ == "all" { None } else { Some(type_.to_string()) },
-                sort: match *sort {
-                    "last_watched" => Sort::LastWatched,
-                    "name" => Sort::Name,
-                    "times_watched" => Sort::TimesWatched,
-                    _ => None?
-                },
-                page: LibraryRequestPage(page.parse().ok()?),
-            }))()
-        }
-        _ => None,
-    };
+    let base_url = route::base_url(&url);
+    let history_entry_id = history_state::current_entry_id();
 
-    load_library(library_request.clone(), orders);
+    // the previous library is still rendered when the page changes only its request
+    if let Some(model) = model.as_mut().filter(|model| model.active) {
+        save_history_state(model);
+    }
 
-    let mut model = model.get_or_insert_with(move || Model {
+    let mut model = model.get_or_insert_with(|| Model {
         base_url,
+        history_entry_id: history_entry_id.clone(),
+        active: true,
         library_request: None,
         selected_library_item: None,
+        meta_items_ref: ElRef::new(),
+        history_states: HistoryStates::default(),
         _events_sub_handle: orders.subscribe_with_handle(|events| {
             Some(match events {
                 Events::CtxLoaded => Msg::ReloadLibrary,
                 Events::WindowClicked => Msg::WindowClicked,
+                Events::PageChanged(page_id) if page_id != PageId::Library => Msg::PageLeft,
                 _ => return None
             })
         }),
     });
+
+    let history_state = history_traversal
+        .then(|| model.history_states.get(&history_entry_id).cloned())
+        .flatten();
+    let request = library_request.clone().unwrap_or_else(default_library_request);
+    let library_loaded = context
+        .core_model
+        .library
+        .selected
+        .as_ref()
+        .map_or(false, |selected| selected.request == request);
+    match history_state {
+        Some(history_state) if library_loaded => {
+            model.selected_library_item = history_state.selected_library_item;
+            orders.after_next_render(move |_| Msg::RestoreScroll(history_state.scroll_top));
+        }
+        _ => load_library(library_request.clone(), orders),
+    }
     model.library_request = library_request;
+    model.history_entry_id = history_entry_id;
+    model.active = true;
     Some(PageId::Library)
 }
 
 fn load_library(library_request: Option<LibraryRequest>, orders: &mut impl Orders<Msg>) {
+    orders.notify(Actions::UpdateCoreModel(Rc::new(load_library_msg(library_request))));
+}
+
+fn load_library_msg(library_request: Option<LibraryRequest>) -> CoreMsg {
     let selected_library = LibraryWithFiltersSelected {
         request: library_request.unwrap_or_else(default_library_request)
     };
-    orders.notify(Actions::UpdateCoreModel(Rc::new(CoreMsg::Action(Action::Load(
-        ActionLoad::LibraryWithFilters(selected_library),
-    )))));
+    CoreMsg::Action(Action::Load(ActionLoad::LibraryWithFilters(selected_library)))
 }
 
 pub fn default_library_request() -> LibraryRequest {
@@ -89,11 +110,31 @@ pub fn default_library_request() -> LibraryRequest {
 
 pub struct Model {
     base_url: Url,
+    // the page's history state is saved under it
+    history_entry_id: String,
+    // the page is displayed
+    active: bool,
     library_request: Option<LibraryRequest>,
     selected_library_item: Option<String>,
+    meta_items_ref: ElRef<HtmlElement>,
+    history_states: HistoryStates<HistoryState>,
     _events_sub_handle: SubHandle,
 }
 
+#[derive(Clone)]
+struct HistoryState {
+    scroll_top: i32,
+    selected_library_item: Option<String>,
+}
+
+fn save_history_state(model: &mut Model) {
+    let history_state = HistoryState {
+        scroll_top: model.meta_items_ref.get().map_or(0, |meta_items| meta_items.scroll_top()),
+        selected_library_item: model.selected_library_item.clone(),
+    };
+    model.history_states.save(&model.history_entry_id, history_state);
+}
+
 // ------ ------
 //     Urls
 // ------ ------
@@ -101,27 +142,10 @@ pub struct Model {
 struct_urls!();
 impl<'a> Urls<'a> {
     pub fn root(self) -> Url {
-        self.base_url()
+        Route::Library(None).to_url(&self.base_url())
     }
     pub fn library_request(self, library_request: &LibraryRequest) -> Url {
-        let type_ = library_request
-            .r#type
-            .as_ref()
-            .map(String::as_str)
-            .unwrap_or("all");
-
-        let sort = match library_request.sort {
-            Sort::LastWatched => "last_watched",
-            Sort::Name => "name",
-            Sort::TimesWatched => "times_watched",
-        };
-
-        let page = library_request.page.to_string();
-
-        self.base_url()
-            .add_hash_path_part(type_)
-            .add_hash_path_part(sort)
-            .add_hash_path_part(page)
+        Route::Library(Some(library_request.clone())).to_url(&self.base_url())
     }
 }
 
@@ -136,6 +160,8 @@ pub enum Msg {
     RemoveLibraryItem(String),
     WindowClicked,
     SendLibraryRequest(LibraryRequest),
+    PageLeft,
+    RestoreScroll(i32),
 }
 
 pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
@@ -160,6 +186,19 @@ pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
         Msg::SendLibraryRequest(library_request) => {
             orders.request_url(Urls::new(&model.base_url).library_request(&library_request));
         }
+        Msg::PageLeft => {
+            orders.skip();
+            if model.active {
+                save_history_state(model);
+                model.active = false;
+            }
+        }
+        Msg::RestoreScroll(scroll_top) => {
+            orders.skip();
+            if let Some(meta_items) = model.meta_items_ref.get() {
+                meta_items.set_scroll_top(scroll_top);
+            }
+        }
     }
 }
 
@@ -198,6 +237,7 @@ fn library_content<'a>(model: &Model, context: &Context) -> Node<Msg> {
                     meta_items_container(
                         library_items, 
                         model.selected_library_item.as_ref(), 
+                        &model.meta_items_ref,
                         &context.root_base_url
                     )
                 }
@@ -372,9 +412,15 @@ fn pagination_next_button(next_page_request: Option<&LibraryRequest>) -> Node<Ms
 }
 
 #[view]
-fn meta_items_container(library_items: &[LibraryItem], selected_library_item: Option<&String>, root_base_url: &Url) -> Node<Msg> {
+fn meta_items_container(
+    library_items: &[LibraryItem],
+    selected_library_item: Option<&String>,
+    meta_items_ref: &ElRef<HtmlElement>,
+    root_base_url: &Url,
+) -> Node<Msg> {
     div![
         C!["meta-items-container"],
+        el_ref(meta_items_ref),
         s()
             .align_items(CssAlignItems::Center)
             .align_self(CssAlignSelf::Stretch)
@@ -703,7 +749,7 @@ fn login_button(root_url_base: &Url) -> Node<Msg> {
             .hover()
             .color(Color::Accent3Light1),
         attrs!{
-            At::Href => RootUrls::new(root_url_base).intro(),
+            At::Href => RootUrls::new(root_url_base).intro_urls().form(FormType::LogIn, Some(&Route::Library(None))),
             At::TabIndex => "0",
         },
         div![
@@ -721,3 +767,69 @@ fn login_button(root_url_base: &Url) -> Node<Msg> {
         ]
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::{self, TestApp};
+    use stremio_core::runtime::msg::ActionCtx;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[test]
+    fn library_items_are_listed() {
+        let mut core_model = test_env::new_core_model();
+        test_env::dispatch(&mut core_model, ActionCtx::AddToLibrary(test_env::meta_preview("tt0000001")));
+        test_env::update(&mut core_model, &load_library_msg(None));
+
+        assert_eq!(
+            core_model.library.selected.as_ref().map(|selected| &selected.request),
+            Some(&default_library_request()),
+        );
+        assert!(core_model.library.catalog.iter().any(|item| item.id == "tt0000001"));
+    }
+
+    #[test]
+    fn library_items_are_filtered_by_type() {
+        let mut core_model = test_env::new_core_model();
+        test_env::dispatch(&mut core_model, ActionCtx::AddToLibrary(test_env::meta_preview("tt0000001")));
+        let library_request = LibraryRequest {
+            r#type: Some("series".to_owned()),
+            ..default_library_request()
+        };
+        test_env::update(&mut core_model, &load_library_msg(Some(library_request)));
+
+        assert!(core_model.library.catalog.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn removed_items_leave_the_library() {
+        let app = TestApp::start().await;
+        app.send(RootMsg::CoreMsg(Rc::new(CoreMsg::Action(Action::Ctx(
+            ActionCtx::AddToLibrary(test_env::meta_preview("tt0000001"))
+        ))))).await;
+        app.open(Route::Library(None)).await;
+        let catalog_ids = || app.read(|model| {
+            model.model.context.core_model.library.catalog.iter().map(|item| item.id.clone()).collect::<Vec<_>>()
+        });
+        assert_eq!(catalog_ids(), vec!["tt0000001".to_owned()]);
+
+        app.send(RootMsg::LibraryMsg(Msg::RemoveLibraryItem("tt0000001".to_owned()))).await;
+        assert!(catalog_ids().is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn library_requests_open_their_route() {
+        let app = TestApp::start().await;
+        app.open(Route::Library(None)).await;
+        let library_request = LibraryRequest {
+            r#type: Some("series".to_owned()),
+            ..default_library_request()
+        };
+        app.send(RootMsg::LibraryMsg(Msg::SendLibraryRequest(library_request.clone()))).await;
+
+        let requested_routes = app.read(|model| model.requested_urls.iter().map(Route::parse).collect::<Vec<_>>());
+        assert_eq!(requested_routes, vec![Some(Route::Library(Some(library_request)))]);
+    }
+}
@@ -0,0 +1,308 @@
+use seed::{prelude::*, *};
+use seed_hooks::{*, topo::nested as view};
+use seed_styles::{em, pc, rem, Style};
+use seed_styles::*;
+use wasm_bindgen_futures::JsFuture;
+use crate::{PageId, Events};
+use crate::logger::{self, Entry, Level};
+use crate::styles::{self, themes::Color, global};
+
+const REFRESH_INTERVAL: u32 = 1000;
+
+// ------ ------
+//     Init
+// ------ ------
+
+pub fn init(
+    _url: Url,
+    model: &mut Option<Model>,
+    orders: &mut impl Orders<Msg>,
+) -> Option<PageId> {
+    let model = model.get_or_insert_with(|| Model {
+        level_filter: Level::Trace,
+        target_filter: String::new(),
+        entries: Vec::new(),
+        copy_status: None,
+        refresh_handle: None,
+        page_change_sub_handle: None,
+    });
+    model.entries = logger::entries();
+    model.refresh_handle = Some(orders.stream_with_handle(streams::interval(REFRESH_INTERVAL, || Msg::Refresh)));
+    model.page_change_sub_handle = Some(orders.subscribe_with_handle(|events| {
+        matches!(events, Events::PageChanged(page_id) if page_id != PageId::Logs)
+            .then(|| Msg::StopRefreshing)
+    }));
+    Some(PageId::Logs)
+}
+
+// ------ ------
+//     Model
+// ------ ------
+
+pub struct Model {
+    level_filter: Level,
+    target_filter: String,
+    entries: Vec<Entry>,
+    copy_status: Option<&'static str>,
+    refresh_handle: Option<StreamHandle>,
+    page_change_sub_handle: Option<SubHandle>,
+}
+
+impl Model {
+    fn filtered_entries(&self) -> impl DoubleEndedIterator<Item = &Entry> {
+        let target_filter = self.target_filter.trim();
+        self.entries.iter().filter(move |entry| {
+            entry.level <= self.level_filter && entry.target.starts_with(target_filter)
+        })
+    }
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+pub enum Msg {
+    Refresh,
+    StopRefreshing,
+    SetLevelFilter(Level),
+    TargetFilterChanged(String),
+    SetMaxLevel(Level),
+    Copy,
+    Copied(Result<(), JsValue>),
+    Clear,
+}
+
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::Refresh => {
+            let entries = logger::entries();
+            if entries == model.entries {
+                orders.skip();
+                return
+            }
+            model.entries = entries;
+        }
+        Msg::StopRefreshing => {
+            model.refresh_handle = None;
+            model.page_change_sub_handle = None;
+            model.copy_status = None;
+        }
+        Msg::SetLevelFilter(level) => {
+            model.level_filter = level;
+        }
+        Msg::TargetFilterChanged(target) => {
+            model.target_filter = target;
+        }
+        Msg::SetMaxLevel(level) => {
+            logger::set_max_level(level);
+        }
+        Msg::Copy => {
+            let text = model
+                .filtered_entries()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n");
+            orders.perform_cmd(async move {
+                Msg::Copied(JsFuture::from(copy_text(&text)).await.map(|_| ()))
+            });
+        }
+        Msg::Copied(Ok(())) => {
+            model.copy_status = Some("Copied");
+        }
+        Msg::Copied(Err(error)) => {
+            log_error!("page::logs", "Copying logs failed: {:?}", error);
+            model.copy_status = Some("Copying failed");
+        }
+        Msg::Clear => {
+            logger::clear();
+            model.entries.clear();
+            model.copy_status = None;
+        }
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+#[view]
+pub fn view(model: &Model) -> Node<Msg> {
+    div![
+        C!["logs-container"],
+        s()
+            .background_color(Color::BackgroundDark2)
+            .color(Color::SurfaceLight5_90)
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Column)
+            .height(pc(100))
+            .padding(rem(2))
+            .width(pc(100)),
+        h1![
+            s()
+                .font_size(rem(1.8))
+                .margin_bottom(rem(1)),
+            "Logs",
+        ],
+        controls(model),
+        entries(model),
+    ]
+}
+
+#[view]
+fn controls(model: &Model) -> Vec<Node<Msg>> {
+    let max_level = logger::max_level();
+    vec![
+        level_buttons("Show", model.level_filter, Msg::SetLevelFilter),
+        level_buttons("Record", max_level, Msg::SetMaxLevel),
+        div![
+            C!["logs-actions"],
+            s()
+                .align_items(CssAlignItems::Center)
+                .display(CssDisplay::Flex)
+                .flex_direction(CssFlexDirection::Row)
+                .margin_bottom(rem(1)),
+            input![
+                C!["target-input"],
+                s()
+                    .border("thin solid hsl(0deg 0% 100%)")
+                    .color(hsl(0, 0, 100))
+                    .margin_right(rem(0.5))
+                    .padding(rem(0.5))
+                    .width(rem(14)),
+                styles::text_input(),
+                attrs!{
+                    At::Placeholder => "Target (e.g. env, page::player)",
+                    At::Value => model.target_filter,
+                },
+                input_ev(Ev::Input, Msg::TargetFilterChanged),
+            ],
+            logs_button("Copy", false, || Msg::Copy),
+            logs_button("Clear", false, || Msg::Clear),
+            model.copy_status.map(|status| span![s().margin_left(rem(0.5)), status]),
+        ],
+    ]
+}
+
+#[view]
+fn level_buttons(title: &str, active_level: Level, on_click: fn(Level) -> Msg) -> Node<Msg> {
+    div![
+        C!["level-buttons"],
+        s()
+            .align_items(CssAlignItems::Center)
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Row)
+            .margin_bottom(rem(0.5)),
+        span![
+            s()
+                .margin_right(rem(0.5))
+                .width(rem(4)),
+            title,
+        ],
+        Level::ALL.iter().map(|level| {
+            let level = *level;
+            logs_button(level.as_str(), level == active_level, move || on_click(level))
+        }),
+    ]
+}
+
+#[view]
+fn logs_button(title: &str, active: bool, on_click: impl FnOnce() -> Msg + Clone + 'static) -> Node<Msg> {
+    div![
+        C!["logs-button", "button-container", IF!(active => "active")],
+        s()
+            .background_color(if active { Color::Accent3 } else { Color::BackgroundLight1 })
+            .cursor(CssCursor::Pointer)
+            .margin_right(rem(0.5))
+            .padding("0.3rem 0.8rem"),
+        s()
+            .hover()
+            .background_color(Color::Accent3Light1),
+        attrs!{
+            At::TabIndex => 0,
+            At::Title => title,
+        },
+        ev(Ev::Click, move |_| on_click()),
+        title,
+    ]
+}
+
+#[view]
+fn entries(model: &Model) -> Node<Msg> {
+    div![
+        C!["log-entries"],
+        s()
+            .flex("1")
+            .font_family("monospace")
+            .overflow_y(CssOverflowY::Auto)
+            .user_select("text"),
+        model.filtered_entries().rev().map(|entry| {
+            div![
+                C!["log-entry"],
+                s()
+                    .color(level_color(entry.level))
+                    .padding("0.2rem 0")
+                    .white_space(CssWhiteSpace::PreWrap),
+                entry.to_string(),
+            ]
+        })
+    ]
+}
+
+fn level_color(level: Level) -> Color {
+    match level {
+        Level::Error => Color::Signal2,
+        Level::Warn => Color::Signal1,
+        Level::Info => Color::SurfaceLight5_90,
+        Level::Debug | Level::Trace => Color::SurfaceLight5_60,
+    }
+}
+
+// ------ ------
+//    Extern
+// ------ ------
+
+#[wasm_bindgen(module = "/js/clipboard.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = copyText)]
+    fn copy_text(text: &str) -> js_sys::Promise;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Msg as RootMsg;
+    use crate::route::Route;
+    use crate::test_env::TestApp;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn entries_are_filtered_by_level_and_target() {
+        let app = TestApp::start().await;
+        app.open(Route::Logs).await;
+        app.send(RootMsg::LogsMsg(Msg::Clear)).await;
+        log_info!("page::search", "shown");
+        log_debug!("page::search", "too detailed");
+        log_info!("core", "other target");
+        app.send(RootMsg::LogsMsg(Msg::Refresh)).await;
+        app.send(RootMsg::LogsMsg(Msg::SetLevelFilter(Level::Info))).await;
+        app.send(RootMsg::LogsMsg(Msg::TargetFilterChanged(" page ".to_owned()))).await;
+
+        let messages = app.read(|model| {
+            let logs_model = model.model.logs_model.as_ref().expect("logs model");
+            logs_model.filtered_entries().map(|entry| entry.message.clone()).collect::<Vec<_>>()
+        });
+        assert_eq!(messages, vec!["shown".to_owned()]);
+    }
+
+    #[wasm_bindgen_test]
+    async fn refreshing_stops_when_the_page_is_left() {
+        let app = TestApp::start().await;
+        app.open(Route::Logs).await;
+        assert!(app.read(|model| model.model.logs_model.as_ref().expect("logs model").refresh_handle.is_some()));
+
+        app.open(Route::Settings).await;
+        assert!(app.read(|model| model.model.logs_model.as_ref().expect("logs model").refresh_handle.is_none()));
+    }
+}
@@ -7,36 +7,62 @@ use std::rc::Rc;
 use std::array;
 use std::convert::{TryFrom, TryInto};
 use enclose::enc;
-use serde::Serialize;
-use crate::{PageId, Context, Actions, Events};
-use crate::styles::{self, themes::{Color, Breakpoint}, global};
+use serde::{Deserialize, Serialize};
+use crate::{PageId, Context, Actions, Events, Urls as RootUrls};
+use crate::env::WebEnv;
+use crate::analytics::{AnalyticsEvent, StreamSourceType};
+use crate::relay;
+use crate::route::{self, Route};
+use crate::remote_control::{self, RemoteMessage, PlayerCommand, PlayerStatus};
+use crate::styles::{self, themes::{self, Color, Breakpoint}, global};
 use stremio_core::types::resource::{Stream, StreamSource};
 use stremio_core::models::player::Selected as PlayerSelected;
+use stremio_core::models::common::Loadable;
+use stremio_core::runtime::{Env, EnvError};
 use stremio_core::runtime::msg::{Action, ActionLoad, Msg as CoreMsg, Internal};
 use js_sys::Reflect;
+use seed::browser::web_socket::{WebSocket, WebSocketMessage};
 
 mod nav_bar;
-mod control_bar;
+pub(crate) mod control_bar;
+pub(crate) mod skip_markers;
+mod watch_party;
+mod remote_pairing;
 
 use nav_bar::nav_bar;
 use control_bar::control_bar;
+use skip_markers::{SkipMarkers, SkipMarkerKind};
+use watch_party::{watch_party_panel, WatchParty, PartyMessage, PlaybackCommand, Envelope};
+use remote_pairing::{pairing_panel, RemotePairing, PairingState};
+
+const CONTROLS_HIDE_DELAY: u32 = 3000;
+const DOUBLE_TAP_DELAY: u32 = 300;
+const TOUCH_SEEK_STEP: i32 = 10;
+const SWIPE_THRESHOLD: f64 = 10.;
 
 // ------ ------
 //     Init
 // ------ ------
 
 pub fn init(
-    mut url: Url,
+    url: Url,
+    stream: Stream,
+    video_path: Option<VideoPath>,
     model: &mut Option<Model>,
     context: &mut Context,
     orders: &mut impl Orders<Msg>,
 ) -> Option<PageId> {
-    let base_url = url.to_hash_base_url();
-
-    let stream: Stream = serde_json::from_str(url.next_hash_path_part()?).ok()?;
+    let base_url = route::base_url(&url);
 
     load_player(stream.clone(), orders);
 
+    if let Some(video_path) = &video_path {
+        let storage_key = skip_markers::storage_key(&video_path.meta_id);
+        orders.perform_cmd(async move {
+            Msg::SkipMarkersLoaded(WebEnv::get_storage::<SkipMarkers>(&storage_key).await)
+        });
+    }
+
     orders.after_next_render(|_| Msg::Rendered);
     
     let mut model = model.get_or_insert_with(move || Model {
@@ -49,34 +75,64 @@ pub fn init(
                 .then(|| Msg::DestroyPlayer)
         }),
         playing: false,
+        playback_started: false,
         muted: false,
         volume: 100,
         active_volume_slider: false,
         active_seek_slider: false, 
         time: None, 
         duration: None,
+        seek_hover_time: None,
+        video_path: None,
+        skip_markers: SkipMarkers::default(),
+        skip_markers_menu_visible: false,
+        next_video_requested: false,
+        controls_visible: true,
+        controls_hider: None,
+        touch_gesture: None,
+        pending_tap: None,
+        watch_party: None,
+        watch_party_panel_visible: false,
+        watch_party_room_input: String::new(),
+        remote_pairing: None,
     });
+    if let Some(watch_party) = model.watch_party.as_mut() {
+        share_stream(watch_party, &stream, video_path.clone());
+    }
     model.stream = Some(stream);
     model.playing = false;
+    model.playback_started = false;
     model.muted = false;
     model.volume = 100;
     model.active_volume_slider = false;
     model.active_seek_slider = false;
     model.time = None;
     model.duration = None;
+    model.seek_hover_time = None;
+    model.video_path = video_path;
+    model.skip_markers = SkipMarkers::default();
+    model.skip_markers_menu_visible = false;
+    model.next_video_requested = false;
+    model.controls_visible = true;
+    model.controls_hider = None;
+    model.touch_gesture = None;
+    model.pending_tap = None;
     Some(PageId::Player)
 }
 
+
 fn load_player(stream: Stream, orders: &mut impl Orders<Msg>) {
+    orders.notify(Actions::UpdateCoreModel(Rc::new(load_player_msg(stream))));
+}
+
+fn load_player_msg(stream: Stream) -> CoreMsg {
     let player_selected = PlayerSelected {
         stream,
         meta_request: None,
         stream_request: None,
         subtitles_path: None
     };
-    orders.notify(Actions::UpdateCoreModel(Rc::new(CoreMsg::Action(Action::Load(
-        ActionLoad::Player(player_selected),
-    )))));
+    CoreMsg::Action(Action::Load(ActionLoad::Player(player_selected)))
 }
 
 // ------ ------
@@ -90,12 +146,64 @@ pub struct Model {
     stream: Option<Stream>,
     page_change_sub_handle: SubHandle,
     playing: bool,
+    // `PlayerStarted` is emitted once the stream is actually playing
+    playback_started: bool,
     muted: bool,
     volume: u32,
     active_volume_slider: bool,
     active_seek_slider: bool, 
     time: Option<u32>, 
     duration: Option<u32>,
+    seek_hover_time: Option<u32>,
+    video_path: Option<VideoPath>,
+    skip_markers: SkipMarkers,
+    skip_markers_menu_visible: bool,
+    next_video_requested: bool,
+    controls_visible: bool,
+    controls_hider: Option<CmdHandle>,
+    touch_gesture: Option<TouchGesture>,
+    pending_tap: Option<PendingTap>,
+    // survives navigation between streams, left on `DestroyPlayer`
+    watch_party: Option<WatchParty>,
+    watch_party_panel_visible: bool,
+    watch_party_room_input: String,
+    remote_pairing: Option<RemotePairing>,
+}
+
+struct TouchGesture {
+    start_y: f64,
+    start_volume: u32,
+    swiping: bool,
+}
+
+/// The first tap of a potential double-tap; dropping `_single_tap_handle` cancels `Msg::SingleTap`.
+struct PendingTap {
+    right_side: bool,
+    _single_tap_handle: CmdHandle,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TouchPosition {
+    // horizontal position relative to the touched element, `0.` = left edge, `1.` = right edge
+    x_ratio: f64,
+    y: f64,
+    height: f64,
+}
+
+/// Identifies the played video when the player has been opened from a meta item.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoPath {
+    pub type_name: String,
+    pub meta_id: String,
+    pub video_id: String,
+}
+
+/// A labeled point on the seek bar, e.g. the end of an intro, see `SkipMarkers::to_markers`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    pub time: u32,
+    pub label: String,
 }
 
 pub struct Youtube {
@@ -117,7 +225,22 @@ pub struct Youtube {
 struct_urls!();
 impl<'a> Urls<'a> {
     pub fn stream(self, stream: &Stream) -> Url {
-        self.base_url().add_hash_path_part(serde_json::to_string(stream).unwrap())
+        Route::Player {
+            stream: stream.clone(),
+            video_path: None,
+        }
+        .to_url(&self.base_url())
+    }
+    pub fn video_stream(self, stream: &Stream, type_name: &str, meta_id: &str, video_id: &str) -> Url {
+        Route::Player {
+            stream: stream.clone(),
+            video_path: Some(VideoPath {
+                type_name: type_name.to_owned(),
+                meta_id: meta_id.to_owned(),
+                video_id: video_id.to_owned(),
+            }),
+        }
+        .to_url(&self.base_url())
     }
 }
 
@@ -139,8 +262,38 @@ pub enum Msg {
     DeactivateVolumeSlider,
     ActivateSeekSlider(u32),
     SeekSliderMoved(u32),
+    SeekSliderHovered(u32),
+    SeekSliderLeft,
     DeactivateSeekSlider,
+    SeekBy(i32),
+    SeekTo(u32),
     OnTimerUpdaterTick,
+    SkipMarkersLoaded(Result<Option<SkipMarkers>, EnvError>),
+    ToggleSkipMarkersMenu,
+    MarkSkipMarker(SkipMarkerKind),
+    ClearSkipMarkers,
+    SkipIntro,
+    PlayNextVideo,
+    PointerMoved,
+    HideControls,
+    TouchStarted(TouchPosition),
+    TouchMoved(TouchPosition),
+    TouchEnded(TouchPosition),
+    SingleTap,
+    ToggleWatchPartyPanel,
+    WatchPartyRoomInputChanged(String),
+    CreateWatchParty,
+    JoinWatchParty,
+    LeaveWatchParty,
+    WatchPartyConnected,
+    WatchPartyMessageReceived(WebSocketMessage),
+    WatchPartyDisconnected,
+    WatchPartyHeartbeat,
+    ToggleRemotePairing,
+    ConfirmRemotePairing,
+    RemotePairingConnected,
+    RemoteMessageReceived(WebSocketMessage),
+    RemotePairingDisconnected,
 }
 
 pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut impl Orders<Msg>) {
@@ -150,7 +303,7 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
                 StreamSource::YouTube { yt_id } => {
                     model.youtube = Some(init_youtube(&model.video_ref, yt_id.clone(), orders));
                 }
-                stream_source => error!("Unhandled stream source"),
+                stream_source => log_error!("player", "Unhandled stream source"),
             }
         }
         Msg::YoutubeReady(video_container, yt_id) => {
@@ -197,7 +350,7 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
             Reflect::set(&config, &"playerVars".into(), &player_vars).unwrap();
             Reflect::set(&config, &"events".into(), &events).unwrap();
 
-            log!("Youtube config:", config);
+            log_debug!("player", "Youtube config: {:?}", config);
             if let Some(youtube) = model.youtube.as_mut() {
                 youtube.player = Some(Player::new(&video_container, config));
                 youtube.on_player_ready = Some(on_ready);
@@ -213,7 +366,7 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
                 Some(player) => player,
                 _ => return
             };
-            log!("Youtube player ready");
+            log_debug!("player", "Youtube player ready");
             model.time = Some(player.get_current_time());
             model.duration = Some(player.get_duration());
             youtube.time_updater_handle = Some(orders.stream_with_handle(
@@ -222,13 +375,35 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
         }
         Msg::YoutubePlayerStateChanged(state) => {
             match state {
-                YoutubePlayerState::Playing => model.playing = true,
-                YoutubePlayerState::Paused | YoutubePlayerState::Ended => model.playing = false,
+                YoutubePlayerState::Playing => {
+                    model.playing = true;
+                    if !model.playback_started {
+                        model.playback_started = true;
+                        if let Some(stream) = &model.stream {
+                            orders.notify(Actions::EmitAnalyticsEvent(AnalyticsEvent::PlayerStarted {
+                                source: StreamSourceType::from(&stream.source),
+                            }));
+                        }
+                    }
+                    schedule_controls_hiding(model, orders);
+                }
+                YoutubePlayerState::Paused | YoutubePlayerState::Ended => {
+                    model.playing = false;
+                    model.controls_visible = true;
+                    model.controls_hider = None;
+                }
                 _ => (),
             }
-            log!(state);
+            send_remote_status(model, context);
+            log_trace!("player", "Youtube state: {:?}", state);
         }
         Msg::DestroyPlayer => {
+            if let Some(stream) = &model.stream {
+                orders.notify(Actions::EmitAnalyticsEvent(AnalyticsEvent::PlayerStopped {
+                    source: StreamSourceType::from(&stream.source),
+                    time: model.time,
+                }));
+            }
             if let Some(mut youtube) = model.youtube.take() {
                 if let Some(player) = youtube.player.take() {
                     player.destroy();
@@ -236,19 +411,25 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
                 youtube.video_container.remove();
                 youtube.api_script.remove();
             }
+            model.watch_party = None;
+            model.watch_party_panel_visible = false;
+            model.remote_pairing = None;
         }
         Msg::ToggleFullscreen => {
             orders.notify(Actions::ToggleFullscreen);
         }
         Msg::TogglePlay => {
-            let player = match model.youtube.as_ref() {
-                Some(Youtube { player: Some(player), .. }) => player,
+            let player = match youtube_player(model) {
+                Some(player) => player,
                 _ => return
             };
+            let time = model.time.unwrap_or_default();
             if model.playing {
                 player.pause_video();
+                broadcast(model, PartyMessage::Pause { time });
             } else {
                 player.play_video();
+                broadcast(model, PartyMessage::Play { time });
             }
         }
         Msg::ToggleMute => {
@@ -270,6 +451,7 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
         }
         Msg::VolumeSliderMoved(volume) => {
             set_volume(volume, model);
+            send_remote_status(model, context);
         }
         Msg::DeactivateVolumeSlider => {
             model.active_volume_slider = false;
@@ -278,27 +460,326 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
             model.active_seek_slider = true;
             set_time(time, model);
         }
+        // dragging is broadcasted once the slider is released
         Msg::SeekSliderMoved(time) => {
+            model.seek_hover_time = Some(time);
             set_time(time, model);
         }
+        Msg::SeekSliderHovered(time) => {
+            if model.seek_hover_time == Some(time) {
+                orders.skip();
+                return
+            }
+            model.seek_hover_time = Some(time);
+        }
+        Msg::SeekSliderLeft => {
+            if model.active_seek_slider {
+                broadcast(model, PartyMessage::Seek { time: model.time.unwrap_or_default() });
+            }
+            model.active_seek_slider = false;
+            model.seek_hover_time = None;
+        }
         Msg::DeactivateSeekSlider => {
+            if model.active_seek_slider {
+                broadcast(model, PartyMessage::Seek { time: model.time.unwrap_or_default() });
+            }
             model.active_seek_slider = false;
         }
+        Msg::SeekBy(offset) => {
+            let (time, duration) = match (model.time, model.duration) {
+                (Some(time), Some(duration)) => (time, duration),
+                _ => return
+            };
+            let time = seek_target(time, duration, offset);
+            set_time(time, model);
+            broadcast(model, PartyMessage::Seek { time });
+        }
+        Msg::SeekTo(time) => {
+            set_time(time, model);
+            broadcast(model, PartyMessage::Seek { time });
+        }
         Msg::OnTimerUpdaterTick => {
             let player = match model.youtube.as_ref() {
                 Some(Youtube { player: Some(player), .. }) => player,
                 _ => return
             };
-            let time = Some(player.get_current_time());
-            if time == model.time {
+            let time = player.get_current_time();
+            if Some(time) == model.time {
+                orders.skip();
+                return
+            }
+            model.time = Some(time);
+            send_remote_status(model, context);
+
+            let binge_watching = context.core_model.ctx.profile.settings.binge_watching;
+            if binge_watching && not(model.active_seek_slider) && model.skip_markers.in_credits(time) {
+                orders.send_msg(Msg::PlayNextVideo);
+            }
+        }
+        Msg::SkipMarkersLoaded(Ok(skip_markers)) => {
+            model.skip_markers = skip_markers.unwrap_or_default();
+        }
+        Msg::SkipMarkersLoaded(Err(error)) => {
+            log_error!("player", "Skip markers load failed: {}", error.message());
+        }
+        Msg::ToggleSkipMarkersMenu => {
+            model.skip_markers_menu_visible = not(model.skip_markers_menu_visible);
+        }
+        Msg::MarkSkipMarker(kind) => {
+            model.skip_markers_menu_visible = false;
+            let time = match model.time {
+                Some(time) => time,
+                None => return
+            };
+            model.skip_markers.set(kind, time);
+            save_skip_markers(model, orders);
+        }
+        Msg::ClearSkipMarkers => {
+            model.skip_markers_menu_visible = false;
+            model.skip_markers = SkipMarkers::default();
+            save_skip_markers(model, orders);
+        }
+        Msg::SkipIntro => {
+            if let Some((_, intro_end)) = model.skip_markers.intro_range() {
+                set_time(intro_end, model);
+                broadcast(model, PartyMessage::Seek { time: intro_end });
+            }
+        }
+        Msg::PlayNextVideo => {
+            if model.next_video_requested {
+                return
+            }
+            let next_video_url = model
+                .video_path
+                .as_ref()
+                .and_then(|video_path| next_video_url(video_path, context));
+            if let Some(next_video_url) = next_video_url {
+                model.next_video_requested = true;
+                orders.request_url(next_video_url);
+            }
+        }
+        Msg::PointerMoved => {
+            let controls_were_visible = model.controls_visible;
+            model.controls_visible = true;
+            schedule_controls_hiding(model, orders);
+            if controls_were_visible {
                 orders.skip();
+            }
+        }
+        Msg::HideControls => {
+            model.controls_hider = None;
+            let controls_in_use = model.active_seek_slider 
+                || model.active_volume_slider 
+                || model.skip_markers_menu_visible;
+            if not(model.playing) || controls_in_use {
+                return
+            }
+            model.controls_visible = false;
+        }
+        Msg::TouchStarted(position) => {
+            model.touch_gesture = Some(TouchGesture {
+                start_y: position.y,
+                start_volume: model.volume,
+                swiping: false,
+            });
+            orders.skip();
+        }
+        Msg::TouchMoved(position) => {
+            let gesture = match model.touch_gesture.as_mut() {
+                Some(gesture) => gesture,
+                None => return
+            };
+            let offset = gesture.start_y - position.y;
+            if not(gesture.swiping) && offset.abs() < SWIPE_THRESHOLD {
+                orders.skip();
+                return
+            }
+            gesture.swiping = true;
+            let volume = f64::from(gesture.start_volume) + offset / position.height * 100.;
+            set_volume(volume.max(0.).min(100.) as u32, model);
+        }
+        Msg::TouchEnded(position) => {
+            let swiped = model.touch_gesture.take().map_or(false, |gesture| gesture.swiping);
+            if swiped {
+                return
+            }
+            let right_side = position.x_ratio > 0.5;
+            match model.pending_tap.take() {
+                Some(pending_tap) if pending_tap.right_side == right_side => {
+                    let offset = if right_side { TOUCH_SEEK_STEP } else { -TOUCH_SEEK_STEP };
+                    orders.send_msg(Msg::SeekBy(offset));
+                }
+                _ => {
+                    model.pending_tap = Some(PendingTap {
+                        right_side,
+                        _single_tap_handle: orders.perform_cmd_with_handle(
+                            cmds::timeout(DOUBLE_TAP_DELAY, || Msg::SingleTap)
+                        ),
+                    });
+                }
+            }
+            orders.skip();
+        }
+        Msg::SingleTap => {
+            model.pending_tap = None;
+            model.controls_visible = not(model.controls_visible);
+            if model.controls_visible {
+                schedule_controls_hiding(model, orders);
+            }
+        }
+        Msg::ToggleWatchPartyPanel => {
+            model.watch_party_panel_visible = not(model.watch_party_panel_visible);
+        }
+        Msg::WatchPartyRoomInputChanged(room) => {
+            model.watch_party_room_input = room;
+        }
+        Msg::CreateWatchParty => {
+            connect_watch_party(relay::new_room_code(), true, model, orders);
+        }
+        Msg::JoinWatchParty => {
+            let room = model.watch_party_room_input.trim().to_uppercase();
+            if room.is_empty() {
+                return
+            }
+            connect_watch_party(room, false, model, orders);
+        }
+        Msg::LeaveWatchParty => {
+            model.watch_party = None;
+        }
+        Msg::WatchPartyConnected => {
+            let stream = model.stream.clone();
+            let video_path = model.video_path.clone();
+            let watch_party = match model.watch_party.as_mut() {
+                Some(watch_party) => watch_party,
+                None => return
+            };
+            watch_party.connected = true;
+            if watch_party.host {
+                watch_party.heartbeat_handle = Some(orders.stream_with_handle(
+                    streams::interval(watch_party::HEARTBEAT_INTERVAL, || Msg::WatchPartyHeartbeat)
+                ));
+                if let Some(stream) = stream {
+                    share_stream(watch_party, &stream, video_path);
+                }
             } else {
-                model.time = time;
+                watch_party.send(PartyMessage::Join);
+            }
+        }
+        Msg::WatchPartyMessageReceived(message) => {
+            let envelope = match message.json::<Envelope>() {
+                Ok(envelope) => envelope,
+                Err(error) => {
+                    log_error!("player", "Watch party message is invalid: {:?}", error);
+                    return
+                }
+            };
+            handle_party_message(envelope, model, context, orders);
+        }
+        Msg::WatchPartyDisconnected => {
+            if model.watch_party.take().is_some() {
+                log_warn!("player", "Watch party disconnected");
+            }
+        }
+        Msg::WatchPartyHeartbeat => {
+            orders.skip();
+            if matches!(&model.watch_party, Some(watch_party) if watch_party.host) {
+                broadcast(model, PartyMessage::State { 
+                    playing: model.playing, 
+                    time: model.time.unwrap_or_default(), 
+                });
+            }
+        }
+        Msg::ToggleRemotePairing => {
+            if model.remote_pairing.take().is_some() {
+                return
+            }
+            let code = relay::new_secret_room_code();
+            let web_socket = WebSocket::builder(relay::room_url(remote_control::ROOM_PREFIX, &code), orders)
+                .on_open(|| Msg::RemotePairingConnected)
+                .on_message(Msg::RemoteMessageReceived)
+                .on_close(|_| Msg::RemotePairingDisconnected)
+                .on_error(|| Msg::RemotePairingDisconnected)
+                .build_and_open();
+            match web_socket {
+                Ok(web_socket) => {
+                    model.remote_pairing = Some(RemotePairing { code, connected: false, state: PairingState::Waiting, web_socket });
+                }
+                Err(error) => log_error!("player", "Remote control connection failed: {:?}", error),
+            }
+        }
+        Msg::ConfirmRemotePairing => {
+            if let Some(remote_pairing) = model.remote_pairing.as_mut() {
+                remote_pairing.state = PairingState::Confirmed;
+            }
+            send_remote_status(model, context);
+        }
+        Msg::RemotePairingConnected => {
+            if let Some(remote_pairing) = model.remote_pairing.as_mut() {
+                remote_pairing.connected = true;
+            }
+        }
+        Msg::RemoteMessageReceived(message) => {
+            let message = match message.json::<RemoteMessage>() {
+                Ok(message) => message,
+                Err(error) => {
+                    log_error!("player", "Remote control message is invalid: {:?}", error);
+                    return
+                }
+            };
+            let accepted = model
+                .remote_pairing
+                .as_mut()
+                .map_or(false, |remote_pairing| remote_pairing.state.accept(&message));
+            if not(accepted) {
+                return
+            }
+            orders.skip();
+            let msg = match message.player_command() {
+                Some(PlayerCommand::SendStatus) => return send_remote_status(model, context),
+                Some(PlayerCommand::TogglePlay) => Msg::TogglePlay,
+                Some(PlayerCommand::SeekBy(offset)) => Msg::SeekBy(offset),
+                Some(PlayerCommand::SeekTo(time)) => Msg::SeekTo(time),
+                Some(PlayerCommand::SetVolume(volume)) => Msg::VolumeSliderMoved(volume),
+                Some(PlayerCommand::NextVideo) => Msg::PlayNextVideo,
+                None => return,
+            };
+            orders.send_msg(msg);
+        }
+        Msg::RemotePairingDisconnected => {
+            if model.remote_pairing.take().is_some() {
+                log_warn!("player", "Remote control disconnected");
             }
         }
     }
 }
 
+fn send_remote_status(model: &Model, context: &Context) {
+    let remote_pairing = match &model.remote_pairing {
+        Some(remote_pairing) => remote_pairing,
+        None => return
+    };
+    let title = context
+        .core_model
+        .player
+        .selected
+        .as_ref()
+        .and_then(|selected| selected.stream.title.clone())
+        .unwrap_or_default();
+    let has_next_video = model
+        .video_path
+        .as_ref()
+        .and_then(|video_path| next_video_url(video_path, context))
+        .is_some();
+    remote_pairing.send(&RemoteMessage::Status(PlayerStatus {
+        title,
+        playing: model.playing,
+        time: model.time,
+        duration: model.duration,
+        volume: model.volume,
+        has_next_video,
+    }));
+}
+
 fn set_volume(volume: u32, model: &mut Model) {
     let player = match model.youtube.as_ref() {
         Some(Youtube { player: Some(player), .. }) => player,
@@ -308,6 +789,159 @@ fn set_volume(volume: u32, model: &mut Model) {
     model.volume = volume;
 }
 
+fn youtube_player(model: &Model) -> Option<&Player> {
+    match model.youtube.as_ref() {
+        Some(Youtube { player: Some(player), .. }) => Some(player),
+        _ => None
+    }
+}
+
+// ------ watch party ------
+
+fn connect_watch_party(room: String, host: bool, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    let web_socket = WebSocket::builder(watch_party::relay_url(&room), orders)
+        .on_open(|| Msg::WatchPartyConnected)
+        .on_message(Msg::WatchPartyMessageReceived)
+        .on_close(|_| Msg::WatchPartyDisconnected)
+        .on_error(|| Msg::WatchPartyDisconnected)
+        .build_and_open();
+    match web_socket {
+        Ok(web_socket) => {
+            model.watch_party = Some(WatchParty {
+                room,
+                participant_id: watch_party::new_participant_id(),
+                host,
+                connected: false,
+                shared_stream: None,
+                web_socket,
+                heartbeat_handle: None,
+            });
+        }
+        Err(error) => log_error!("player", "Watch party connection failed: {:?}", error),
+    }
+}
+
+fn broadcast(model: &Model, message: PartyMessage) {
+    if let Some(watch_party) = model.watch_party.as_ref().filter(|watch_party| watch_party.connected) {
+        watch_party.send(message);
+    }
+}
+
+fn share_stream(watch_party: &mut WatchParty, stream: &Stream, video_path: Option<VideoPath>) {
+    let serialized_stream = serde_json::to_string(stream).ok();
+    if not(watch_party.connected) || watch_party.shared_stream == serialized_stream {
+        return
+    }
+    watch_party.shared_stream = serialized_stream;
+    watch_party.send(PartyMessage::Stream { stream: stream.clone(), video_path });
+}
+
+fn handle_party_message(envelope: Envelope, model: &mut Model, context: &Context, orders: &mut impl Orders<Msg>) {
+    let watch_party = match model.watch_party.as_mut() {
+        Some(watch_party) if watch_party.participant_id != envelope.sender => watch_party,
+        _ => return
+    };
+    if let PartyMessage::Stream { stream, video_path } = envelope.message {
+        let serialized_stream = serde_json::to_string(&stream).ok();
+        if watch_party.shared_stream == serialized_stream {
+            return
+        }
+        watch_party.shared_stream = serialized_stream;
+        let player_urls = RootUrls::new(&context.root_base_url).player();
+        orders.request_url(match video_path {
+            Some(video_path) => player_urls.video_stream(
+                &stream, 
+                &video_path.type_name, 
+                &video_path.meta_id, 
+                &video_path.video_id,
+            ),
+            None => player_urls.stream(&stream),
+        });
+        return
+    }
+    let host = watch_party.host;
+    for command in watch_party::playback_commands(&envelope.message, host, model.playing, model.time) {
+        match command {
+            PlaybackCommand::Play => {
+                if let Some(player) = youtube_player(model) {
+                    player.play_video();
+                }
+            }
+            PlaybackCommand::Pause => {
+                if let Some(player) = youtube_player(model) {
+                    player.pause_video();
+                }
+            }
+            PlaybackCommand::Seek(time) => set_time(time, model),
+            PlaybackCommand::ShareStream => {
+                if let Some(watch_party) = model.watch_party.as_mut() {
+                    // force re-sending the current stream to the new participant
+                    watch_party.shared_stream = None;
+                    if let Some(stream) = model.stream.clone() {
+                        share_stream(watch_party, &stream, model.video_path.clone());
+                    }
+                }
+                broadcast(model, PartyMessage::State { 
+                    playing: model.playing, 
+                    time: model.time.unwrap_or_default(), 
+                });
+            }
+        }
+    }
+}
+
+fn schedule_controls_hiding(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    model.controls_hider = model.playing.then(|| {
+        orders.perform_cmd_with_handle(cmds::timeout(CONTROLS_HIDE_DELAY, || Msg::HideControls))
+    });
+}
+
+fn save_skip_markers(model: &Model, orders: &mut impl Orders<Msg>) {
+    let video_path = match &model.video_path {
+        Some(video_path) => video_path,
+        None => return
+    };
+    let storage_key = skip_markers::storage_key(&video_path.meta_id);
+    let skip_markers = model.skip_markers;
+    orders.perform_cmd(async move {
+        let skip_markers = not(skip_markers.is_empty()).then(|| skip_markers);
+        if let Err(error) = WebEnv::set_storage(&storage_key, skip_markers.as_ref()).await {
+            log_error!("player", "Skip markers save failed: {}", error.message());
+        }
+    });
+}
+
+fn next_video_url(video_path: &VideoPath, context: &Context) -> Option<Url> {
+    let meta_item = context
+        .core_model
+        .meta_details
+        .meta_items
+        .iter()
+        .find_map(|meta_items| match &meta_items.content {
+            Loadable::Ready(meta_item) if meta_item.id == video_path.meta_id => Some(meta_item),
+            _ => None,
+        })?;
+    let mut videos = meta_item.videos.iter().skip_while(|video| video.id != video_path.video_id);
+    videos.next()?;
+    let next_video = videos.next()?;
+
+    let root_urls = RootUrls::new(&context.root_base_url);
+    Some(if next_video.streams.len() == 1 {
+        root_urls
+            .player()
+            .video_stream(&next_video.streams[0], &video_path.type_name, &video_path.meta_id, &next_video.id)
+    } else {
+        root_urls
+            .detail_urls()
+            .with_video_id(&video_path.type_name, &video_path.meta_id, &next_video.id)
+    })
+}
+
+/// `time` moved by `offset`, kept within the video.
+fn seek_target(time: u32, duration: u32, offset: i32) -> u32 {
+    (i64::from(time) + i64::from(offset)).clamp(0, i64::from(duration)) as u32
+}
+
 fn set_time(time: u32, model: &mut Model) {
     let player = match model.youtube.as_ref() {
         Some(Youtube { player: Some(player), .. }) => player,
@@ -376,7 +1010,7 @@ fn init_youtube(video_ref: &ElRef<HtmlElement>, yt_id: String, orders: &mut impl
 
     // -- on_api_error --
     let on_api_error = || {
-        error!("Youtube error");
+        log_error!("player", "Youtube error");
     };
     let on_api_error = Closure::wrap(Box::new(on_api_error) as Box<dyn Fn()>);
     api_script.set_onerror(None);
@@ -464,6 +1098,19 @@ pub struct PlayerVars {
 #[view]
 pub fn view(model: &Model, context: &Context) -> Node<Msg> {
     if let Some(player) = &context.core_model.player.selected {
+        let time = model.time.unwrap_or_default();
+        let skip_action: Option<(&str, fn() -> Msg)> = if model.skip_markers.in_intro(time) {
+            Some(("Skip intro", || Msg::SkipIntro))
+        } else if model.skip_markers.in_credits(time) && model
+            .video_path
+            .as_ref()
+            .and_then(|video_path| next_video_url(video_path, context))
+            .is_some() 
+        {
+            Some(("Next episode", || Msg::PlayNextVideo))
+        } else {
+            None
+        };
         route_content(
             &model.video_ref, 
             // @TODO make sure `selected` contains `title`
@@ -476,6 +1123,18 @@ pub fn view(model: &Model, context: &Context) -> Node<Msg> {
             model.active_seek_slider, 
             model.time, 
             model.duration,
+            model.seek_hover_time,
+            &model.skip_markers.to_markers(),
+            skip_action,
+            model.skip_markers_menu_visible,
+            model.video_path.is_some(),
+            model.controls_visible,
+            model.watch_party_panel_visible.then(|| {
+                (model.watch_party.as_ref(), model.watch_party_room_input.as_str())
+            }),
+            model.remote_pairing.as_ref().map(|remote_pairing| {
+                (remote_pairing, remote_url(&remote_pairing.code, context))
+            }),
         )
     } else {
         div!["Loading..."]
@@ -494,6 +1153,14 @@ fn route_content(
     active_seek_slider: bool, 
     time: Option<u32>, 
     duration: Option<u32>,
+    seek_hover_time: Option<u32>,
+    markers: &[Marker],
+    skip_action: Option<(&str, fn() -> Msg)>,
+    markers_menu_visible: bool,
+    markable: bool,
+    controls_visible: bool,
+    watch_party_panel: Option<(Option<&WatchParty>, &str)>,
+    remote_pairing: Option<(&RemotePairing, String)>,
 ) -> Node<Msg> {
     div![
         C!["route-content"],
@@ -516,6 +1183,14 @@ fn route_content(
             active_seek_slider,
             time,
             duration,
+            seek_hover_time,
+            markers,
+            skip_action,
+            markers_menu_visible,
+            markable,
+            controls_visible,
+            watch_party_panel,
+            remote_pairing,
         ),
     ]
 }
@@ -532,6 +1207,14 @@ fn player_container(
     active_seek_slider: bool, 
     time: Option<u32>, 
     duration: Option<u32>,
+    seek_hover_time: Option<u32>,
+    markers: &[Marker],
+    skip_action: Option<(&str, fn() -> Msg)>,
+    markers_menu_visible: bool,
+    markable: bool,
+    controls_visible: bool,
+    watch_party_panel: Option<(Option<&WatchParty>, &str)>,
+    remote_pairing: Option<(&RemotePairing, String)>,
 ) -> Node<Msg> {
     div![
         C!["player-container"],
@@ -541,10 +1224,22 @@ fn player_container(
             .position(CssPosition::Relative)
             .width(pc(100))
             .z_index("0"),
+        IF!(not(controls_visible) => s().raw("cursor: none;")),
+        ev(Ev::MouseMove, |_| Msg::PointerMoved),
         video_container(video_ref),
         overlay(),
-        nav_bar(title, fullscreen),
+        skip_action.map(|(title, on_click)| skip_button(title, on_click)),
+        nav_bar(
+            title, 
+            fullscreen, 
+            controls_visible, 
+            watch_party_panel.is_some(), 
+            remote_pairing.is_some(),
+        ),
+        watch_party_panel.map(|(watch_party, room_input)| watch_party_panel(watch_party, room_input)),
+        remote_pairing.map(|(remote_pairing, remote_url)| pairing_panel(remote_pairing, &remote_url)),
         control_bar(
+            controls_visible,
             playing, 
             muted, 
             volume, 
@@ -552,6 +1247,10 @@ fn player_container(
             active_seek_slider,
             time,
             duration,
+            seek_hover_time,
+            markers,
+            markers_menu_visible,
+            markable,
         ),
     ]
 }
@@ -595,6 +1294,116 @@ fn overlay() -> Node<Msg> {
             .top("0")
             .z_index("0"),
         ev(Ev::Click, |_| Msg::TogglePlay),
+        touch_ev(Ev::TouchStart, |event| {
+            touch_position(&event).map(Msg::TouchStarted)
+        }),
+        touch_ev(Ev::TouchMove, |event| {
+            let position = touch_position(&event)?;
+            event.prevent_default();
+            Some(Msg::TouchMoved(position))
+        }),
+        touch_ev(Ev::TouchEnd, |event| {
+            let position = touch_position(&event)?;
+            // prevent emulated mouse events, e.g. the `click` toggling playback
+            event.prevent_default();
+            Some(Msg::TouchEnded(position))
+        }),
+    ]
+}
+
+/// Returns `None` for layouts wider than `Breakpoint::Zero`, where touch gestures are disabled.
+fn touch_position(event: &web_sys::TouchEvent) -> Option<TouchPosition> {
+    let window_width = window().inner_width().ok()?.as_f64()?;
+    if window_width >= f64::from(themes::ZERO_BREAKPOINT_MAX_WIDTH) {
+        return None
+    }
+    let touch = event.changed_touches().get(0)?;
+    let target = event.current_target()?.dyn_into::<web_sys::Element>().ok()?;
+    let rect = target.get_bounding_client_rect();
+    Some(TouchPosition {
+        x_ratio: (f64::from(touch.client_x()) - rect.left()) / rect.width(),
+        y: f64::from(touch.client_y()),
+        height: rect.height(),
+    })
+}
+
+fn remote_url(code: &str, context: &Context) -> String {
+    let origin = window().location().origin().unwrap_or_default();
+    let url = RootUrls::new(&context.root_base_url).remote_urls().code(code);
+    format!("{}{}", origin, url)
+}
+
+#[view]
+fn skip_button(title: &str, on_click: fn() -> Msg) -> Node<Msg> {
+    div![
+        C!["skip-button", "button-container"],
+        s()
+            .background_color(hsla(0, 0, 0, 0.7))
+            .border("thin solid hsl(0deg 0% 100%)")
+            .bottom(rem(9))
+            .color(hsl(0, 0, 100))
+            .font_size(rem(1.2))
+            .padding("0.8rem 1.5rem")
+            .position(CssPosition::Absolute)
+            .right(rem(2))
+            .z_index("1")
+            .cursor(CssCursor::Pointer),
+        s()
+            .hover()
+            .background_color(hsla(0, 0, 100, 0.2)),
+        attrs!{
+            At::TabIndex => 0,
+            At::Title => title,
+        },
+        ev(Ev::Click, move |_| on_click()),
+        title,
     ]
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Msg as RootMsg;
+    use crate::test_env::{self, TestApp};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    fn stream(url: &str) -> Stream {
+        serde_json::from_value::<Stream>(serde_json::json!({ "url": url })).expect("valid stream")
+    }
+
+    #[test]
+    fn seeking_stays_within_the_video() {
+        assert_eq!(seek_target(10, 100, 5), 15);
+        assert_eq!(seek_target(10, 100, -30), 0);
+        assert_eq!(seek_target(90, 100, 30), 100);
+    }
+
+    #[test]
+    fn stream_is_loaded() {
+        let mut core_model = test_env::new_core_model();
+        let stream = stream("https://example.com/video.mp4");
+        test_env::update(&mut core_model, &load_player_msg(stream.clone()));
+
+        assert_eq!(core_model.player.selected.as_ref().map(|selected| &selected.stream), Some(&stream));
+    }
+
+    #[wasm_bindgen_test]
+    async fn reopened_player_starts_from_a_clean_state() {
+        let app = TestApp::start().await;
+        app.open(Route::Player { stream: stream("https://example.com/first.mp4"), video_path: None }).await;
+        app.send(RootMsg::PlayerMsg(Msg::ToggleSkipMarkersMenu)).await;
+        assert!(app.read(|model| model.model.player_model.as_ref().expect("player model").skip_markers_menu_visible));
+
+        let second_stream = stream("https://example.com/second.mp4");
+        app.open(Route::Player { stream: second_stream.clone(), video_path: None }).await;
+
+        let (menu_visible, selected_stream) = app.read(|model| (
+            model.model.player_model.as_ref().expect("player model").skip_markers_menu_visible,
+            model.model.context.core_model.player.selected.as_ref().map(|selected| selected.stream.clone()),
+        ));
+        assert!(!menu_visible);
+        assert_eq!(selected_stream, Some(second_stream));
+    }
+}
@@ -12,7 +12,7 @@ use crate::styles::{self, themes::{Color, Breakpoint}, global};
 use stremio_core::types::resource::{Stream, StreamSource};
 use stremio_core::models::player::Selected as PlayerSelected;
 use stremio_core::runtime::msg::{Action, ActionLoad, Msg as CoreMsg, Internal};
-use super::Msg;
+use super::{Msg, Marker};
 
 mod play_button;
 use play_button::play_button;
@@ -38,11 +38,15 @@ use subtitles_button::subtitles_button;
 mod videos_button;
 use videos_button::videos_button;
 
-mod seek_bar;
+pub(crate) mod seek_bar;
 use seek_bar::seek_bar;
 
+mod markers_button;
+use markers_button::markers_button;
+
 #[view]
 pub fn control_bar(
+    visible: bool,
     playing: bool, 
     muted: bool, 
     volume: u32, 
@@ -50,6 +54,10 @@ pub fn control_bar(
     active_seek_slider: bool, 
     time: Option<u32>, 
     duration: Option<u32>,
+    seek_hover_time: Option<u32>,
+    markers: &[Marker],
+    markers_menu_visible: bool,
+    markable: bool,
 ) -> Node<Msg> {
     div![
         C!["layer", "control-bar-layer", "control-bar-container"],
@@ -62,6 +70,9 @@ pub fn control_bar(
             .right("0")
             .z_index("0")
             .padding("0 1.5rem"),
+        s()
+            .transition("opacity 300ms ease-out"),
+        IF!(not(visible) => s().opacity("0").pointer_events("none")),
         s()
             .before()
             .bottom("0")
@@ -71,13 +82,20 @@ pub fn control_bar(
             .position(CssPosition::Absolute)
             .right("0")
             .z_index("-1"),
-        seek_bar(active_seek_slider, time, duration),
-        control_bar_buttons(playing, muted, volume, active_volume_slider),
+        seek_bar(active_seek_slider, time, duration, seek_hover_time, markers),
+        control_bar_buttons(playing, muted, volume, active_volume_slider, markers_menu_visible, markable),
     ]
 }
 
 #[view]
-fn control_bar_buttons(playing: bool, muted: bool, volume: u32, active_volume_slider: bool) -> Node<Msg> {
+fn control_bar_buttons(
+    playing: bool, 
+    muted: bool, 
+    volume: u32, 
+    active_volume_slider: bool, 
+    markers_menu_visible: bool, 
+    markable: bool,
+) -> Node<Msg> {
     div![
         s()
             .align_items(CssAlignItems::Center)
@@ -87,6 +105,7 @@ fn control_bar_buttons(playing: bool, muted: bool, volume: u32, active_volume_sl
         mute_button(muted, volume),
         volume_slider(volume, active_volume_slider),
         spacer(),
+        markers_button(markers_menu_visible, markable),
         network_button(),
         info_button(),
         screencast_button(),
@@ -0,0 +1,97 @@
+use seed::{prelude::*, *};
+use seed_hooks::{*, topo::nested as view};
+use seed_styles::{em, pc, rem, Style};
+use seed_styles::*;
+use crate::styles::{self, themes::{Color, Breakpoint}, global};
+use crate::page::player::skip_markers::SkipMarkerKind;
+use super::Msg;
+
+#[view]
+pub fn markers_button(menu_visible: bool, enabled: bool) -> Node<Msg> {
+    let disabled = not(enabled);
+    div![
+        s()
+            .align_items(CssAlignItems::Center)
+            .display(CssDisplay::Flex)
+            .flex(CssFlex::None)
+            .height(rem(4))
+            .justify_content(CssJustifyContent::Center)
+            .width(rem(4))
+            .cursor(CssCursor::Pointer)
+            .overflow(CssOverflow::Visible)
+            .position(CssPosition::Relative),
+        IF!(disabled => s().pointer_events("none")),
+        C!["control-bar-button", "button-container", IF!(disabled => "disabled")],
+        attrs!{
+            At::TabIndex => -1,
+            At::Title => "Intro & credits markers",
+        },
+        ev(Ev::Click, |_| Msg::ToggleSkipMarkersMenu),
+        icon(disabled),
+        IF!(menu_visible => menu()),
+    ]
+}
+
+#[view]
+fn menu() -> Node<Msg> {
+    div![
+        C!["markers-menu"],
+        s()
+            .background_color(Color::BackgroundDark2)
+            .bottom(pc(100))
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Column)
+            .position(CssPosition::Absolute)
+            .right("0")
+            .width(rem(14))
+            .z_index("1"),
+        ev(Ev::Click, |event| event.stop_propagation()),
+        menu_item("Intro starts here", || Msg::MarkSkipMarker(SkipMarkerKind::IntroStart)),
+        menu_item("Intro ends here", || Msg::MarkSkipMarker(SkipMarkerKind::IntroEnd)),
+        menu_item("Credits start here", || Msg::MarkSkipMarker(SkipMarkerKind::CreditsStart)),
+        menu_item("Clear markers", || Msg::ClearSkipMarkers),
+    ]
+}
+
+#[view]
+fn menu_item(title: &str, on_click: fn() -> Msg) -> Node<Msg> {
+    div![
+        C!["markers-menu-item", "button-container"],
+        s()
+            .color(hsl(0, 0, 100))
+            .padding("0.8rem 1rem")
+            .white_space(CssWhiteSpace::NoWrap)
+            .cursor(CssCursor::Pointer),
+        s()
+            .hover()
+            .background_color(Color::BackgroundLight1),
+        attrs!{
+            At::TabIndex => 0,
+            At::Title => title,
+        },
+        ev(Ev::Click, move |_| on_click()),
+        title,
+    ]
+}
+
+#[view]
+pub fn icon(disabled: bool) -> Node<Msg> {
+    svg![
+        C!["icon"],
+        s()
+            .fill(hsl(0, 0, if disabled { 75 } else { 100 }))
+            .flex(CssFlex::None)
+            .height(rem(2))
+            .width(rem(3))
+            .overflow(CssOverflow::Visible),
+        attrs!{
+            At::ViewBox => "0 0 1024 1024",
+            At::from("icon") => "ic_bookmark",
+        },
+        path![
+            attrs!{
+                At::D => "M170.667 0h682.667v1024l-341.333-256-341.333 256z",
+            }
+        ]
+    ]
+}
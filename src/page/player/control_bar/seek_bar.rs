@@ -14,13 +14,19 @@ use stremio_core::types::resource::{Stream, StreamSource};
 use stremio_core::models::player::Selected as PlayerSelected;
 use stremio_core::runtime::msg::{Action, ActionLoad, Msg as CoreMsg, Internal};
 use super::Msg;
+use crate::page::player::Marker;
+
+const KEYBOARD_SEEK_STEP: i32 = 5;
 
 #[view]
-pub fn seek_bar(active: bool, time: Option<u32>, duration: Option<u32>) -> Node<Msg> {
-    let position_percent = match (time, duration) {
-        (Some(time), Some(duration)) => time as f32 / duration as f32 * 100.,
-        _ => 0.,
-    };
+pub fn seek_bar(
+    active: bool, 
+    time: Option<u32>, 
+    duration: Option<u32>, 
+    hover_time: Option<u32>, 
+    markers: &[Marker],
+) -> Node<Msg> {
+    let position_percent = time_to_percent(time, duration);
     div![
         C!["seek-bar", "seek-bar-container"],
         s()
@@ -35,12 +41,19 @@ pub fn seek_bar(active: bool, time: Option<u32>, duration: Option<u32>) -> Node<
             .style_other(":hover .track-before")
             .background_color(Color::PrimaryLight5),
         label(format_time(time)),
-        slider(active, position_percent, duration),
+        slider(active, position_percent, time, duration, hover_time, markers),
         label(format_time(duration)),
     ]
 }
 
-fn format_time(seconds: Option<u32>) -> Cow<'static, str> {
+fn time_to_percent(time: Option<u32>, duration: Option<u32>) -> f32 {
+    match (time, duration) {
+        (Some(time), Some(duration)) if duration > 0 => time as f32 / duration as f32 * 100.,
+        _ => 0.,
+    }
+}
+
+pub(crate) fn format_time(seconds: Option<u32>) -> Cow<'static, str> {
     let seconds = match seconds {
         None => return "--:--:--".into(),
         Some(seconds) => seconds,
@@ -68,7 +81,14 @@ fn label(text: Cow<'static, str>) -> Node<Msg> {
 }
 
 #[view]
-fn slider(active: bool, position_percent: f32, duration: Option<u32>) -> Node<Msg> {
+fn slider(
+    active: bool, 
+    position_percent: f32, 
+    time: Option<u32>, 
+    duration: Option<u32>, 
+    hover_time: Option<u32>, 
+    markers: &[Marker],
+) -> Node<Msg> {
     div![
         C!["slider", "slider-container"],
         s()
@@ -79,19 +99,51 @@ fn slider(active: bool, position_percent: f32, duration: Option<u32>) -> Node<Ms
             .overflow(CssOverflow::Visible)
             .position(CssPosition::Relative)
             .z_index("0"),
+        s()
+            .focus()
+            .outline_style(CssOutlineStyle::Solid)
+            .outline_width(global::FOCUS_OUTLINE_SIZE)
+            .outline_color(Color::SurfaceLighter),
+        attrs!{
+            At::TabIndex => 0,
+            At::from("role") => "slider",
+            At::from("aria-label") => "Seek",
+            At::from("aria-valuemin") => 0,
+            At::from("aria-valuemax") => duration.unwrap_or_default(),
+            At::from("aria-valuenow") => time.unwrap_or_default(),
+            At::from("aria-valuetext") => format_time(time),
+        },
         layer(track()),
         layer(track_before(position_percent)),
+        layer(markers_view(markers, duration)),
         layer(thumb(position_percent)),
+        hover_time.map(|hover_time| tooltip(hover_time, duration, markers)),
         mouse_ev(Ev::MouseDown, move |event| {
             Msg::ActivateSeekSlider(get_time(event, duration))
         }),
-        active.then(|| {
-            mouse_ev(Ev::MouseMove, move |event| {
-                Msg::SeekSliderMoved(get_time(event, duration))
-            })
+        mouse_ev(Ev::MouseMove, move |event| {
+            let time = get_time(event, duration);
+            if active {
+                Msg::SeekSliderMoved(time)
+            } else {
+                Msg::SeekSliderHovered(time)
+            }
         }),
         ev(Ev::MouseUp, |_| Msg::DeactivateSeekSlider),
-        ev(Ev::MouseLeave, |_| Msg::DeactivateSeekSlider),
+        ev(Ev::MouseLeave, |_| Msg::SeekSliderLeft),
+        keyboard_ev(Ev::KeyDown, move |event| {
+            let msg = match event.key().as_str() {
+                "ArrowLeft" | "ArrowDown" => Msg::SeekBy(-KEYBOARD_SEEK_STEP),
+                "ArrowRight" | "ArrowUp" => Msg::SeekBy(KEYBOARD_SEEK_STEP),
+                "PageDown" => Msg::SeekBy(-KEYBOARD_SEEK_STEP * 6),
+                "PageUp" => Msg::SeekBy(KEYBOARD_SEEK_STEP * 6),
+                "Home" => Msg::SeekTo(0),
+                "End" => Msg::SeekTo(duration?),
+                _ => return None,
+            };
+            event.prevent_default();
+            Some(msg)
+        }),
     ]
 }
 
@@ -103,6 +155,71 @@ fn get_time(event: web_sys::MouseEvent, duration: Option<u32>) -> u32 {
     time as u32
 }
 
+#[view]
+fn tooltip(hover_time: u32, duration: Option<u32>, markers: &[Marker]) -> Node<Msg> {
+    let position_percent = time_to_percent(Some(hover_time), duration);
+    // markers are sorted by time, so the last passed marker names the hovered part
+    let marker_label = markers
+        .iter()
+        .take_while(|marker| marker.time <= hover_time)
+        .last()
+        .map(|marker| marker.label.as_str());
+    div![
+        C!["tooltip"],
+        s()
+            .background_color(Color::BackgroundDark2)
+            .bottom(pc(100))
+            .color(hsl(0, 0, 100))
+            .left(format!("calc({}%)", position_percent).as_str())
+            .margin_bottom(rem(0.5))
+            .padding("0.3rem 0.6rem")
+            .pointer_events("none")
+            .position(CssPosition::Absolute)
+            .text_align(CssTextAlign::Center)
+            .transform("translateX(-50%)")
+            .white_space(CssWhiteSpace::NoWrap)
+            .z_index("1"),
+        marker_label.map(|marker_label| {
+            div![
+                C!["marker-label"],
+                s()
+                    .font_size(rem(0.9))
+                    .margin_bottom(rem(0.2))
+                    .opacity("0.8"),
+                marker_label,
+            ]
+        }),
+        div![
+            C!["time"],
+            format_time(Some(hover_time)),
+        ],
+    ]
+}
+
+#[view]
+fn markers_view(markers: &[Marker], duration: Option<u32>) -> Node<Msg> {
+    div![
+        C!["markers"],
+        s()
+            .flex("1")
+            .height(global::TRACK_SIZE)
+            .position(CssPosition::Relative),
+        markers.iter().map(|marker| {
+            div![
+                C!["marker"],
+                s()
+                    .background_color(hsl(0, 0, 100))
+                    .height(pc(100))
+                    .left(format!("calc({}%)", time_to_percent(Some(marker.time), duration)).as_str())
+                    .position(CssPosition::Absolute)
+                    .top("0")
+                    .transform("translateX(-50%)")
+                    .width(rem(0.2)),
+            ]
+        }),
+    ]
+}
+
 #[view]
 fn layer(content: Node<Msg>) -> Node<Msg> {
     div![
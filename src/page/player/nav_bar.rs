@@ -15,7 +15,13 @@ use stremio_core::runtime::msg::{Action, ActionLoad, Msg as CoreMsg, Internal};
 use super::Msg;
 
 #[view]
-pub fn nav_bar(title: &str, fullscreen: bool) -> Node<Msg> {
+pub fn nav_bar(
+    title: &str, 
+    fullscreen: bool, 
+    visible: bool, 
+    watch_party_panel_visible: bool, 
+    remote_paired: bool,
+) -> Node<Msg> {
     nav![
         C!["layer", "nav-bar-layer", "horizontal-nav-bar-container"],
         s()
@@ -32,6 +38,9 @@ pub fn nav_bar(title: &str, fullscreen: bool) -> Node<Msg> {
             .flex_direction(CssFlexDirection::Row)
             .height(global::HORIZONTAL_NAV_BAR_SIZE)
             .padding_right(rem(1)),
+        s()
+            .transition("opacity 300ms ease-out"),
+        IF!(not(visible) => s().opacity("0").pointer_events("none")),
         s()
             .before()
             .box_shadow("0 0 8rem 6rem hsl(0deg 0% 0%)")
@@ -44,6 +53,8 @@ pub fn nav_bar(title: &str, fullscreen: bool) -> Node<Msg> {
         back_button(),
         nav_title(title),
         spacing(),
+        remote_button(remote_paired),
+        watch_party_button(watch_party_panel_visible),
         fullscreen_button(fullscreen),
     ]
 }
@@ -112,6 +123,110 @@ pub fn spacing() -> Node<Msg> {
     ]
 }
 
+#[view]
+pub fn remote_button(paired: bool) -> Node<Msg> {
+    div![
+        C!["button-container", IF!(paired => "active")],
+        s()
+            .align_items(CssAlignItems::Center)
+            .display(CssDisplay::Flex)
+            .flex(CssFlex::None)
+            .height(global::HORIZONTAL_NAV_BAR_SIZE)
+            .justify_content(CssJustifyContent::Center)
+            .width(global::HORIZONTAL_NAV_BAR_SIZE)
+            .cursor(CssCursor::Pointer),
+        attrs!{
+            At::TabIndex => -1,
+            At::Title => if paired { "Unpair remote control" } else { "Pair remote control" },
+        },
+        ev(Ev::Click, |_| Msg::ToggleRemotePairing),
+        remote_icon(paired),
+    ]
+}
+
+#[view]
+fn remote_icon(active: bool) -> Node<Msg> {
+    svg![
+        C!["icon"],
+        s()
+            .fill(if active { Color::PrimaryLight3 } else { Color::SurfaceLight5 })
+            .flex(CssFlex::None)
+            .height(rem(1.7))
+            .width(rem(1.7)),
+        attrs!{
+            At::ViewBox => "0 0 1024 1024",
+            At::from("icon") => "ic_remote",
+        },
+        path![
+            attrs!{
+                At::D => "M320 0h384c35.346 0 64 28.654 64 64v896c0 35.346-28.654 64-64 64h-384c-35.346 0-64-28.654-64-64v-896c0-35.346 28.654-64 64-64zM512 128c-53.019 0-96 42.981-96 96s42.981 96 96 96c53.019 0 96-42.981 96-96s-42.981-96-96-96zM416 480v96h192v-96zM416 672v96h192v-96z",
+                At::from("fill-rule") => "evenodd",
+            }
+        ],
+    ]
+}
+
+#[view]
+pub fn watch_party_button(panel_visible: bool) -> Node<Msg> {
+    div![
+        C!["button-container", IF!(panel_visible => "active")],
+        s()
+            .align_items(CssAlignItems::Center)
+            .display(CssDisplay::Flex)
+            .flex(CssFlex::None)
+            .height(global::HORIZONTAL_NAV_BAR_SIZE)
+            .justify_content(CssJustifyContent::Center)
+            .width(global::HORIZONTAL_NAV_BAR_SIZE)
+            .cursor(CssCursor::Pointer),
+        attrs!{
+            At::TabIndex => -1,
+            At::Title => "Watch party",
+        },
+        ev(Ev::Click, |_| Msg::ToggleWatchPartyPanel),
+        watch_party_icon(panel_visible),
+    ]
+}
+
+#[view]
+fn watch_party_icon(active: bool) -> Node<Msg> {
+    svg![
+        C!["icon"],
+        s()
+            .fill(if active { Color::PrimaryLight3 } else { Color::SurfaceLight5 })
+            .flex(CssFlex::None)
+            .height(rem(1.7))
+            .width(rem(1.7)),
+        attrs!{
+            At::ViewBox => "0 0 1024 1024",
+            At::from("icon") => "ic_users",
+        },
+        circle![
+            attrs!{
+                At::Cx => 352,
+                At::Cy => 288,
+                At::R => 160,
+            }
+        ],
+        circle![
+            attrs!{
+                At::Cx => 736,
+                At::Cy => 352,
+                At::R => 128,
+            }
+        ],
+        path![
+            attrs!{
+                At::D => "M0 1024c0-194.404 157.596-352 352-352s352 157.596 352 352z",
+            }
+        ],
+        path![
+            attrs!{
+                At::D => "M760 1024c0-120-40-230-108-314 26-8 54-12 84-12 159.058 0 288 128.942 288 288v38z",
+            }
+        ],
+    ]
+}
+
 #[view]
 pub fn fullscreen_button(fullscreen: bool) -> Node<Msg> {
     div![
@@ -0,0 +1,153 @@
+use seed::{prelude::*, *};
+use seed::browser::web_socket::WebSocket;
+use seed_hooks::{*, topo::nested as view};
+use seed_styles::{em, pc, rem, Style};
+use seed_styles::*;
+use crate::remote_control::RemoteMessage;
+use crate::styles::{self, themes::Color, global};
+use super::Msg;
+
+/// Connection to the relay room where paired remote controls send commands.
+pub struct RemotePairing {
+    pub code: String,
+    pub connected: bool,
+    pub state: PairingState,
+    pub web_socket: WebSocket,
+}
+
+impl RemotePairing {
+    /// Nothing is sent before the pairing is confirmed, the room may be joined by anyone with the code.
+    pub fn send(&self, message: &RemoteMessage) {
+        if not(self.connected) || self.state != PairingState::Confirmed {
+            return
+        }
+        if let Err(error) = self.web_socket.send_json(message) {
+            log_error!("player", "Remote control message send failed: {:?}", error);
+        }
+    }
+}
+
+/// Remote commands are handled only after the user confirms the pairing on the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairingState {
+    Waiting,
+    /// A remote sent `RemoteMessage::Hello`.
+    ConfirmationRequested,
+    Confirmed,
+}
+
+impl PairingState {
+    /// Whether the player handles the message.
+    pub fn accept(&mut self, message: &RemoteMessage) -> bool {
+        if *self == Self::Confirmed {
+            return true
+        }
+        if *message == RemoteMessage::Hello {
+            *self = Self::ConfirmationRequested;
+        }
+        false
+    }
+}
+
+// ------ View ------
+
+#[view]
+pub fn pairing_panel(remote_pairing: &RemotePairing, remote_url: &str) -> Node<Msg> {
+    div![
+        C!["remote-pairing-panel"],
+        s()
+            .background_color(Color::BackgroundDark2)
+            .color(hsl(0, 0, 100))
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Column)
+            .padding(rem(1))
+            .position(CssPosition::Absolute)
+            .right(rem(1))
+            .top(global::HORIZONTAL_NAV_BAR_SIZE)
+            .width(rem(18))
+            .z_index("1"),
+        div![
+            C!["description"],
+            s()
+                .margin_bottom(rem(0.5)),
+            "Open the link on your phone or enter the code there:",
+        ],
+        div![
+            C!["remote-url"],
+            s()
+                .color(Color::SurfaceDark5_90)
+                .margin_bottom(rem(1))
+                .user_select("text"),
+            remote_url,
+        ],
+        div![
+            C!["pairing-code"],
+            s()
+                .font_size(rem(2.5))
+                .font_weight("500")
+                .letter_spacing(rem(0.3))
+                .margin_bottom(rem(1))
+                .text_align(CssTextAlign::Center)
+                .user_select("text"),
+            &remote_pairing.code,
+        ],
+        div![
+            C!["status"],
+            s()
+                .color(Color::SurfaceDark5_90)
+                .margin_bottom(rem(1)),
+            match (remote_pairing.connected, remote_pairing.state) {
+                (false, _) => "Connecting...",
+                (true, PairingState::Waiting) => "Waiting for the remote",
+                (true, PairingState::ConfirmationRequested) => "A remote control wants to pair. Allow it only if you've just opened the link.",
+                (true, PairingState::Confirmed) => "Waiting for commands",
+            },
+        ],
+        IF!(remote_pairing.state == PairingState::ConfirmationRequested => {
+            pairing_button("Allow", || Msg::ConfirmRemotePairing)
+        }),
+        pairing_button("Unpair", || Msg::ToggleRemotePairing),
+    ]
+}
+
+#[view]
+fn pairing_button(title: &str, on_click: fn() -> Msg) -> Node<Msg> {
+    div![
+        C!["button-container"],
+        s()
+            .background_color(Color::Accent3)
+            .margin_bottom(rem(0.5))
+            .padding(rem(0.7))
+            .text_align(CssTextAlign::Center)
+            .cursor(CssCursor::Pointer),
+        s()
+            .hover()
+            .background_color(Color::Accent3Light1),
+        attrs!{
+            At::TabIndex => 0,
+            At::Title => title,
+        },
+        ev(Ev::Click, move |_| on_click()),
+        title,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_wait_for_the_confirmation() {
+        let mut state = PairingState::Waiting;
+        assert!(!state.accept(&RemoteMessage::TogglePlay));
+        assert_eq!(state, PairingState::Waiting);
+
+        assert!(!state.accept(&RemoteMessage::Hello));
+        assert!(!state.accept(&RemoteMessage::TogglePlay));
+        assert_eq!(state, PairingState::ConfirmationRequested);
+
+        state = PairingState::Confirmed;
+        assert!(state.accept(&RemoteMessage::TogglePlay));
+        assert!(state.accept(&RemoteMessage::Hello));
+    }
+}
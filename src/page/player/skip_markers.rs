@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use super::Marker;
+
+pub const SKIP_MARKERS_STORAGE_KEY_PREFIX: &str = "skip_markers";
+
+pub fn storage_key(meta_id: &str) -> String {
+    format!("{}_{}", SKIP_MARKERS_STORAGE_KEY_PREFIX, meta_id)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipMarkerKind {
+    IntroStart,
+    IntroEnd,
+    CreditsStart,
+}
+
+/// Intro and credits positions (in seconds) marked by the user once per series
+/// and reused for all its episodes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkipMarkers {
+    pub intro_start: Option<u32>,
+    pub intro_end: Option<u32>,
+    pub credits_start: Option<u32>,
+}
+
+impl SkipMarkers {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn set(&mut self, kind: SkipMarkerKind, time: u32) {
+        match kind {
+            SkipMarkerKind::IntroStart => self.intro_start = Some(time),
+            SkipMarkerKind::IntroEnd => self.intro_end = Some(time),
+            SkipMarkerKind::CreditsStart => self.credits_start = Some(time),
+        }
+    }
+
+    pub fn intro_range(&self) -> Option<(u32, u32)> {
+        let intro_start = self.intro_start.unwrap_or_default();
+        let intro_end = self.intro_end?;
+        (intro_start < intro_end).then(|| (intro_start, intro_end))
+    }
+
+    pub fn in_intro(&self, time: u32) -> bool {
+        matches!(self.intro_range(), Some((start, end)) if time >= start && time < end)
+    }
+
+    pub fn in_credits(&self, time: u32) -> bool {
+        matches!(self.credits_start, Some(credits_start) if time >= credits_start)
+    }
+
+    /// Seek bar markers, sorted by time.
+    pub fn to_markers(&self) -> Vec<Marker> {
+        let mut markers = Vec::new();
+        if let Some((intro_start, intro_end)) = self.intro_range() {
+            markers.push(Marker { time: intro_start, label: "Intro".to_owned() });
+            markers.push(Marker { time: intro_end, label: "Episode".to_owned() });
+        }
+        if let Some(credits_start) = self.credits_start {
+            markers.push(Marker { time: credits_start, label: "Credits".to_owned() });
+        }
+        markers.sort_by_key(|marker| marker.time);
+        markers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intro_without_end_is_ignored() {
+        let mut skip_markers = SkipMarkers::default();
+        skip_markers.set(SkipMarkerKind::IntroStart, 30);
+        assert_eq!(skip_markers.intro_range(), None);
+        assert!(!skip_markers.in_intro(40));
+    }
+
+    #[test]
+    fn markers_are_sorted_by_time() {
+        let mut skip_markers = SkipMarkers::default();
+        skip_markers.set(SkipMarkerKind::CreditsStart, 1200);
+        skip_markers.set(SkipMarkerKind::IntroEnd, 90);
+        skip_markers.set(SkipMarkerKind::IntroStart, 10);
+
+        assert!(skip_markers.in_intro(10));
+        assert!(!skip_markers.in_intro(90));
+        assert!(skip_markers.in_credits(1200));
+        let labels = skip_markers.to_markers().into_iter().map(|marker| marker.label).collect::<Vec<_>>();
+        assert_eq!(labels, vec!["Intro", "Episode", "Credits"]);
+    }
+}
@@ -0,0 +1,270 @@
+use seed::{prelude::*, *};
+use seed::browser::web_socket::WebSocket;
+use seed_hooks::{*, topo::nested as view};
+use seed_styles::{em, pc, rem, Style};
+use seed_styles::*;
+use serde::{Deserialize, Serialize};
+use stremio_core::types::resource::Stream;
+use crate::env::WebEnv;
+use crate::relay;
+use crate::styles::{self, themes::Color, global};
+use super::{Msg, VideoPath};
+
+pub const HEARTBEAT_INTERVAL: u32 = 5000;
+/// Guests seek to the host's position when they are off by more seconds than this.
+pub const MAX_DRIFT: u32 = 2;
+
+const ROOM_PREFIX: &str = "watch-party";
+
+pub struct WatchParty {
+    pub room: String,
+    pub participant_id: String,
+    pub host: bool,
+    pub connected: bool,
+    // serialized `Stream` currently played by all participants
+    pub shared_stream: Option<String>,
+    pub web_socket: WebSocket,
+    pub heartbeat_handle: Option<StreamHandle>,
+}
+
+impl WatchParty {
+    pub fn send(&self, message: PartyMessage) {
+        let envelope = Envelope {
+            sender: self.participant_id.clone(),
+            message,
+        };
+        if let Err(error) = self.web_socket.send_json(&envelope) {
+            log_error!("player", "Watch party message send failed: {:?}", error);
+        }
+    }
+}
+
+pub fn relay_url(room: &str) -> String {
+    relay::room_url(ROOM_PREFIX, room)
+}
+
+pub fn new_participant_id() -> String {
+    hex::encode(WebEnv::random_buffer(8))
+}
+
+// ------ Protocol ------
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Envelope {
+    pub sender: String,
+    #[serde(flatten)]
+    pub message: PartyMessage,
+}
+
+/// Messages exchanged between participants through the relay.
+/// Times are in seconds.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PartyMessage {
+    /// Sent by a guest after connecting; the host replies with `Stream` and `State`.
+    Join,
+    Play { time: u32 },
+    Pause { time: u32 },
+    Seek { time: u32 },
+    Stream {
+        stream: Stream,
+        video_path: Option<VideoPath>,
+    },
+    /// Periodic host heartbeat used for drift correction.
+    State { playing: bool, time: u32 },
+}
+
+/// Changes of the local playback requested by a received `PartyMessage`, in order.
+#[derive(Debug, PartialEq)]
+pub enum PlaybackCommand {
+    Play,
+    Pause,
+    Seek(u32),
+    /// Host only - re-send the current stream and state to a new participant.
+    ShareStream,
+}
+
+/// `PartyMessage::Stream` isn't handled here, it changes the page url.
+pub fn playback_commands(message: &PartyMessage, host: bool, playing: bool, local_time: Option<u32>) -> Vec<PlaybackCommand> {
+    match *message {
+        PartyMessage::Join if host => vec![PlaybackCommand::ShareStream],
+        PartyMessage::Join | PartyMessage::Stream { .. } => vec![],
+        PartyMessage::Play { time } => {
+            drift_correction(local_time, time).into_iter().chain(Some(PlaybackCommand::Play)).collect()
+        }
+        PartyMessage::Pause { time } => vec![PlaybackCommand::Pause, PlaybackCommand::Seek(time)],
+        PartyMessage::Seek { time } => vec![PlaybackCommand::Seek(time)],
+        PartyMessage::State { playing: remote_playing, time } => {
+            let toggle = match (remote_playing, playing) {
+                (true, false) => Some(PlaybackCommand::Play),
+                (false, true) => Some(PlaybackCommand::Pause),
+                _ => None,
+            };
+            drift_correction(local_time, time).into_iter().chain(toggle).collect()
+        }
+    }
+}
+
+/// Seeks to the remote time when the local one is unknown or off by more than `MAX_DRIFT`.
+fn drift_correction(local_time: Option<u32>, remote_time: u32) -> Option<PlaybackCommand> {
+    let drift = match local_time {
+        Some(local_time) => (i64::from(local_time) - i64::from(remote_time)).abs(),
+        None => i64::MAX,
+    };
+    (drift > i64::from(MAX_DRIFT)).then(|| PlaybackCommand::Seek(remote_time))
+}
+
+// ------ View ------
+
+#[view]
+pub fn watch_party_panel(watch_party: Option<&WatchParty>, room_input: &str) -> Node<Msg> {
+    div![
+        C!["watch-party-panel"],
+        s()
+            .background_color(Color::BackgroundDark2)
+            .color(hsl(0, 0, 100))
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Column)
+            .padding(rem(1))
+            .position(CssPosition::Absolute)
+            .right(rem(1))
+            .top(global::HORIZONTAL_NAV_BAR_SIZE)
+            .width(rem(18))
+            .z_index("1"),
+        if let Some(watch_party) = watch_party {
+            party_info(watch_party)
+        } else {
+            join_form(room_input)
+        },
+    ]
+}
+
+#[view]
+fn party_info(watch_party: &WatchParty) -> Vec<Node<Msg>> {
+    vec![
+        div![
+            C!["room"],
+            s()
+                .font_size(rem(1.2))
+                .margin_bottom(rem(0.5)),
+            "Room ",
+            strong![&watch_party.room],
+        ],
+        div![
+            C!["status"],
+            s()
+                .color(Color::SurfaceDark5_90)
+                .margin_bottom(rem(1)),
+            match (watch_party.connected, watch_party.host) {
+                (false, _) => "Connecting...",
+                (true, true) => "Connected (host)",
+                (true, false) => "Connected",
+            },
+        ],
+        panel_button("Leave", || Msg::LeaveWatchParty),
+    ]
+}
+
+#[view]
+fn join_form(room_input: &str) -> Vec<Node<Msg>> {
+    vec![
+        panel_button("Create room", || Msg::CreateWatchParty),
+        input![
+            C!["room-input"],
+            s()
+                .border("thin solid hsl(0deg 0% 100%)")
+                .color(hsl(0, 0, 100))
+                .margin("1rem 0")
+                .padding(rem(0.5)),
+            styles::text_input(),
+            attrs!{
+                At::Placeholder => "Room code",
+                At::Value => room_input,
+                At::from("autocorrect") => "off",
+                At::from("autocapitalize") => "characters",
+            },
+            input_ev(Ev::Input, Msg::WatchPartyRoomInputChanged),
+        ],
+        panel_button("Join room", || Msg::JoinWatchParty),
+    ]
+}
+
+#[view]
+fn panel_button(title: &str, on_click: fn() -> Msg) -> Node<Msg> {
+    div![
+        C!["button-container"],
+        s()
+            .background_color(Color::Accent3)
+            .padding(rem(0.7))
+            .text_align(CssTextAlign::Center)
+            .cursor(CssCursor::Pointer),
+        s()
+            .hover()
+            .background_color(Color::Accent3Light1),
+        attrs!{
+            At::TabIndex => 0,
+            At::Title => title,
+        },
+        ev(Ev::Click, move |_| on_click()),
+        title,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn envelopes_are_flattened() {
+        let envelope = Envelope { sender: "a1".to_owned(), message: PartyMessage::Seek { time: 42 } };
+        assert_eq!(serde_json::to_value(&envelope).unwrap(), json!({ "sender": "a1", "type": "seek", "time": 42 }));
+
+        let envelope = serde_json::from_value::<Envelope>(json!({ "sender": "b2", "type": "state", "playing": true, "time": 7 })).unwrap();
+        assert_eq!(envelope.sender, "b2");
+        assert!(matches!(envelope.message, PartyMessage::State { playing: true, time: 7 }));
+    }
+
+    #[test]
+    fn only_the_host_answers_joins() {
+        assert_eq!(playback_commands(&PartyMessage::Join, true, false, None), vec![PlaybackCommand::ShareStream]);
+        assert_eq!(playback_commands(&PartyMessage::Join, false, false, None), vec![]);
+    }
+
+    #[test]
+    fn small_drift_is_tolerated() {
+        let state = PartyMessage::State { playing: true, time: 100 };
+        assert_eq!(playback_commands(&state, false, true, Some(100 + MAX_DRIFT)), vec![]);
+        assert_eq!(playback_commands(&state, false, true, Some(100 - MAX_DRIFT)), vec![]);
+        assert_eq!(
+            playback_commands(&state, false, true, Some(100 + MAX_DRIFT + 1)),
+            vec![PlaybackCommand::Seek(100)],
+        );
+        assert_eq!(playback_commands(&state, false, true, None), vec![PlaybackCommand::Seek(100)]);
+    }
+
+    #[test]
+    fn heartbeats_sync_the_playing_state() {
+        assert_eq!(
+            playback_commands(&PartyMessage::State { playing: true, time: 10 }, false, false, Some(10)),
+            vec![PlaybackCommand::Play],
+        );
+        assert_eq!(
+            playback_commands(&PartyMessage::State { playing: false, time: 10 }, false, true, Some(30)),
+            vec![PlaybackCommand::Seek(10), PlaybackCommand::Pause],
+        );
+    }
+
+    #[test]
+    fn playback_messages_are_applied() {
+        assert_eq!(
+            playback_commands(&PartyMessage::Play { time: 50 }, false, false, Some(20)),
+            vec![PlaybackCommand::Seek(50), PlaybackCommand::Play],
+        );
+        assert_eq!(
+            playback_commands(&PartyMessage::Pause { time: 51 }, false, true, Some(51)),
+            vec![PlaybackCommand::Pause, PlaybackCommand::Seek(51)],
+        );
+        assert_eq!(playback_commands(&PartyMessage::Seek { time: 3 }, false, true, Some(80)), vec![PlaybackCommand::Seek(3)]);
+    }
+}
@@ -0,0 +1,351 @@
+use seed::{prelude::*, *};
+use seed::browser::web_socket::{WebSocket, WebSocketMessage};
+use seed_hooks::{*, topo::nested as view};
+use seed_styles::{em, pc, rem, Style};
+use seed_styles::*;
+use crate::{PageId, Events};
+use crate::relay;
+use crate::route::{self, Route};
+use crate::remote_control::{self, RemoteMessage, PlayerStatus};
+use crate::styles::{self, themes::Color, global};
+use crate::page::player::control_bar::seek_bar::format_time;
+
+const SEEK_STEP: i32 = 10;
+const VOLUME_STEP: u32 = 10;
+
+// ------ ------
+//     Init
+// ------ ------
+
+pub fn init(
+    url: Url,
+    code: Option<String>,
+    model: &mut Option<Model>,
+    orders: &mut impl Orders<Msg>,
+) -> Option<PageId> {
+    let base_url = route::base_url(&url);
+    let code = code.map(|code| code.to_uppercase());
+
+    let model = model.get_or_insert_with(move || Model {
+        base_url,
+        code_input: String::new(),
+        connection: None,
+        status: None,
+        page_change_sub_handle: None,
+    });
+    model.page_change_sub_handle = Some(orders.subscribe_with_handle(|events| {
+        matches!(events, Events::PageChanged(page_id) if page_id != PageId::Remote)
+            .then(|| Msg::Disconnect)
+    }));
+
+    let connected_code = model.connection.as_ref().map(|connection| connection.code.as_str());
+    if code.as_deref() != connected_code {
+        model.connection = None;
+        model.status = None;
+        if let Some(code) = code {
+            model.code_input = code.clone();
+            connect(code, model, orders);
+        }
+    }
+    Some(PageId::Remote)
+}
+
+fn connect(code: String, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    let web_socket = WebSocket::builder(relay::room_url(remote_control::ROOM_PREFIX, &code), orders)
+        .on_open(|| Msg::Connected)
+        .on_message(Msg::MessageReceived)
+        .on_close(|_| Msg::Disconnected)
+        .on_error(|| Msg::Disconnected)
+        .build_and_open();
+    match web_socket {
+        Ok(web_socket) => {
+            model.connection = Some(Connection { code, connected: false, web_socket });
+        }
+        Err(error) => log_error!("page::remote", "Remote control connection failed: {:?}", error),
+    }
+}
+
+// ------ ------
+//     Model
+// ------ ------
+
+pub struct Model {
+    base_url: Url,
+    code_input: String,
+    connection: Option<Connection>,
+    status: Option<PlayerStatus>,
+    page_change_sub_handle: Option<SubHandle>,
+}
+
+struct Connection {
+    code: String,
+    connected: bool,
+    web_socket: WebSocket,
+}
+
+// ------ ------
+//     Urls
+// ------ ------
+
+struct_urls!();
+impl<'a> Urls<'a> {
+    pub fn root(self) -> Url {
+        Route::Remote(None).to_url(&self.base_url())
+    }
+    pub fn code(self, code: &str) -> Url {
+        Route::Remote(Some(code.to_owned())).to_url(&self.base_url())
+    }
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+pub enum Msg {
+    CodeInputChanged(String),
+    Pair,
+    Connected,
+    MessageReceived(WebSocketMessage),
+    Disconnected,
+    Disconnect,
+    Send(RemoteMessage),
+}
+
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::CodeInputChanged(code) => {
+            model.code_input = code;
+        }
+        Msg::Pair => {
+            let code = model.code_input.trim().to_uppercase();
+            if code.is_empty() {
+                return
+            }
+            orders.request_url(Urls::new(&model.base_url).code(&code));
+        }
+        Msg::Connected => {
+            if let Some(connection) = model.connection.as_mut() {
+                connection.connected = true;
+            }
+            send(model, &RemoteMessage::Hello);
+        }
+        Msg::MessageReceived(message) => {
+            match message.json::<RemoteMessage>() {
+                Ok(RemoteMessage::Status(status)) => model.status = Some(status),
+                Ok(_) => orders.skip(),
+                Err(error) => log_error!("page::remote", "Remote control message is invalid: {:?}", error),
+            };
+        }
+        Msg::Disconnected => {
+            if let Some(connection) = model.connection.as_mut() {
+                connection.connected = false;
+            }
+        }
+        Msg::Disconnect => {
+            model.connection = None;
+            model.status = None;
+            model.page_change_sub_handle = None;
+        }
+        Msg::Send(message) => {
+            orders.skip();
+            send(model, &message);
+        }
+    }
+}
+
+fn send(model: &Model, message: &RemoteMessage) {
+    let connection = match &model.connection {
+        Some(connection) if connection.connected => connection,
+        _ => return
+    };
+    if let Err(error) = connection.web_socket.send_json(message) {
+        log_error!("page::remote", "Remote control message send failed: {:?}", error);
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+#[view]
+pub fn view(model: &Model) -> Node<Msg> {
+    div![
+        C!["remote-container"],
+        s()
+            .align_items(CssAlignItems::Center)
+            .background_color(Color::BackgroundDark2)
+            .color(Color::SurfaceLight5_90)
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Column)
+            .height(pc(100))
+            .overflow_y(CssOverflowY::Auto)
+            .padding(rem(2))
+            .width(pc(100)),
+        h1![
+            s()
+                .font_size(rem(1.8))
+                .margin_bottom(rem(2)),
+            "Remote control",
+        ],
+        match &model.connection {
+            Some(connection) => remote(connection, model.status.as_ref()),
+            None => pairing_form(&model.code_input),
+        },
+    ]
+}
+
+#[view]
+fn pairing_form(code_input: &str) -> Vec<Node<Msg>> {
+    vec![
+        div![
+            s()
+                .margin_bottom(rem(1)),
+            "Enter the code shown in the player:",
+        ],
+        input![
+            C!["code-input"],
+            s()
+                .border("thin solid hsl(0deg 0% 100%)")
+                .color(hsl(0, 0, 100))
+                .font_size(rem(2))
+                .letter_spacing(rem(0.3))
+                .margin_bottom(rem(1))
+                .padding(rem(0.5))
+                .text_align(CssTextAlign::Center)
+                .width(rem(14)),
+            styles::text_input(),
+            attrs!{
+                At::Placeholder => "CODE",
+                At::Value => code_input,
+                At::from("autocorrect") => "off",
+                At::from("autocapitalize") => "characters",
+            },
+            input_ev(Ev::Input, Msg::CodeInputChanged),
+        ],
+        remote_button("Pair", true, || Msg::Pair),
+    ]
+}
+
+#[view]
+fn remote(connection: &Connection, status: Option<&PlayerStatus>) -> Vec<Node<Msg>> {
+    let enabled = connection.connected && status.is_some();
+    let playing = status.map_or(false, |status| status.playing);
+    let volume = status.map_or(0, |status| status.volume);
+    let has_next_video = status.map_or(false, |status| status.has_next_video);
+    vec![
+        div![
+            C!["status"],
+            s()
+                .margin_bottom(rem(2))
+                .text_align(CssTextAlign::Center),
+            match (connection.connected, status) {
+                (false, _) => vec![div!["Connecting..."]],
+                (true, None) => vec![div![format!("Waiting for the player {} to allow the pairing...", connection.code)]],
+                (true, Some(status)) => vec![
+                    div![
+                        s()
+                            .font_size(rem(1.2))
+                            .margin_bottom(rem(0.5)),
+                        &status.title,
+                    ],
+                    div![
+                        format!(
+                            "{} / {} · volume {}%",
+                            format_time(status.time),
+                            format_time(status.duration),
+                            status.volume,
+                        )
+                    ],
+                ],
+            },
+        ],
+        remote_button(if playing { "Pause" } else { "Play" }, enabled, || Msg::Send(RemoteMessage::TogglePlay)),
+        div![
+            s()
+                .display(CssDisplay::Flex)
+                .flex_direction(CssFlexDirection::Row),
+            remote_button("-10 s", enabled, || Msg::Send(RemoteMessage::SeekBy { offset: -SEEK_STEP })),
+            remote_button("+10 s", enabled, || Msg::Send(RemoteMessage::SeekBy { offset: SEEK_STEP })),
+        ],
+        div![
+            s()
+                .display(CssDisplay::Flex)
+                .flex_direction(CssFlexDirection::Row),
+            {
+                let volume = volume.saturating_sub(VOLUME_STEP);
+                remote_button("Volume -", enabled, move || Msg::Send(RemoteMessage::SetVolume { volume }))
+            },
+            {
+                let volume = (volume + VOLUME_STEP).min(100);
+                remote_button("Volume +", enabled, move || Msg::Send(RemoteMessage::SetVolume { volume }))
+            },
+        ],
+        remote_button("Next episode", enabled && has_next_video, || Msg::Send(RemoteMessage::NextVideo)),
+    ]
+}
+
+#[view]
+fn remote_button(title: &str, enabled: bool, on_click: impl FnOnce() -> Msg + Clone + 'static) -> Node<Msg> {
+    div![
+        C!["remote-button", "button-container", IF!(not(enabled) => "disabled")],
+        s()
+            .background_color(Color::Accent3)
+            .font_size(rem(1.2))
+            .margin(rem(0.5))
+            .padding("1rem 2rem")
+            .text_align(CssTextAlign::Center)
+            .min_width(rem(8))
+            .cursor(CssCursor::Pointer),
+        s()
+            .hover()
+            .background_color(Color::Accent3Light1),
+        IF!(not(enabled) => {
+            s()
+                .opacity("0.5")
+                .pointer_events("none")
+        }),
+        attrs!{
+            At::TabIndex => if enabled { 0 } else { -1 },
+            At::Title => title,
+        },
+        ev(Ev::Click, move |_| on_click()),
+        title,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Msg as RootMsg;
+    use crate::test_env::TestApp;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn entered_code_opens_its_route() {
+        let app = TestApp::start().await;
+        app.open(Route::Remote(None)).await;
+        app.send(RootMsg::RemoteMsg(Msg::CodeInputChanged("  ".to_owned()))).await;
+        app.send(RootMsg::RemoteMsg(Msg::Pair)).await;
+        app.send(RootMsg::RemoteMsg(Msg::CodeInputChanged(" ab12cd34ef56 ".to_owned()))).await;
+        app.send(RootMsg::RemoteMsg(Msg::Pair)).await;
+
+        let requested_routes = app.read(|model| model.requested_urls.iter().map(Route::parse).collect::<Vec<_>>());
+        assert_eq!(requested_routes, vec![Some(Route::Remote(Some("AB12CD34EF56".to_owned())))]);
+    }
+
+    #[wasm_bindgen_test]
+    async fn connection_is_closed_when_the_page_is_left() {
+        let app = TestApp::start().await;
+        app.open(Route::Remote(Some("ab12cd34ef56".to_owned()))).await;
+        let connected_code = app.read(|model| {
+            let remote_model = model.model.remote_model.as_ref().expect("remote model");
+            remote_model.connection.as_ref().map(|connection| connection.code.clone())
+        });
+        assert_eq!(connected_code.as_deref(), Some("AB12CD34EF56"));
+
+        app.open(Route::Logs).await;
+        assert!(app.read(|model| model.model.remote_model.as_ref().expect("remote model").connection.is_none()));
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{PageId, Msg as RootMsg, Urls as RootUrls, Context};
+use crate::{PageId, Msg as RootMsg, Urls as RootUrls, Context, Actions};
+use crate::analytics::AnalyticsEvent;
 use seed::{prelude::*, *};
 use seed_styles::{pc, rem, em};
 use seed_styles::*;
@@ -7,6 +8,7 @@ use serde::Deserialize;
 use localsearch::LocalSearch;
 use seed_hooks::{*, topo::nested as view};
 use crate::basic_layout::{basic_layout, BasicLayoutArgs, SearchArgs};
+use crate::route::{self, Route};
 use std::rc::Rc;
 use stremio_core::types::addon::{ResourceRequest, ResourceResponse, ResourcePath};
 
@@ -21,12 +23,12 @@ fn on_click_not_implemented() -> EventHandler<Msg> {
 // ------ ------
 
 pub fn init(
-    mut url: Url,
+    url: Url,
+    search_query: Option<String>,
     model: &mut Option<Model>,
     orders: &mut impl Orders<Msg>,
 ) -> Option<PageId> {
-    let base_url = url.to_hash_base_url();
-    let search_query = url.next_hash_path_part().map(ToOwned::to_owned);
+    let base_url = route::base_url(&url);
     let input_search_query = search_query.clone().unwrap_or_default();
 
     if let Some(model) = model {
@@ -45,7 +47,7 @@ pub fn init(
             search_results: Vec::new(),
         });
         orders.perform_cmd(async { 
-            Msg::VideosReceived(get_videos().await.unwrap()) 
+            Msg::VideosReceived(get_videos().await) 
         });
     }
     Some(PageId::Search)
@@ -103,10 +105,10 @@ pub struct Video {
 struct_urls!();
 impl<'a> Urls<'a> {
     pub fn root(self) -> Url {
-        self.base_url()
+        Route::Search(None).to_url(&self.base_url())
     }
     pub fn query(self, query: &str) -> Url {
-        self.base_url().add_hash_path_part(query)
+        Route::Search(Some(query.to_owned())).to_url(&self.base_url())
     }
 }
 
@@ -117,7 +119,7 @@ impl<'a> Urls<'a> {
 pub enum Msg {
     SearchQueryInputChanged(String),
     UpdateSearchQuery,
-    VideosReceived(Vec<Video>),
+    VideosReceived(Result<Vec<Video>, FetchError>),
     Search,
 }
 
@@ -132,63 +134,78 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
         Msg::UpdateSearchQuery => {
             orders.request_url(Urls::new(&model.base_url).query(&model.input_search_query));
         }
-        Msg::VideosReceived(videos) => {
-            let mut cinemeta_top_movie = Vec::new();
-            let mut cinemeta_top_series = Vec::new();
-
-            for video in videos {
-                match video.r#type.as_str() {
-                    "movie" => cinemeta_top_movie.push(video),  
-                    "series" => cinemeta_top_series.push(video),
-                    unknown => {
-                        log!("Unhandled MetaItem type:", unknown);
-                    }
-                }
-            }
-            model.video_groups = vec![
-                VideoGroup {
-                    label: "Cinemeta - top movie".to_owned(),
-                    videos: index(cinemeta_top_movie),
-                    see_all_url: RootUrls::new(&context.root_base_url).discover_urls().res_req(&ResourceRequest::new(
-                        "https://v4-cinemeta.strem.io/manifest.json".parse().expect("valid BASE url"),
-                        ResourcePath::without_extra("catalog", "movie", "top"),
-                    )),
-                },
-                VideoGroup {
-                    label: "Cinemeta - top series".to_owned(),
-                    videos: index(cinemeta_top_series),
-                    see_all_url: RootUrls::new(&context.root_base_url).discover_urls().res_req(&ResourceRequest::new(
-                        "https://v4-cinemeta.strem.io/manifest.json".parse().expect("valid BASE url"),
-                        ResourcePath::without_extra("catalog", "series", "top"),
-                    )),
-                },
-            ];
+        Msg::VideosReceived(Ok(videos)) => {
+            model.video_groups = video_groups(videos, &context.root_base_url);
             orders.send_msg(Msg::Search);
         }
+        Msg::VideosReceived(Err(error)) => {
+            log_error!("page::search", "Search videos loading failed: {:?}", error);
+        }
         Msg::Search => {
-            let mut search_results = Vec::new();
-            if let Some(search_query) = &model.search_query {
-                for group in &model.video_groups {
-
-                    let group_results = group
-                        .videos
-                        .search(search_query, 10)
-                        .into_iter()
-                        .map(|(video, _)| video.clone())
-                        .collect::<Vec<_>>();
-
-                    if !group_results.is_empty() {
-                        search_results.push(VideoGroupResults {
-                            label: group.label.clone(),
-                            videos: group_results,
-                            see_all_url: group.see_all_url.clone(),
-                        });
-                    }
+            model.search_results = match &model.search_query {
+                Some(search_query) => {
+                    orders.notify(Actions::EmitAnalyticsEvent(AnalyticsEvent::Search {
+                        query_length: search_query.chars().count(),
+                    }));
+                    search_results(&model.video_groups, search_query)
                 }
+                None => Vec::new(),
+            };
+        }
+    }
+}
+
+fn video_groups(videos: Vec<Video>, root_base_url: &Url) -> Vec<VideoGroup> {
+    let mut cinemeta_top_movie = Vec::new();
+    let mut cinemeta_top_series = Vec::new();
+
+    for video in videos {
+        match video.r#type.as_str() {
+            "movie" => cinemeta_top_movie.push(video),  
+            "series" => cinemeta_top_series.push(video),
+            unknown => {
+                log_warn!("page::search", "Unhandled MetaItem type: {}", unknown);
             }
-            model.search_results = search_results;
         }
     }
+    vec![
+        VideoGroup {
+            label: "Cinemeta - top movie".to_owned(),
+            videos: index(cinemeta_top_movie),
+            see_all_url: RootUrls::new(root_base_url).discover_urls().res_req(&ResourceRequest::new(
+                "https://v4-cinemeta.strem.io/manifest.json".parse().expect("valid BASE url"),
+                ResourcePath::without_extra("catalog", "movie", "top"),
+            )),
+        },
+        VideoGroup {
+            label: "Cinemeta - top series".to_owned(),
+            videos: index(cinemeta_top_series),
+            see_all_url: RootUrls::new(root_base_url).discover_urls().res_req(&ResourceRequest::new(
+                "https://v4-cinemeta.strem.io/manifest.json".parse().expect("valid BASE url"),
+                ResourcePath::without_extra("catalog", "series", "top"),
+            )),
+        },
+    ]
+}
+
+/// Groups without results are left out.
+fn search_results(video_groups: &[VideoGroup], search_query: &str) -> Vec<VideoGroupResults> {
+    video_groups
+        .iter()
+        .filter_map(|group| {
+            let group_results = group
+                .videos
+                .search(search_query, 10)
+                .into_iter()
+                .map(|(video, _)| video.clone())
+                .collect::<Vec<_>>();
+            not(group_results.is_empty()).then(|| VideoGroupResults {
+                label: group.label.clone(),
+                videos: group_results,
+                see_all_url: group.see_all_url.clone(),
+            })
+        })
+        .collect()
 }
 
 fn index(videos: Vec<Video>) -> LocalSearch<Video> {
@@ -563,3 +580,63 @@ fn poster_container(poster: &str) -> Node<Msg> {
         ]
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::TestApp;
+    use serde_json::json;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    fn video(name: &str, r#type: &str) -> Video {
+        serde_json::from_value(json!({
+            "id": name,
+            "name": name,
+            "poster": "",
+            "type": r#type,
+            "imdbRating": 8.0,
+            "popularity": 1.0,
+        })).expect("valid video")
+    }
+
+    #[test]
+    fn videos_are_grouped_by_type() {
+        let video_groups = video_groups(
+            vec![video("The Matrix", "movie"), video("Breaking Bad", "series"), video("News", "channel")],
+            &Url::new(),
+        );
+        assert_eq!(video_groups.len(), 2);
+
+        let search_results = search_results(&video_groups, "matrix");
+        assert_eq!(search_results.len(), 1);
+        assert_eq!(search_results[0].label, "Cinemeta - top movie");
+        assert_eq!(search_results[0].videos[0].name, "The Matrix");
+    }
+
+    #[wasm_bindgen_test]
+    async fn query_from_the_route_is_searched() {
+        let app = TestApp::start().await;
+        app.open(Route::Search(None)).await;
+        let videos = vec![video("The Matrix", "movie"), video("Breaking Bad", "series")];
+        app.send(RootMsg::SearchMsg(Msg::VideosReceived(Ok(videos)))).await;
+        app.open(Route::Search(Some("breaking".to_owned()))).await;
+
+        let results = app.read(|model| {
+            let search_model = model.model.search_model.as_ref().expect("search model");
+            search_model.search_results.iter().map(|group| group.label.clone()).collect::<Vec<_>>()
+        });
+        assert_eq!(results, vec!["Cinemeta - top series".to_owned()]);
+    }
+
+    #[wasm_bindgen_test]
+    async fn typed_query_opens_its_route() {
+        let app = TestApp::start().await;
+        app.open(Route::Search(None)).await;
+        app.send(RootMsg::SearchMsg(Msg::SearchQueryInputChanged("matrix".to_owned()))).await;
+
+        let requested_routes = app.read(|model| model.requested_urls.iter().map(Route::parse).collect::<Vec<_>>());
+        assert_eq!(requested_routes, vec![Some(Route::Search(Some("matrix".to_owned())))]);
+    }
+}
@@ -7,10 +7,15 @@ use std::rc::Rc;
 use std::collections::HashMap;
 use url::Url as CoreUrl;
 use stremio_core::types::profile::User;
+use stremio_core::runtime::EnvError;
 use stremio_core::runtime::msg::{Action, ActionStreamingServer, ActionCtx, Msg as CoreMsg};
 use stremio_core::models::common::Loadable;
 use crate::{multi_select, Msg as RootMsg, Context, PageId, Actions, Urls as RootUrls, Events};
 use crate::basic_layout::{basic_layout, BasicLayoutArgs};
+use crate::route::{self, Route};
+use crate::env::http_cache;
+use crate::env::storage_usage::{self, StorageUsage};
+use crate::analytics::AnalyticsConsent;
 use crate::styles::{self, themes::{Color, Breakpoint}, global};
 use web_sys::{
     ScrollIntoViewOptions, 
@@ -37,7 +42,7 @@ pub fn init(
     context: &mut Context,
     orders: &mut impl Orders<Msg>,
 ) -> Option<PageId> {
-    let base_url = url.to_hash_base_url();
+    let base_url = route::base_url(&url);
 
     orders
         .after_next_render(|_| Msg::Rendered);
@@ -49,13 +54,17 @@ pub fn init(
                 (Section::General, 0.),
                 (Section::Player, 0.),
                 (Section::StreamingServer, 0.),
+                (Section::Privacy, 0.),
+                (Section::Storage, 0.),
             ].into_iter().collect(),
             section_refs: SectionRefs::default(),
             observer: None,
             observer_callback: None,
             page_change_sub_handle: None,
+            storage_usage: None,
         }
     });
+    orders.send_msg(Msg::LoadStorageUsage);
     model.page_change_sub_handle = Some(orders.subscribe_with_handle(|events| {
         if let Events::PageChanged(page_id) = events {
             return Some(Msg::PageChanged(page_id))
@@ -76,6 +85,7 @@ pub struct Model {
     observer: Option<IntersectionObserver>,
     observer_callback: Option<Closure<dyn Fn(Vec<JsValue>)>>,
     page_change_sub_handle: Option<SubHandle>,
+    storage_usage: Option<StorageUsage>,
 }
 
 // ------ ------
@@ -85,7 +95,7 @@ pub struct Model {
 struct_urls!();
 impl<'a> Urls<'a> {
     pub fn root(self) -> Url {
-        self.base_url()
+        Route::Settings.to_url(&self.base_url())
     }
 }
 
@@ -101,6 +111,11 @@ pub enum Msg {
     MenuButtonClicked(Section),
     UpdateSettings(UpdateSettingsMsg),
     ReloadStreamingServer,
+    ClearCache,
+    CacheCleared(Result<(), EnvError>),
+    SetAnalyticsConsent(AnalyticsConsent),
+    LoadStorageUsage,
+    StorageUsageLoaded(Result<StorageUsage, EnvError>),
 }
 
 pub enum UpdateSettingsMsg {
@@ -150,6 +165,8 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
             observer.observe(&model.section_refs.general.get().unwrap());
             observer.observe(&model.section_refs.player.get().unwrap());
             observer.observe(&model.section_refs.streaming_server.get().unwrap());
+            observer.observe(&model.section_refs.privacy.get().unwrap());
+            observer.observe(&model.section_refs.storage.get().unwrap());
 
             model.observer = Some(observer);
             model.observer_callback = Some(callback);
@@ -170,6 +187,12 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
                     target_el if Some(target_el) == model.section_refs.streaming_server.get().as_ref() => {
                         Section::StreamingServer
                     }
+                    target_el if Some(target_el) == model.section_refs.privacy.get().as_ref() => {
+                        Section::Privacy
+                    }
+                    target_el if Some(target_el) == model.section_refs.storage.get().as_ref() => {
+                        Section::Storage
+                    }
                     _ => {
                         orders.skip();
                         return
@@ -207,6 +230,8 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
                 Section::General => &model.section_refs.general,
                 Section::Player => &model.section_refs.player,
                 Section::StreamingServer => &model.section_refs.streaming_server,
+                Section::Privacy => &model.section_refs.privacy,
+                Section::Storage => &model.section_refs.storage,
             };
             let mut options = ScrollIntoViewOptions::new();
             // @TODO: Does it work on Safari?
@@ -241,6 +266,27 @@ pub fn update(msg: Msg, model: &mut Model, context: &mut Context, orders: &mut i
                 ActionStreamingServer::Reload,
             )))));
         }
+        Msg::ClearCache => {
+            orders.perform_cmd(async { Msg::CacheCleared(http_cache::clear().await) });
+        }
+        Msg::SetAnalyticsConsent(consent) => {
+            orders.notify(Actions::SetAnalyticsConsent(consent));
+        }
+        Msg::CacheCleared(Ok(())) => {
+            orders.send_msg(Msg::LoadStorageUsage);
+        }
+        Msg::CacheCleared(Err(error)) => {
+            log_error!("page::settings", "Clear cache failed: {}", error.message());
+        }
+        Msg::LoadStorageUsage => {
+            orders.skip().perform_cmd(async { Msg::StorageUsageLoaded(storage_usage::storage_usage().await) });
+        }
+        Msg::StorageUsageLoaded(Ok(storage_usage)) => {
+            model.storage_usage = Some(storage_usage);
+        }
+        Msg::StorageUsageLoaded(Err(error)) => {
+            log_error!("page::settings", "Storage usage loading failed: {}", error.message());
+        }
     }
 }
 
@@ -279,8 +325,50 @@ fn settings_content<'a>(model: &Model, context: &Context) -> Node<Msg> {
             .height(pc(100))
             .width(pc(100)),            
         side_menu(model.active_section, server_version),
-        sections(settings, &context.root_base_url, user, &model.section_refs, &context.core_model.streaming_server),
+        sections(
+            settings,
+            &context.root_base_url,
+            user,
+            &model.section_refs,
+            &context.core_model.streaming_server,
+            context.analytics_consent,
+            model.storage_usage.as_ref(),
+        ),
     ]
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::{TestApp, TestEnv};
+    use stremio_core::constants::PROFILE_STORAGE_KEY;
+    use stremio_core::types::profile::Profile;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn updated_settings_are_persisted() {
+        let app = TestApp::start().await;
+        app.open(Route::Settings).await;
+        let binge_watching = app.read(|model| model.model.context.core_model.ctx.profile.settings.binge_watching);
+        app.send(RootMsg::SettingsMsg(Msg::UpdateSettings(UpdateSettingsMsg::BingeWatching(!binge_watching)))).await;
+
+        let settings_binge_watching = app.read(|model| model.model.context.core_model.ctx.profile.settings.binge_watching);
+        assert_eq!(settings_binge_watching, !binge_watching);
+        let profile = TestEnv::storage_item::<Profile>(PROFILE_STORAGE_KEY).expect("persisted profile");
+        assert_eq!(profile.settings.binge_watching, !binge_watching);
+    }
+
+    #[wasm_bindgen_test]
+    async fn sections_are_observed_only_on_the_page() {
+        let app = TestApp::start().await;
+        app.open(Route::Settings).await;
+        assert!(app.read(|model| model.model.settings_model.as_ref().expect("settings model").observer.is_some()));
+
+        app.open(Route::Logs).await;
+        assert!(app.read(|model| model.model.settings_model.as_ref().expect("settings model").observer.is_none()));
+    }
+}
@@ -6,6 +6,8 @@ use std::borrow::Cow;
 use stremio_core::types::profile::{User, Settings};
 use stremio_core::models::streaming_server::StreamingServer;
 use crate::Urls as RootUrls;
+use crate::analytics::AnalyticsConsent;
+use crate::env::storage_usage::StorageUsage;
 use crate::styles::{self, themes::Color, global};
 use crate::page::settings::Msg;
 use crate::page::settings::section::{
@@ -25,11 +27,19 @@ use player::player_section;
 mod streaming_server;
 use streaming_server::streaming_server_section;
 
+mod privacy;
+use privacy::privacy_section;
+
+mod storage;
+use storage::storage_section;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Section {
     General,
     Player,
     StreamingServer,
+    Privacy,
+    Storage,
 }
 
 #[derive(Default)]
@@ -38,6 +48,8 @@ pub struct SectionRefs {
     pub general: ElRef<Element>,
     pub player: ElRef<Element>,
     pub streaming_server: ElRef<Element>,
+    pub privacy: ElRef<Element>,
+    pub storage: ElRef<Element>,
 }
 
 #[view]
@@ -46,7 +58,9 @@ pub fn sections(
     root_base_url: &Url, 
     user: Option<&User>, 
     section_refs: &SectionRefs, 
-    streaming_server: &StreamingServer
+    streaming_server: &StreamingServer,
+    analytics_consent: Option<AnalyticsConsent>,
+    storage_usage: Option<&StorageUsage>,
 ) -> Node<Msg> {
     div![
         el_ref(&section_refs.container),
@@ -59,6 +73,8 @@ pub fn sections(
         general_section(settings, root_base_url, user, &section_refs.general),
         player_section(settings, &section_refs.player),
         streaming_server_section(settings, &section_refs.streaming_server, streaming_server),
+        privacy_section(analytics_consent, &section_refs.privacy),
+        storage_section(storage_usage, &section_refs.storage),
     ]
 }
 
@@ -14,7 +14,7 @@ pub fn status(streaming_server: &StreamingServer) -> Node<Msg> {
         Loadable::Loading => "Loading...",
         Loadable::Ready(_) => "Online",
         Loadable::Err(error) => {
-            error!(error.code(), error.message());
+            log_error!("page::settings", "Streaming server error {}: {}", error.code(), error.message());
             "Error"
         }
     };
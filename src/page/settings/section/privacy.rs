@@ -0,0 +1,34 @@
+use seed::{prelude::*, *};
+use seed_hooks::{*, topo::nested as view};
+use seed_styles::{em, pc, rem, Style};
+use seed_styles::*;
+use crate::analytics::AnalyticsConsent;
+use crate::page::settings::Msg;
+use crate::page::settings::section::{
+    section_option,
+    section,
+    control::{label, checkbox, link_label}
+};
+use web_sys::Element;
+
+#[view]
+pub fn privacy_section(analytics_consent: Option<AnalyticsConsent>, section_ref: &ElRef<Element>) -> Node<Msg> {
+    let analytics_granted = AnalyticsConsent::is_granted(analytics_consent);
+    let options = vec![
+        section_option(None, vec![
+            label("Send anonymous usage data"),
+            checkbox(
+                analytics_granted,
+                {
+                    let new_consent = if analytics_granted { AnalyticsConsent::Denied } else { AnalyticsConsent::Granted };
+                    ev(Ev::Click, move |_| Msg::SetAnalyticsConsent(new_consent))
+                },
+                true
+            ),
+        ]),
+        section_option(Some(s().margin_bottom("0")), vec![
+            link_label("Privacy Policy", "https://www.stremio.com/privacy")
+        ]),
+    ];
+    section("Privacy", true, section_ref, options)
+}
@@ -0,0 +1,107 @@
+use seed::{prelude::*, *};
+use seed_hooks::{*, topo::nested as view};
+use seed_styles::{em, pc, rem, Style};
+use seed_styles::*;
+use crate::env::http_cache;
+use crate::env::storage_usage::StorageUsage;
+use crate::storage_schema::BACKUP_KEY_PREFIX;
+use crate::styles::{self, themes::Color, global};
+use crate::page::settings::Msg;
+use crate::page::settings::section::{
+    section_option,
+    section,
+    control::{label, large_button}
+};
+use web_sys::Element;
+
+#[view]
+pub fn storage_section(storage_usage: Option<&StorageUsage>, section_ref: &ElRef<Element>) -> Node<Msg> {
+    let mut options = vec![
+        section_option(None, vec![
+            label("Used"),
+            value(&storage_usage.map_or_else(|| "Loading...".to_owned(), used)),
+        ]),
+    ];
+    if let Some(storage_usage) = storage_usage {
+        let size_of = |is_grouped_key: fn(&str) -> bool| {
+            storage_usage
+                .items
+                .iter()
+                .filter(|(key, _)| is_grouped_key(key))
+                .map(|(_, size)| size)
+                .sum::<u64>()
+        };
+        options.push(section_option(None, vec![
+            label("Cache"),
+            value(&format_size(size_of(http_cache::is_cache_key))),
+        ]));
+        options.push(section_option(None, vec![
+            label("Backups"),
+            value(&format_size(size_of(is_backup_key))),
+        ]));
+        let other_items = storage_usage
+            .items
+            .iter()
+            .filter(|(key, _)| not(http_cache::is_cache_key(key) || is_backup_key(key)));
+        for (key, size) in other_items {
+            options.push(section_option(None, vec![
+                label(key),
+                value(&format_size(*size)),
+            ]));
+        }
+    }
+    options.push(section_option(Some(s().margin_bottom("0")), vec![
+        large_button("Clear cache", None, Some(|| Msg::ClearCache))
+    ]));
+    section("Storage", false, section_ref, options)
+}
+
+#[view]
+fn value(value: &str) -> Node<Msg> {
+    div![
+        C!["option-input-container", "info-container"],
+        s()
+            .justify_content(CssJustifyContent::Center)
+            .padding(rem(1))
+            .align_items(CssAlignItems::Center)
+            .display(CssDisplay::Flex)
+            .flex("1 1 50%")
+            .flex_direction(CssFlexDirection::Row),
+        div![
+            C!["label"],
+            s()
+                .color(Color::SurfaceLight5_90)
+                .flex_basis(CssFlexBasis::Auto)
+                .flex_grow("0")
+                .flex_shrink("1")
+                .line_height(rem(1.5)),
+            value,
+        ]
+    ]
+}
+
+fn is_backup_key(key: &str) -> bool {
+    key.starts_with(BACKUP_KEY_PREFIX)
+}
+
+fn used(storage_usage: &StorageUsage) -> String {
+    match (storage_usage.usage, storage_usage.quota) {
+        (Some(usage), Some(quota)) => format!("{} of {}", format_size(usage), format_size(quota)),
+        _ => format_size(storage_usage.items.iter().map(|(_, size)| size).sum()),
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
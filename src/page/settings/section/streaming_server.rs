@@ -55,5 +55,5 @@ pub fn streaming_server_section(
             ]),
         ]);
     }
-    section("Streaming Server", false, section_ref, options)
+    section("Streaming Server", true, section_ref, options)
 }
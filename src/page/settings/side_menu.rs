@@ -36,6 +36,16 @@ pub fn side_menu(active_section: Section, server_version: Option<&str>) -> Node<
             active_section == Section::StreamingServer, 
             || Msg::MenuButtonClicked(Section::StreamingServer)
         ),
+        side_menu_button(
+            "Privacy", 
+            active_section == Section::Privacy, 
+            || Msg::MenuButtonClicked(Section::Privacy)
+        ),
+        side_menu_button(
+            "Storage", 
+            active_section == Section::Storage, 
+            || Msg::MenuButtonClicked(Section::Storage)
+        ),
         div![
             C!["spacing"],
             s()
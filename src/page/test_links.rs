@@ -76,5 +76,32 @@ pub fn view<Ms: 'static>(root_base_url: &Url) -> Node<Ms> {
             },
             "Go to Detail (Hard Kill [movie]) ▶"
         ],
+        a![
+            style! {
+                St::Padding => px(20),
+            },
+            attrs! {
+                At::Href => RootUrls::new(root_base_url).remote_urls().root()
+            },
+            "Go to Remote control ▶"
+        ],
+        a![
+            style! {
+                St::Padding => px(20),
+            },
+            attrs! {
+                At::Href => RootUrls::new(root_base_url).logs()
+            },
+            "Go to Logs ▶"
+        ],
+        a![
+            style! {
+                St::Padding => px(20),
+            },
+            attrs! {
+                At::Href => RootUrls::new(root_base_url).inspector()
+            },
+            "Go to Core inspector ▶"
+        ],
     ]
 }
@@ -0,0 +1,26 @@
+use crate::env::WebEnv;
+
+/// WebSocket relay forwarding messages between clients in the same room,
+/// set by the `RELAY_URL` env variable during the build.
+/// See `/relay/relay.js` for a local stand-in.
+pub const RELAY_URL: &str = match option_env!("RELAY_URL") {
+    Some(url) => url,
+    None => "ws://localhost:8787",
+};
+
+pub fn room_url(room_prefix: &str, room: &str) -> String {
+    format!("{}/{}-{}", RELAY_URL, room_prefix, room)
+}
+
+const ROOM_CODE_BYTES: usize = 3;
+const SECRET_ROOM_CODE_BYTES: usize = 6;
+
+/// Short code users can type on another device.
+pub fn new_room_code() -> String {
+    hex::encode_upper(WebEnv::random_buffer(ROOM_CODE_BYTES))
+}
+
+/// Code of a room that gives control over the player, long enough not to be guessed.
+pub fn new_secret_room_code() -> String {
+    hex::encode_upper(WebEnv::random_buffer(SECRET_ROOM_CODE_BYTES))
+}
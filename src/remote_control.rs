@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+/// Relay room prefix, the room itself is the pairing code.
+pub const ROOM_PREFIX: &str = "remote";
+
+/// Messages exchanged between a remote control and the paired player through the relay.
+/// Times are in seconds, volume is in percent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RemoteMessage {
+    // ---- remote -> player ----
+    /// Sent by the remote after connecting; the player replies with `Status`.
+    Hello,
+    TogglePlay,
+    SeekBy { offset: i32 },
+    SeekTo { time: u32 },
+    SetVolume { volume: u32 },
+    NextVideo,
+    // ---- player -> remote ----
+    Status(PlayerStatus),
+}
+
+/// What the paired player does for a received `RemoteMessage`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayerCommand {
+    SendStatus,
+    TogglePlay,
+    SeekBy(i32),
+    SeekTo(u32),
+    SetVolume(u32),
+    NextVideo,
+}
+
+impl RemoteMessage {
+    /// `None` for the messages sent by the player.
+    pub fn player_command(&self) -> Option<PlayerCommand> {
+        match *self {
+            Self::Hello => Some(PlayerCommand::SendStatus),
+            Self::TogglePlay => Some(PlayerCommand::TogglePlay),
+            Self::SeekBy { offset } => Some(PlayerCommand::SeekBy(offset)),
+            Self::SeekTo { time } => Some(PlayerCommand::SeekTo(time)),
+            Self::SetVolume { volume } => Some(PlayerCommand::SetVolume(volume.min(100))),
+            Self::NextVideo => Some(PlayerCommand::NextVideo),
+            Self::Status(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerStatus {
+    pub title: String,
+    pub playing: bool,
+    pub time: Option<u32>,
+    pub duration: Option<u32>,
+    pub volume: u32,
+    pub has_next_video: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn messages_match_the_relay_clients() {
+        let messages = vec![
+            (json!({ "type": "hello" }), RemoteMessage::Hello),
+            (json!({ "type": "togglePlay" }), RemoteMessage::TogglePlay),
+            (json!({ "type": "seekBy", "offset": -10 }), RemoteMessage::SeekBy { offset: -10 }),
+            (json!({ "type": "seekTo", "time": 90 }), RemoteMessage::SeekTo { time: 90 }),
+            (json!({ "type": "setVolume", "volume": 40 }), RemoteMessage::SetVolume { volume: 40 }),
+            (json!({ "type": "nextVideo" }), RemoteMessage::NextVideo),
+        ];
+        for (json, message) in messages {
+            assert_eq!(serde_json::from_value::<RemoteMessage>(json.clone()).unwrap(), message);
+            assert_eq!(serde_json::to_value(&message).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn status_is_camel_cased() {
+        let status = RemoteMessage::Status(PlayerStatus {
+            title: "Trailer".to_owned(),
+            playing: true,
+            time: Some(5),
+            duration: None,
+            volume: 80,
+            has_next_video: false,
+        });
+        assert_eq!(serde_json::to_value(&status).unwrap(), json!({
+            "type": "status",
+            "title": "Trailer",
+            "playing": true,
+            "time": 5,
+            "duration": null,
+            "volume": 80,
+            "hasNextVideo": false,
+        }));
+    }
+
+    #[test]
+    fn messages_are_mapped_to_player_commands() {
+        assert_eq!(RemoteMessage::Hello.player_command(), Some(PlayerCommand::SendStatus));
+        assert_eq!(RemoteMessage::SeekTo { time: 12 }.player_command(), Some(PlayerCommand::SeekTo(12)));
+        assert_eq!(RemoteMessage::SetVolume { volume: 250 }.player_command(), Some(PlayerCommand::SetVolume(100)));
+        assert_eq!(RemoteMessage::Status(PlayerStatus::default()).player_command(), None);
+    }
+}
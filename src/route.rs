@@ -0,0 +1,696 @@
+use seed::{prelude::*, *};
+use std::cell::Cell;
+use url::form_urlencoded;
+use wasm_bindgen::{closure::Closure, JsCast};
+use stremio_core::models::installed_addons_with_filters::InstalledAddonsRequest;
+use stremio_core::models::library_with_filters::{LibraryRequest, LibraryRequestPage, Sort};
+use stremio_core::types::addon::ResourceRequest;
+use stremio_core::types::resource::Stream;
+use crate::page::addons::AddonRequest;
+use crate::page::intro::FormType;
+use crate::page::player::VideoPath;
+
+// ---- url parts ----
+
+const DISCOVER: &str = "discover";
+const DETAIL: &str = "detail";
+// the detail page's part before the path routing mode
+const LEGACY_DETAIL: &str = "metadetails";
+const INTRO: &str = "intro";
+const LIBRARY: &str = "library";
+const PLAYER: &str = "player";
+const ADDONS: &str = "addons";
+const SEARCH: &str = "search";
+const SETTINGS: &str = "settings";
+const REMOTE: &str = "remote";
+const TEST_LINKS: &str = "test_links";
+const LOGS: &str = "logs";
+const INSPECTOR: &str = "inspector";
+const BUG_REPORT: &str = "bug_report";
+
+const LOGIN: &str = "login";
+// the route opened after authentication, e.g. `#/intro/login?return_to=library%252Fall%252Fname%252F1`
+const RETURN_TO: &str = "return_to";
+// `LibraryRequest` without a type
+const ALL_TYPES: &str = "all";
+const LAST_WATCHED: &str = "last_watched";
+const NAME: &str = "name";
+const TIMES_WATCHED: &str = "times_watched";
+
+// ------ RoutingMode ------
+
+/// Read from the `data-routing` attribute of the app element, see `index.html`.
+const ROUTING_MODE_ATTRIBUTE: &str = "data-routing";
+
+thread_local! {
+    static MODE: Cell<RoutingMode> = Cell::new(RoutingMode::Hash);
+    static HISTORY_TRAVERSED: Cell<bool> = Cell::new(false);
+}
+
+/// Where routes are encoded in URLs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoutingMode {
+    /// `/#/detail/series/tt8111088`, works on any static host and in shells.
+    Hash,
+    /// `/detail/series/tt8111088`, the server has to serve `index.html` for all paths (see `netlify.toml`).
+    Path,
+}
+
+impl RoutingMode {
+    fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "hash" => Some(Self::Hash),
+            "path" => Some(Self::Path),
+            _ => None,
+        }
+    }
+}
+
+/// Selects the routing mode set on the app element; it has to be called before any URL is built.
+pub fn init_mode(app_element_id: &str) {
+    let mode = document()
+        .get_element_by_id(app_element_id)
+        .and_then(|element| element.get_attribute(ROUTING_MODE_ATTRIBUTE))
+        .map(|mode| {
+            RoutingMode::parse(&mode).unwrap_or_else(|| {
+                log_warn!("route", "Unknown routing mode '{}', the hash mode is used", mode);
+                RoutingMode::Hash
+            })
+        })
+        .unwrap_or(RoutingMode::Hash);
+    MODE.with(|current_mode| current_mode.set(mode));
+}
+
+pub fn mode() -> RoutingMode {
+    MODE.with(Cell::get)
+}
+
+#[cfg(test)]
+pub fn set_mode(mode: RoutingMode) {
+    MODE.with(|current_mode| current_mode.set(mode));
+}
+
+/// The URL all routes are appended to.
+pub fn base_url(url: &Url) -> Url {
+    match mode() {
+        RoutingMode::Hash => url.to_hash_base_url(),
+        // the app is served from the root in the path mode
+        RoutingMode::Path => Url::new(),
+    }
+}
+
+/// The current route as displayed in the address bar, e.g. `#/discover` or `/discover`.
+pub fn location() -> String {
+    let location = window().location();
+    let route = match mode() {
+        RoutingMode::Hash => location.hash(),
+        RoutingMode::Path => location.pathname(),
+    };
+    route.unwrap_or_default()
+}
+
+/// Replaces `#/...` links (e.g. bookmarks from the hash mode) with their path equivalents in the path mode.
+/// The history entry is replaced, so going back doesn't return to the hash link.
+pub fn redirect_hash_url(url: Url) -> Url {
+    if mode() != RoutingMode::Path || url.hash_path().is_empty() {
+        return url
+    }
+    let path_url = match Route::from_parts(url.hash_path()) {
+        Some(route) => route.to_url(&Url::new()),
+        None => return url,
+    };
+    log_info!("route", "Redirecting {} to {}", url, path_url);
+    replace_url(&path_url);
+    path_url
+}
+
+/// Changes the URL of the current history entry without notifying `subs::UrlChanged`.
+pub fn replace_url(url: &Url) {
+    if let Err(error) = window()
+        .history()
+        .and_then(|history| history.replace_state_with_url(&JsValue::NULL, "", Some(&url.to_string())))
+    {
+        log_error!("route", "History entry replacement failed: {:?}", error);
+    }
+}
+
+/// Marks URL changes caused by the back and forward buttons, see `take_history_traversal`.
+pub fn listen_to_history_traversal() {
+    let on_pop_state = Closure::wrap(Box::new(|| {
+        HISTORY_TRAVERSED.with(|traversed| traversed.set(true));
+    }) as Box<dyn Fn()>);
+    // the capture phase listener runs before the Seed's `popstate` listener notifying `subs::UrlChanged`
+    let result = window().add_event_listener_with_callback_and_bool(
+        "popstate",
+        on_pop_state.as_ref().unchecked_ref(),
+        true,
+    );
+    if let Err(error) = result {
+        log_error!("route", "History listener registration failed: {:?}", error);
+    }
+    on_pop_state.forget();
+}
+
+/// Whether the current URL change comes from the back or forward button. It's reset by the call.
+pub fn take_history_traversal() -> bool {
+    HISTORY_TRAVERSED.with(|traversed| traversed.replace(false))
+}
+
+// ------ Route ------
+
+/// A page with its parameters, encoded in the hash or the path depending on the `RoutingMode`
+/// (e.g. `#/detail/movie/tt0000001` or `/detail/movie/tt0000001`).
+///
+/// `Route::from_parts` and `Route::to_parts` are inverse, so every route can be shared as a link.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Route {
+    Board,
+    // `None` is the default catalog
+    Discover(Option<ResourceRequest>),
+    Detail {
+        type_name: String,
+        id: String,
+        video_id: Option<String>,
+    },
+    Intro {
+        form_type: FormType,
+        return_to: Option<Box<Route>>,
+    },
+    // `None` is the default library view
+    Library(Option<LibraryRequest>),
+    Player {
+        stream: Stream,
+        video_path: Option<VideoPath>,
+    },
+    Addons(AddonRequest),
+    Search(Option<String>),
+    Settings,
+    Remote(Option<String>),
+    TestLinks,
+    Logs,
+    Inspector,
+    BugReport,
+}
+
+impl Route {
+    /// `None` when the URL doesn't match any page or its parameters are invalid.
+    pub fn parse(url: &Url) -> Option<Self> {
+        match mode() {
+            RoutingMode::Hash => Self::from_parts(url.hash_path()),
+            RoutingMode::Path => Self::from_parts(&path_parts(url)),
+        }
+    }
+
+    /// Pages only for logged in users; others are redirected to the login form by `Route::login_redirect`.
+    pub fn requires_auth(&self) -> bool {
+        matches!(self, Self::Library(_))
+    }
+
+    /// The login form, which opens this route after authentication.
+    pub fn login_redirect(self) -> Self {
+        Self::Intro {
+            form_type: FormType::LogIn,
+            return_to: Some(Box::new(self)),
+        }
+    }
+
+    /// The query (only `return_to`) is kept at the end of the last part, e.g. `["intro", "login?return_to=..."]`.
+    pub fn from_parts(parts: &[impl AsRef<str>]) -> Option<Self> {
+        let mut parts = parts.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+        let mut return_to = None;
+        if let Some(last_part) = parts.last_mut() {
+            if let Some((part, query)) = split_query(last_part) {
+                *last_part = part;
+                return_to = parse_return_to(query);
+            }
+        }
+        let (page, parts) = match parts.split_first() {
+            Some((page, parts)) => (*page, parts),
+            None => return Some(Self::Board),
+        };
+        Some(match (page, parts) {
+            (DISCOVER, []) => Self::Discover(None),
+            (DISCOVER, [base, path]) => Self::Discover(Some(parse_resource_request(base, path)?)),
+            (DETAIL | LEGACY_DETAIL, [type_name, id]) => Self::Detail {
+                type_name: (*type_name).to_owned(),
+                id: (*id).to_owned(),
+                video_id: None,
+            },
+            (DETAIL | LEGACY_DETAIL, [type_name, id, video_id]) => Self::Detail {
+                type_name: (*type_name).to_owned(),
+                id: (*id).to_owned(),
+                video_id: Some((*video_id).to_owned()),
+            },
+            (INTRO, []) => Self::Intro {
+                form_type: FormType::SignUp,
+                return_to: return_to.map(Box::new),
+            },
+            (INTRO, [LOGIN]) => Self::Intro {
+                form_type: FormType::LogIn,
+                return_to: return_to.map(Box::new),
+            },
+            (LIBRARY, []) => Self::Library(None),
+            (LIBRARY, [type_, sort, page]) => Self::Library(Some(LibraryRequest {
+                r#type: if *type_ == ALL_TYPES { None } else { Some((*type_).to_owned()) },
+                sort: match *sort {
+                    LAST_WATCHED => Sort::LastWatched,
+                    NAME => Sort::Name,
+                    TIMES_WATCHED => Sort::TimesWatched,
+                    _ => return None,
+                },
+                page: LibraryRequestPage(page.parse().ok()?),
+            })),
+            (PLAYER, [stream]) => Self::Player {
+                stream: parse_stream(stream)?,
+                video_path: None,
+            },
+            (PLAYER, [stream, type_name, meta_id, video_id]) => Self::Player {
+                stream: parse_stream(stream)?,
+                video_path: Some(VideoPath {
+                    type_name: (*type_name).to_owned(),
+                    meta_id: (*meta_id).to_owned(),
+                    video_id: (*video_id).to_owned(),
+                }),
+            },
+            (ADDONS, []) => Self::Addons(AddonRequest::default()),
+            (ADDONS, [type_]) => Self::Addons(AddonRequest::Installed(InstalledAddonsRequest {
+                r#type: Some((*type_).to_owned()),
+            })),
+            (ADDONS, [base, path]) => Self::Addons(AddonRequest::Remote(parse_resource_request(base, path)?)),
+            (SEARCH, []) => Self::Search(None),
+            (SEARCH, [query]) => Self::Search(Some((*query).to_owned())),
+            (SETTINGS, []) => Self::Settings,
+            (REMOTE, []) => Self::Remote(None),
+            (REMOTE, [code]) => Self::Remote(Some((*code).to_owned())),
+            (TEST_LINKS, []) => Self::TestLinks,
+            (LOGS, []) => Self::Logs,
+            (INSPECTOR, []) => Self::Inspector,
+            (BUG_REPORT, []) => Self::BugReport,
+            _ => return None,
+        })
+    }
+
+    pub fn to_parts(&self) -> Vec<String> {
+        let mut parts = Vec::new();
+        let mut push = |part: &str| parts.push(part.to_owned());
+        match self {
+            Self::Board => {}
+            Self::Discover(resource_request) => {
+                push(DISCOVER);
+                if let Some(resource_request) = resource_request {
+                    push_resource_request(resource_request, &mut push);
+                }
+            }
+            Self::Detail { type_name, id, video_id } => {
+                push(DETAIL);
+                push(type_name);
+                push(id);
+                if let Some(video_id) = video_id {
+                    push(video_id);
+                }
+            }
+            Self::Intro { form_type, return_to } => {
+                push(INTRO);
+                if *form_type == FormType::LogIn {
+                    push(LOGIN);
+                }
+                if let Some(return_to) = return_to {
+                    let last_part = parts.last_mut().expect("intro part");
+                    last_part.push('?');
+                    last_part.push_str(&encode_return_to(return_to));
+                }
+            }
+            Self::Library(library_request) => {
+                push(LIBRARY);
+                if let Some(library_request) = library_request {
+                    push(library_request.r#type.as_deref().unwrap_or(ALL_TYPES));
+                    push(match library_request.sort {
+                        Sort::LastWatched => LAST_WATCHED,
+                        Sort::Name => NAME,
+                        Sort::TimesWatched => TIMES_WATCHED,
+                    });
+                    push(&library_request.page.to_string());
+                }
+            }
+            Self::Player { stream, video_path } => {
+                push(PLAYER);
+                push(&serde_json::to_string(stream).expect("serializable stream"));
+                if let Some(video_path) = video_path {
+                    push(&video_path.type_name);
+                    push(&video_path.meta_id);
+                    push(&video_path.video_id);
+                }
+            }
+            Self::Addons(addon_request) => {
+                push(ADDONS);
+                match addon_request {
+                    AddonRequest::Remote(resource_request) => push_resource_request(resource_request, &mut push),
+                    AddonRequest::Installed(InstalledAddonsRequest { r#type: Some(type_) }) => push(type_),
+                    AddonRequest::Installed(InstalledAddonsRequest { r#type: None }) => {}
+                }
+            }
+            Self::Search(query) => {
+                push(SEARCH);
+                if let Some(query) = query {
+                    push(query);
+                }
+            }
+            Self::Settings => push(SETTINGS),
+            Self::Remote(code) => {
+                push(REMOTE);
+                if let Some(code) = code {
+                    push(code);
+                }
+            }
+            Self::TestLinks => push(TEST_LINKS),
+            Self::Logs => push(LOGS),
+            Self::Inspector => push(INSPECTOR),
+            Self::BugReport => push(BUG_REPORT),
+        }
+        parts
+    }
+
+    pub fn to_url(&self, root_base_url: &Url) -> Url {
+        let parts = self.to_parts().into_iter();
+        match mode() {
+            RoutingMode::Hash => parts.fold(root_base_url.clone(), Url::add_hash_path_part),
+            RoutingMode::Path => parts.fold(root_base_url.clone(), |url, part| {
+                match split_query(&part) {
+                    Some((part, query)) => {
+                        let search = form_urlencoded::parse(query.as_bytes())
+                            .map(|(key, value)| (key.into_owned(), vec![value.into_owned()]));
+                        url.add_path_part(part).set_search(UrlSearch::new(search))
+                    }
+                    None => url.add_path_part(part),
+                }
+            }),
+        }
+    }
+}
+
+/// Path parts with the `return_to` search parameter moved to the last part, see `Route::from_parts`.
+fn path_parts(url: &Url) -> Vec<String> {
+    let mut parts = url.path().to_vec();
+    let return_to = url.search().get(RETURN_TO).and_then(|values| values.first());
+    if let (Some(last_part), Some(return_to)) = (parts.last_mut(), return_to) {
+        last_part.push('?');
+        last_part.push_str(&form_urlencoded::Serializer::new(String::new()).append_pair(RETURN_TO, return_to).finish());
+    }
+    parts
+}
+
+// Parts can contain `?` (e.g. stream URLs), so only the `return_to` query is split.
+fn split_query(part: &str) -> Option<(&str, &str)> {
+    let (part, query) = part.rsplit_once('?')?;
+    query.starts_with(&format!("{}=", RETURN_TO)).then(|| (part, query))
+}
+
+// Each part is encoded, so it can contain `/`, and the whole value is encoded again as the query value.
+fn encode_return_to(return_to: &Route) -> String {
+    let return_to = return_to
+        .to_parts()
+        .iter()
+        .map(|part| form_urlencoded::byte_serialize(part.as_bytes()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("/");
+    form_urlencoded::Serializer::new(String::new()).append_pair(RETURN_TO, &return_to).finish()
+}
+
+fn parse_return_to(query: &str) -> Option<Route> {
+    let (_, return_to) = form_urlencoded::parse(query.as_bytes()).find(|(key, _)| key == RETURN_TO)?;
+    if return_to.is_empty() {
+        return Some(Route::Board)
+    }
+    let parts = return_to
+        .split('/')
+        .map(|part| {
+            form_urlencoded::parse(part.as_bytes())
+                .map(|(part, _)| part.into_owned())
+                .next()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    let route = Route::from_parts(&parts);
+    if route.is_none() {
+        log_warn!("route", "Invalid return_to route '{}'", return_to);
+    }
+    route
+}
+
+fn parse_resource_request(base: &str, path: &str) -> Option<ResourceRequest> {
+    Some(ResourceRequest::new(
+        base.parse().map_err(|error| log_error!("route", "Invalid base url: {:?}", error)).ok()?,
+        serde_json::from_str(path).map_err(|error| log_error!("route", "Invalid path: {}", error)).ok()?,
+    ))
+}
+
+fn push_resource_request(resource_request: &ResourceRequest, mut push: impl FnMut(&str)) {
+    push(resource_request.base.as_str());
+    push(&serde_json::to_string(&resource_request.path).expect("serializable path"));
+}
+
+fn parse_stream(stream: &str) -> Option<Stream> {
+    serde_json::from_str(stream).map_err(|error| log_error!("route", "Invalid stream: {}", error)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use proptest::test_runner::{RngAlgorithm, TestCaseError, TestRng, TestRunner};
+    use serde_json::json;
+    use stremio_core::types::addon::{ExtraValue, ResourcePath};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    /// Parts a URL can carry: empty parts and dot segments are normalized away by the URL parser.
+    fn part() -> impl Strategy<Value = String> {
+        "[a-zA-Z0-9][a-zA-Z0-9 ._~:,;=+@!$&'()*-]{0,15}"
+    }
+
+    fn resource_request() -> impl Strategy<Value = ResourceRequest> {
+        (
+            "https://[a-z]{1,10}\\.[a-z]{2,3}/[a-z0-9]{1,8}/manifest\\.json",
+            part(),
+            part(),
+            part(),
+            prop::collection::vec((part(), part()), 0..3),
+        )
+            .prop_map(|(base, resource, type_name, id, extra)| {
+                let mut path = ResourcePath::without_extra(&resource, &type_name, &id);
+                path.extra = extra.into_iter().map(|(name, value)| ExtraValue { name, value }).collect();
+                ResourceRequest::new(base.parse().expect("valid base url"), path)
+            })
+    }
+
+    fn library_request() -> impl Strategy<Value = LibraryRequest> {
+        (
+            // `all` is reserved for requests without a type
+            prop::option::of(part().prop_filter("reserved type", |type_| type_ != ALL_TYPES)),
+            prop_oneof![Just(Sort::LastWatched), Just(Sort::Name), Just(Sort::TimesWatched)],
+            1..10_000_usize,
+        )
+            .prop_map(|(r#type, sort, page)| LibraryRequest {
+                r#type,
+                sort,
+                page: LibraryRequestPage(page.to_string().parse().expect("valid page")),
+            })
+    }
+
+    fn stream() -> impl Strategy<Value = Stream> {
+        prop_oneof![
+            "https://[a-z]{1,10}\\.com/[a-zA-Z0-9._-]{0,20}".prop_map(|url| json!({ "url": url })),
+            "[a-zA-Z0-9_-]{11}".prop_map(|yt_id| json!({ "ytId": yt_id })),
+        ]
+        .prop_map(|stream| serde_json::from_value(stream).expect("valid stream"))
+    }
+
+    fn form_type() -> impl Strategy<Value = FormType> {
+        prop_oneof![Just(FormType::SignUp), Just(FormType::LogIn)]
+    }
+
+    fn route() -> impl Strategy<Value = Route> {
+        prop_oneof![
+            route_without_intro(),
+            (form_type(), prop::option::of(route_without_intro())).prop_map(|(form_type, return_to)| Route::Intro {
+                form_type,
+                return_to: return_to.map(Box::new),
+            }),
+        ]
+    }
+
+    fn route_without_intro() -> impl Strategy<Value = Route> {
+        prop_oneof![
+            prop::sample::select(vec![
+                Route::Board,
+                Route::Settings,
+                Route::TestLinks,
+                Route::Logs,
+                Route::Inspector,
+                Route::BugReport,
+            ]),
+            prop::option::of(resource_request()).prop_map(Route::Discover),
+            (part(), part(), prop::option::of(part()))
+                .prop_map(|(type_name, id, video_id)| Route::Detail { type_name, id, video_id }),
+            prop::option::of(library_request()).prop_map(Route::Library),
+            (stream(), prop::option::of((part(), part(), part()))).prop_map(|(stream, video_path)| Route::Player {
+                stream,
+                video_path: video_path.map(|(type_name, meta_id, video_id)| VideoPath { type_name, meta_id, video_id }),
+            }),
+            prop_oneof![
+                resource_request().prop_map(AddonRequest::Remote),
+                prop::option::of(part()).prop_map(|r#type| AddonRequest::Installed(InstalledAddonsRequest { r#type })),
+            ]
+            .prop_map(Route::Addons),
+            prop::option::of(part()).prop_map(Route::Search),
+            prop::option::of(part()).prop_map(Route::Remote),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn routes_survive_a_round_trip(route in route()) {
+            prop_assert_eq!(Route::from_parts(&route.to_parts()), Some(route));
+        }
+
+        #[test]
+        fn parsed_routes_are_serialized_to_the_same_parts(route in route()) {
+            let parts = route.to_parts();
+            let parsed_route = Route::from_parts(&parts).expect("valid route");
+            prop_assert_eq!(parsed_route.to_parts(), parts);
+        }
+    }
+
+    /// `proptest!` tests aren't run by `wasm_bindgen_test`, so the runner is driven manually.
+    fn check_routes(test: impl Fn(Route) -> Result<(), TestCaseError>) {
+        let config = ProptestConfig {
+            cases: 64,
+            failure_persistence: None,
+            ..ProptestConfig::default()
+        };
+        let mut runner = TestRunner::new_with_rng(config, TestRng::deterministic_rng(RngAlgorithm::ChaCha));
+        runner.run(&route(), test).expect("all routes survive a round-trip");
+    }
+
+    /// Serializes the route to a URL string and parses it back like the browser does.
+    fn url_round_trip(route: &Route, mode: RoutingMode) -> Option<Route> {
+        MODE.with(|current_mode| current_mode.set(mode));
+        let url = route.to_url(&Url::new()).to_string().parse::<Url>().expect("valid URL");
+        Route::parse(&url)
+    }
+
+    #[wasm_bindgen_test]
+    fn routes_survive_a_hash_url_round_trip() {
+        check_routes(|route| {
+            prop_assert_eq!(url_round_trip(&route, RoutingMode::Hash), Some(route));
+            Ok(())
+        });
+    }
+
+    #[wasm_bindgen_test]
+    fn routes_survive_a_path_url_round_trip() {
+        check_routes(|route| {
+            prop_assert_eq!(url_round_trip(&route, RoutingMode::Path), Some(route));
+            Ok(())
+        });
+    }
+
+    #[wasm_bindgen_test]
+    fn return_to_is_moved_to_the_last_path_part() {
+        let url = "/intro/login?return_to=library%252Fall".parse::<Url>().expect("valid URL");
+        assert_eq!(path_parts(&url), ["intro", "login?return_to=library%252Fall"]);
+
+        let url = "/search/why%3F?unknown=1".parse::<Url>().expect("valid URL");
+        assert_eq!(path_parts(&url), ["search", "why?"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn hash_links_are_redirected_in_the_path_mode() {
+        let hash_url = "/#/detail/movie/tt0000001".parse::<Url>().expect("valid URL");
+        let route = Route::Detail {
+            type_name: "movie".to_owned(),
+            id: "tt0000001".to_owned(),
+            video_id: None,
+        };
+
+        MODE.with(|mode| mode.set(RoutingMode::Hash));
+        assert_eq!(redirect_hash_url(hash_url.clone()), hash_url);
+
+        MODE.with(|mode| mode.set(RoutingMode::Path));
+        let path_url = redirect_hash_url(hash_url);
+        assert_eq!(path_url.to_string(), route.to_url(&Url::new()).to_string());
+        assert_eq!(Route::parse(&path_url), Some(route));
+
+        let unknown_url = "/#/unknown".parse::<Url>().expect("valid URL");
+        assert_eq!(redirect_hash_url(unknown_url.clone()), unknown_url);
+    }
+
+    #[test]
+    fn only_return_to_is_split_as_a_query() {
+        assert_eq!(split_query("login?return_to=library"), Some(("login", "return_to=library")));
+        assert_eq!(split_query("why?"), None);
+        assert_eq!(split_query("a?b?return_to="), Some(("a?b", "return_to=")));
+        assert_eq!(split_query("login?other=1"), None);
+    }
+
+    #[test]
+    fn existing_links_are_parsed() {
+        assert_eq!(Route::from_parts(&[] as &[&str]), Some(Route::Board));
+        assert_eq!(
+            Route::from_parts(&["metadetails", "movie", "tt11656172", "tt11656172"]),
+            Route::from_parts(&["detail", "movie", "tt11656172", "tt11656172"]),
+        );
+        assert_eq!(
+            Route::from_parts(&["detail", "movie", "tt11656172", "tt11656172"]),
+            Some(Route::Detail {
+                type_name: "movie".to_owned(),
+                id: "tt11656172".to_owned(),
+                video_id: Some("tt11656172".to_owned()),
+            }),
+        );
+        assert_eq!(
+            Route::from_parts(&["library", "all", "name", "2"]),
+            Some(Route::Library(Some(LibraryRequest {
+                r#type: None,
+                sort: Sort::Name,
+                page: LibraryRequestPage("2".parse().unwrap()),
+            }))),
+        );
+        assert_eq!(
+            Route::from_parts(&["intro", "login"]),
+            Some(Route::Intro { form_type: FormType::LogIn, return_to: None }),
+        );
+    }
+
+    #[test]
+    fn login_redirect_returns_to_the_route() {
+        let route = Route::Library(None).login_redirect();
+        let parts = route.to_parts();
+        assert_eq!(parts, ["intro", "login?return_to=library"]);
+        assert_eq!(Route::from_parts(&parts), Some(route));
+
+        let route = Route::Detail {
+            type_name: "series".to_owned(),
+            id: "tt8111088".to_owned(),
+            video_id: Some("tt8111088:1:1".to_owned()),
+        }
+        .login_redirect();
+        assert_eq!(Route::from_parts(&route.to_parts()), Some(route));
+    }
+
+    #[test]
+    fn question_marks_in_parts_are_not_queries() {
+        let route = Route::Search(Some("why?".to_owned()));
+        assert_eq!(Route::from_parts(&route.to_parts()), Some(route));
+    }
+
+    #[test]
+    fn invalid_links_are_rejected() {
+        assert_eq!(Route::from_parts(&["unknown"]), None);
+        assert_eq!(Route::from_parts(&["settings", "general"]), None);
+        assert_eq!(Route::from_parts(&["library", "movie", "popularity", "1"]), None);
+        assert_eq!(Route::from_parts(&["discover", "not a url", "{}"]), None);
+        assert_eq!(Route::from_parts(&["player", "not a stream"]), None);
+    }
+}
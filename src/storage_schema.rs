@@ -0,0 +1,272 @@
+//! Versioned schema of the persisted `Ctx` (profile and library buckets).
+//!
+//! Entries are read as raw strings, parsed as JSON, migrated to the current `SCHEMA_VERSION` and only then
+//! deserialized. Entries that still can't be deserialized are backed up under `BACKUP_KEY_PREFIX`
+//! instead of being silently dropped; library buckets keep all valid items. Only the backups
+//! of the last migration are kept.
+
+use serde_json::{Map, Value};
+use stremio_core::constants::{LIBRARY_RECENT_STORAGE_KEY, LIBRARY_STORAGE_KEY, PROFILE_STORAGE_KEY};
+use stremio_core::runtime::msg::CtxStorageResponse;
+use stremio_core::runtime::{Env, EnvError, TryEnvFuture};
+use stremio_core::types::library::{LibraryBucket, LibraryItem};
+use stremio_core::types::profile::{Profile, Settings};
+
+pub const SCHEMA_VERSION_STORAGE_KEY: &str = "schema_version";
+pub const BACKUP_KEY_PREFIX: &str = "backup:";
+
+/// `MIGRATIONS[n]` migrates the storage from the version `n` to `n + 1`.
+/// The version `0` is the unversioned storage written before the schema was introduced.
+const MIGRATIONS: &[fn(&mut CtxValues)] = &[
+    fill_missing_settings,
+];
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Raw storage entries.
+struct CtxValues {
+    profile: Option<Value>,
+    library_recent: Option<Value>,
+    library: Option<Value>,
+}
+
+impl CtxValues {
+    fn entries(&mut self) -> [(&'static str, &mut Option<Value>); 3] {
+        [
+            (PROFILE_STORAGE_KEY, &mut self.profile),
+            (LIBRARY_RECENT_STORAGE_KEY, &mut self.library_recent),
+            (LIBRARY_STORAGE_KEY, &mut self.library),
+        ]
+    }
+}
+
+/// Storage read without deserialization, so unparseable entries can be backed up.
+pub trait RawStorage {
+    fn get_raw_storage(key: &str) -> TryEnvFuture<Option<String>>;
+}
+
+/// Storage entry that couldn't be loaded completely.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageRecovery {
+    pub key: &'static str,
+    /// Where the original entry was backed up, `None` when the backup couldn't be written.
+    pub backup_key: Option<String>,
+    pub description: String,
+}
+
+pub fn backup_key(key: &str) -> String {
+    format!("{}{}", BACKUP_KEY_PREFIX, key)
+}
+
+fn migration_backup_key(version: u32, key: &str) -> String {
+    format!("{}v{}:{}", BACKUP_KEY_PREFIX, version, key)
+}
+
+/// Migrates the storage and loads the profile and library buckets.
+pub async fn load_ctx_storage<E: Env + RawStorage>() -> Result<(CtxStorageResponse, Vec<StorageRecovery>), EnvError> {
+    let mut recoveries = Vec::new();
+    let mut values = CtxValues {
+        profile: read_value::<E>(PROFILE_STORAGE_KEY, &mut recoveries).await?,
+        library_recent: read_value::<E>(LIBRARY_RECENT_STORAGE_KEY, &mut recoveries).await?,
+        library: read_value::<E>(LIBRARY_STORAGE_KEY, &mut recoveries).await?,
+    };
+    migrate::<E>(&mut values).await?;
+
+    let profile = match values.profile.take() {
+        Some(value) => match serde_json::from_value::<Profile>(value.clone()) {
+            Ok(profile) => Some(profile),
+            Err(error) => {
+                recoveries.push(back_up::<E>(PROFILE_STORAGE_KEY, &value, format!("Profile is invalid: {}", error)).await);
+                None
+            }
+        },
+        None => None,
+    };
+    let recent_bucket = recover_library_bucket::<E>(LIBRARY_RECENT_STORAGE_KEY, values.library_recent.take(), &mut recoveries).await?;
+    let other_bucket = recover_library_bucket::<E>(LIBRARY_STORAGE_KEY, values.library.take(), &mut recoveries).await?;
+    Ok(((profile, recent_bucket, other_bucket), recoveries))
+}
+
+/// An entry that isn't valid JSON is backed up as a string and treated as missing.
+async fn read_value<E: Env + RawStorage>(
+    key: &'static str,
+    recoveries: &mut Vec<StorageRecovery>,
+) -> Result<Option<Value>, EnvError> {
+    let raw_value = match E::get_raw_storage(key).await? {
+        Some(raw_value) => raw_value,
+        None => return Ok(None),
+    };
+    match serde_json::from_str(&raw_value) {
+        Ok(value) => Ok(Some(value)),
+        Err(error) => {
+            let description = format!("Unreadable entry was reset: {}", error);
+            recoveries.push(back_up::<E>(key, &Value::String(raw_value), description).await);
+            Ok(None)
+        }
+    }
+}
+
+async fn migrate<E: Env>(values: &mut CtxValues) -> Result<(), EnvError> {
+    let version = E::get_storage::<u32>(SCHEMA_VERSION_STORAGE_KEY).await?.unwrap_or_default();
+    if version >= SCHEMA_VERSION {
+        // a newer schema written by a newer app version is read as is
+        return Ok(())
+    }
+    remove_migration_backups::<E>(version).await?;
+    for (key, value) in values.entries().iter() {
+        if let Some(value) = value {
+            E::set_storage(&migration_backup_key(version, key), Some(value)).await?;
+        }
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(values);
+    }
+    for (key, value) in values.entries().iter() {
+        if value.is_some() {
+            E::set_storage(key, value.as_ref()).await?;
+        }
+    }
+    E::set_storage(SCHEMA_VERSION_STORAGE_KEY, Some(&SCHEMA_VERSION)).await
+}
+
+/// Removes the backups of the migrations older than `version`.
+async fn remove_migration_backups<E: Env>(version: u32) -> Result<(), EnvError> {
+    for old_version in 0..version {
+        for key in &[PROFILE_STORAGE_KEY, LIBRARY_RECENT_STORAGE_KEY, LIBRARY_STORAGE_KEY] {
+            E::set_storage::<Value>(&migration_backup_key(old_version, key), None).await?;
+        }
+    }
+    Ok(())
+}
+
+/// A failed backup doesn't prevent loading, the entry is recovered anyway.
+async fn back_up<E: Env>(key: &'static str, value: &Value, description: String) -> StorageRecovery {
+    let backup_key = backup_key(key);
+    let backup_key = match E::set_storage(&backup_key, Some(value)).await {
+        Ok(()) => Some(backup_key),
+        Err(error) => {
+            log_error!("core", "Storage entry '{}' couldn't be backed up: {}", key, error.message());
+            None
+        }
+    };
+    StorageRecovery { key, backup_key, description }
+}
+
+/// Keeps the valid items of a corrupt bucket.
+async fn recover_library_bucket<E: Env>(
+    key: &'static str,
+    value: Option<Value>,
+    recoveries: &mut Vec<StorageRecovery>,
+) -> Result<Option<LibraryBucket>, EnvError> {
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    let error = match serde_json::from_value::<LibraryBucket>(value.clone()) {
+        Ok(bucket) => return Ok(Some(bucket)),
+        Err(error) => error,
+    };
+    let items = value.get("items").and_then(Value::as_object).cloned().unwrap_or_default();
+    let valid_items = items
+        .into_iter()
+        .filter(|(_, item)| serde_json::from_value::<LibraryItem>(item.clone()).is_ok())
+        .collect::<Map<_, _>>();
+    let dropped_items = value.get("items").and_then(Value::as_object).map_or(0, Map::len) - valid_items.len();
+    let uid = value.get("uid").cloned().unwrap_or(Value::Null);
+    let bucket = serde_json::from_value::<LibraryBucket>(serde_json::json!({ "uid": uid, "items": valid_items })).ok();
+    let description = match &bucket {
+        Some(_) => format!("{} invalid library items were removed: {}", dropped_items, error),
+        None => format!("Library is invalid: {}", error),
+    };
+    recoveries.push(back_up::<E>(key, &value, description).await);
+    Ok(bucket)
+}
+
+// ------ Migrations ------
+
+/// Profiles saved by older versions miss the settings added later.
+fn fill_missing_settings(values: &mut CtxValues) {
+    let settings = match values.profile.as_mut().and_then(|profile| profile.get_mut("settings")) {
+        Some(Value::Object(settings)) => settings,
+        _ => return,
+    };
+    if let Ok(Value::Object(default_settings)) = serde_json::to_value(Settings::default()) {
+        for (name, default_value) in default_settings {
+            settings.entry(name).or_insert(default_value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_env::TestEnv;
+    use futures::executor::block_on;
+
+    #[test]
+    fn old_profile_is_migrated() {
+        let mut profile = serde_json::to_value(Profile::default()).unwrap();
+        profile["settings"].as_object_mut().unwrap().remove("bingeWatching");
+        TestEnv::set_storage_item(PROFILE_STORAGE_KEY, &profile);
+
+        let ((profile, _, _), recoveries) = block_on(load_ctx_storage::<TestEnv>()).expect("loaded storage");
+
+        assert!(profile.is_some());
+        assert!(recoveries.is_empty());
+        assert_eq!(TestEnv::storage_item::<u32>(SCHEMA_VERSION_STORAGE_KEY), Some(SCHEMA_VERSION));
+        assert!(TestEnv::storage_item::<Value>(&migration_backup_key(0, PROFILE_STORAGE_KEY)).is_some());
+    }
+
+    #[test]
+    fn only_the_last_migration_backups_are_kept() {
+        TestEnv::set_storage_item(&migration_backup_key(0, PROFILE_STORAGE_KEY), &Profile::default());
+        TestEnv::set_storage_item(&migration_backup_key(1, PROFILE_STORAGE_KEY), &Profile::default());
+
+        block_on(remove_migration_backups::<TestEnv>(1)).expect("removed backups");
+
+        assert_eq!(TestEnv::storage_keys(), vec![migration_backup_key(1, PROFILE_STORAGE_KEY)]);
+    }
+
+    #[test]
+    fn unparseable_entry_is_backed_up() {
+        TestEnv::set_storage_item(SCHEMA_VERSION_STORAGE_KEY, &SCHEMA_VERSION);
+        TestEnv::set_raw_storage_item(LIBRARY_STORAGE_KEY, "{\"uid\":");
+
+        let ((_, _, other_bucket), recoveries) = block_on(load_ctx_storage::<TestEnv>()).expect("loaded storage");
+
+        assert!(other_bucket.is_none());
+        assert_eq!(recoveries.len(), 1);
+        assert_eq!(recoveries[0].key, LIBRARY_STORAGE_KEY);
+        assert_eq!(
+            TestEnv::storage_item::<Value>(&backup_key(LIBRARY_STORAGE_KEY)),
+            Some(Value::String("{\"uid\":".to_owned())),
+        );
+    }
+
+    #[test]
+    fn corrupt_profile_is_backed_up() {
+        TestEnv::set_storage_item(SCHEMA_VERSION_STORAGE_KEY, &SCHEMA_VERSION);
+        TestEnv::set_storage_item(PROFILE_STORAGE_KEY, &serde_json::json!({ "auth": 42 }));
+
+        let ((profile, _, _), recoveries) = block_on(load_ctx_storage::<TestEnv>()).expect("loaded storage");
+
+        assert!(profile.is_none());
+        assert_eq!(recoveries.len(), 1);
+        assert_eq!(
+            TestEnv::storage_item::<Value>(&backup_key(PROFILE_STORAGE_KEY)),
+            Some(serde_json::json!({ "auth": 42 })),
+        );
+    }
+
+    #[test]
+    fn invalid_library_items_are_dropped() {
+        TestEnv::set_storage_item(SCHEMA_VERSION_STORAGE_KEY, &SCHEMA_VERSION);
+        let bucket = serde_json::json!({ "uid": null, "items": { "tt0000001": { "corrupt": true } } });
+        TestEnv::set_storage_item(LIBRARY_RECENT_STORAGE_KEY, &bucket);
+
+        let ((_, recent_bucket, _), recoveries) = block_on(load_ctx_storage::<TestEnv>()).expect("loaded storage");
+
+        assert!(recent_bucket.expect("recovered bucket").items.is_empty());
+        assert_eq!(recoveries[0].key, LIBRARY_RECENT_STORAGE_KEY);
+        assert_eq!(TestEnv::storage_item::<Value>(&backup_key(LIBRARY_RECENT_STORAGE_KEY)), Some(bucket));
+    }
+}
@@ -17,11 +17,15 @@ pub enum Breakpoint {
 }
 impl BreakpointTheme for Breakpoint {} 
 
+/// The upper bound of `Breakpoint::Zero`, in pixels.
+/// Also read where the breakpoint isn't applied by styles (e.g. player touch gestures).
+pub const ZERO_BREAKPOINT_MAX_WIDTH: u32 = 640;
+
 pub fn default_breakpoint_theme() -> Theme {
     use Breakpoint::*;
     Theme::new("default_breakpoint_theme")
-        .set_breakpoint(Zero, (0, Some(640))) 
-        .set_breakpoint(Minimum, (640, Some(800)))
+        .set_breakpoint(Zero, (0, Some(ZERO_BREAKPOINT_MAX_WIDTH))) 
+        .set_breakpoint(Minimum, (ZERO_BREAKPOINT_MAX_WIDTH, Some(800)))
         .set_breakpoint(XXSmall, (800, Some(1000)))
         .set_breakpoint(XSmall, (1000, Some(1300)))
         .set_breakpoint(Small, (1300, Some(1600)))
@@ -31,7 +35,7 @@ pub fn default_breakpoint_theme() -> Theme {
         .set_breakpoint(XLarge, (2500, Some(2800)))
         .set_breakpoint(XXLarge, (2800,Some( 3800)))
         .set_breakpoint(XXXLarge, (3800, None))
-        .breakpoint_scale([640, 800, 1000, 1300, 1600, 1900, 2200, 2500, 2800, 3800]) 
+        .breakpoint_scale([ZERO_BREAKPOINT_MAX_WIDTH, 800, 1000, 1300, 1600, 1900, 2200, 2500, 2800, 3800]) 
 }
 
 // @TODO: Remove unused
@@ -0,0 +1,346 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use futures::future::LocalBoxFuture;
+use futures::{future, Future, FutureExt};
+use http::Request;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use stremio_core::models::ctx::Ctx;
+use stremio_core::models::streaming_server::StreamingServer;
+use stremio_core::runtime::{Effect, Effects, Env, EnvError, EnvFuture, TryEnvFuture, Update};
+use stremio_core::runtime::msg::{Action, ActionCtx, Msg as CoreMsg};
+use stremio_core::types::resource::MetaItemPreview;
+use seed::{body, document, window};
+use seed::prelude::{subs, App, MessageMapper, Node, Orders, Url};
+use std::rc::Rc;
+use wasm_bindgen_futures::JsFuture;
+use crate::{route, styles, Context, CoreModel, Model, Msg};
+use crate::route::Route;
+use crate::storage_schema::RawStorage;
+
+/// In-process `Env` for tests.
+///
+/// The state is thread-local, so every native test (running in its own thread) gets a fresh environment.
+/// Browser tests run in one thread and reset it, see `TestApp::start`.
+/// Futures passed to `exec` are queued and run by `TestEnv::run_pending`.
+pub enum TestEnv {}
+
+#[derive(Default)]
+struct State {
+    // URL -> serialized response
+    fetch_responses: HashMap<String, serde_json::Value>,
+    fetch_requests: Vec<FetchRequest>,
+    storage: HashMap<String, String>,
+    now: Option<DateTime<Utc>>,
+    pending_futures: Vec<LocalBoxFuture<'static, ()>>,
+    analytics_contexts: Vec<serde_json::Value>,
+    analytics_flushes: usize,
+    logs: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchRequest {
+    pub method: String,
+    pub url: String,
+    pub body: serde_json::Value,
+}
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+impl TestEnv {
+    /// Browser tests share the thread, so each `TestApp` starts from an empty environment.
+    pub fn reset() {
+        STATE.with(|state| *state.borrow_mut() = State::default());
+    }
+
+    pub fn add_fetch_response<T: Serialize>(url: &str, response: &T) {
+        let response = serde_json::to_value(response).expect("serialize fetch response");
+        STATE.with(|state| state.borrow_mut().fetch_responses.insert(url.to_owned(), response));
+    }
+
+    pub fn fetch_requests() -> Vec<FetchRequest> {
+        STATE.with(|state| state.borrow().fetch_requests.clone())
+    }
+
+    pub fn set_storage_item<T: Serialize>(key: &str, value: &T) {
+        let value = serde_json::to_string(value).expect("serialize storage item");
+        STATE.with(|state| state.borrow_mut().storage.insert(key.to_owned(), value));
+    }
+
+    /// Stores `value` as is, e.g. to simulate a corrupt entry.
+    pub fn set_raw_storage_item(key: &str, value: &str) {
+        STATE.with(|state| state.borrow_mut().storage.insert(key.to_owned(), value.to_owned()));
+    }
+
+    fn raw_storage_item(key: &str) -> Option<String> {
+        STATE.with(|state| state.borrow().storage.get(key).cloned())
+    }
+
+    pub fn storage_keys() -> Vec<String> {
+        STATE.with(|state| state.borrow().storage.keys().cloned().collect())
+    }
+
+    pub fn storage_item<T>(key: &str) -> Option<T>
+    where
+        for<'de> T: Deserialize<'de>,
+    {
+        STATE.with(|state| {
+            state
+                .borrow()
+                .storage
+                .get(key)
+                .map(|value| serde_json::from_str(value).expect("deserialize storage item"))
+        })
+    }
+
+    pub fn set_now(now: DateTime<Utc>) {
+        STATE.with(|state| state.borrow_mut().now = Some(now));
+    }
+
+    pub fn advance_now(duration: Duration) {
+        let now = Self::now() + duration;
+        Self::set_now(now);
+    }
+
+    pub fn analytics_contexts() -> Vec<serde_json::Value> {
+        STATE.with(|state| state.borrow().analytics_contexts.clone())
+    }
+
+    pub fn analytics_flushes() -> usize {
+        STATE.with(|state| state.borrow().analytics_flushes)
+    }
+
+    pub fn logs() -> Vec<String> {
+        STATE.with(|state| state.borrow().logs.clone())
+    }
+
+    /// Runs futures passed to `exec`, including the ones spawned while running them.
+    pub fn run_pending() {
+        loop {
+            let futures = STATE.with(|state| std::mem::take(&mut state.borrow_mut().pending_futures));
+            if futures.is_empty() {
+                return;
+            }
+            for future in futures {
+                futures::executor::block_on(future);
+            }
+        }
+    }
+}
+
+impl RawStorage for TestEnv {
+    fn get_raw_storage(key: &str) -> TryEnvFuture<Option<String>> {
+        future::ok(TestEnv::raw_storage_item(key)).boxed_local()
+    }
+}
+
+impl Env for TestEnv {
+    fn fetch<IN, OUT>(request: Request<IN>) -> TryEnvFuture<OUT>
+    where
+        IN: Serialize,
+        for<'de> OUT: Deserialize<'de> + 'static,
+    {
+        let (parts, body) = request.into_parts();
+        let url = parts.uri.to_string();
+        let request = FetchRequest {
+            method: parts.method.to_string(),
+            url: url.clone(),
+            body: serde_json::to_value(&body).unwrap_or(serde_json::Value::Null),
+        };
+        let response = STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            state.fetch_requests.push(request);
+            state.fetch_responses.get(&url).cloned()
+        });
+        let result = match response {
+            Some(response) => serde_json::from_value(response).map_err(EnvError::from),
+            None => Err(EnvError::Fetch(format!("Unexpected request: {}", url))),
+        };
+        future::ready(result).boxed_local()
+    }
+    fn get_storage<T>(key: &str) -> TryEnvFuture<Option<T>>
+    where
+        for<'de> T: Deserialize<'de> + 'static,
+    {
+        let value = TestEnv::raw_storage_item(key);
+        let result = match value {
+            Some(value) => serde_json::from_str(&value).map(Some).map_err(EnvError::from),
+            None => Ok(None),
+        };
+        future::ready(result).boxed_local()
+    }
+    fn set_storage<T: Serialize>(key: &str, value: Option<&T>) -> TryEnvFuture<()> {
+        let result = value.map(serde_json::to_string).transpose().map(|value| {
+            STATE.with(|state| {
+                let mut state = state.borrow_mut();
+                match value {
+                    Some(value) => state.storage.insert(key.to_owned(), value),
+                    None => state.storage.remove(key),
+                };
+            })
+        });
+        future::ready(result.map_err(EnvError::from)).boxed_local()
+    }
+    fn exec<F>(future: F)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        STATE.with(|state| state.borrow_mut().pending_futures.push(future.boxed_local()));
+    }
+    fn now() -> DateTime<Utc> {
+        STATE.with(|state| state.borrow().now.unwrap_or_else(|| Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)))
+    }
+    fn flush_analytics() -> EnvFuture<()> {
+        STATE.with(|state| state.borrow_mut().analytics_flushes += 1);
+        future::ready(()).boxed_local()
+    }
+    fn analytics_context(ctx: &Ctx, _streaming_server: &StreamingServer) -> serde_json::Value {
+        let context = serde_json::json!({
+            "appType": "test",
+            "appLanguage": ctx.profile.settings.interface_language,
+        });
+        STATE.with(|state| state.borrow_mut().analytics_contexts.push(context.clone()));
+        context
+    }
+    #[cfg(debug_assertions)]
+    fn log(message: String) {
+        STATE.with(|state| state.borrow_mut().logs.push(message));
+    }
+}
+
+// ------ CoreModel helpers ------
+
+pub fn new_core_model() -> CoreModel {
+    let (mut core_model, effects) = CoreModel::new();
+    run_effects(&mut core_model, effects);
+    core_model
+}
+
+/// Applies `core_msg` and everything produced by its effects.
+pub fn update(core_model: &mut CoreModel, core_msg: &CoreMsg) {
+    let effects = core_model.update(core_msg);
+    run_effects(core_model, effects);
+}
+
+pub fn dispatch(core_model: &mut CoreModel, action: ActionCtx) {
+    update(core_model, &CoreMsg::Action(Action::Ctx(action)));
+}
+
+/// Resolves all `effects` and feeds the produced messages back to `core_model`.
+pub fn run_effects(core_model: &mut CoreModel, effects: Effects) {
+    for effect in effects {
+        let core_msg = match effect {
+            Effect::Msg(core_msg) => core_msg,
+            Effect::Future(future) => futures::executor::block_on(future),
+        };
+        update(core_model, &core_msg);
+    }
+    TestEnv::run_pending();
+}
+
+pub fn meta_preview(id: &str) -> MetaItemPreview {
+    serde_json::from_value(serde_json::json!({ "id": id, "type": "movie", "name": "Test movie" }))
+        .expect("valid meta preview")
+}
+
+// ------ TestApp ------
+
+pub enum TestMsg {
+    Root(Msg),
+    UrlRequested(Url),
+    Inspect(Box<dyn FnOnce(&mut TestModel)>),
+}
+
+pub struct TestModel {
+    pub model: Model,
+    /// URLs requested by pages, they aren't opened.
+    pub requested_urls: Vec<Url>,
+}
+
+/// Runs the app's `update` and `view` in the browser for page tests.
+///
+/// `init` is replaced, so there are no window listeners, storage loading or analytics timers;
+/// the ctx is loaded by sending `Msg::CtxStorageResponse`. `CoreModel` runs against `TestEnv`.
+pub struct TestApp {
+    app: App<TestMsg, TestModel, Node<TestMsg>>,
+}
+
+impl TestApp {
+    pub async fn start() -> Self {
+        TestEnv::reset();
+        route::set_mode(route::RoutingMode::Hash);
+        let element = document().create_element("div").expect("create app element");
+        body().append_child(&element).expect("append app element");
+        let app = Self { app: App::start(element, app_init, app_update, app_view) };
+        app.settle().await;
+        app
+    }
+
+    pub async fn open(&self, route: Route) {
+        let url = self.read(|model| route.to_url(&model.model.context.root_base_url));
+        self.send(Msg::UrlChanged(subs::UrlChanged(url))).await;
+    }
+
+    /// Sends the message and waits until its commands, effects and renders are finished.
+    pub async fn send(&self, msg: Msg) {
+        self.app.update(TestMsg::Root(msg));
+        self.settle().await;
+    }
+
+    pub fn read<T: 'static>(&self, read: impl FnOnce(&TestModel) -> T + 'static) -> T {
+        let value = Rc::new(RefCell::new(None));
+        let value_setter = Rc::clone(&value);
+        self.app.update(TestMsg::Inspect(Box::new(move |model| {
+            *value_setter.borrow_mut() = Some(read(model));
+        })));
+        let value = value.borrow_mut().take();
+        value.expect("inspected model")
+    }
+
+    async fn settle(&self) {
+        // commands resolve in microtasks, renders and `after_next_render` callbacks in animation frames
+        for _ in 0..3 {
+            let animation_frame = js_sys::Promise::new(&mut |resolve, _| {
+                window().request_animation_frame(&resolve).expect("request animation frame");
+            });
+            JsFuture::from(animation_frame).await.expect("animation frame");
+            TestEnv::run_pending();
+        }
+    }
+}
+
+fn app_init(url: Url, orders: &mut impl Orders<TestMsg>) -> TestModel {
+    styles::global::init();
+    orders
+        .subscribe(|subs::UrlRequested(url, url_request)| {
+            url_request.handled();
+            TestMsg::UrlRequested(url)
+        })
+        .proxy(TestMsg::Root)
+        .subscribe(Msg::UrlChanged)
+        .subscribe(crate::action_msg)
+        .subscribe(Msg::CoreMsg);
+    let (core_model, effects) = CoreModel::new();
+    crate::handle_core_effects(effects, &mut orders.proxy(TestMsg::Root));
+    TestModel {
+        model: Model::new(Context::new(core_model, route::base_url(&url))),
+        requested_urls: Vec::new(),
+    }
+}
+
+fn app_update(msg: TestMsg, model: &mut TestModel, orders: &mut impl Orders<TestMsg>) {
+    match msg {
+        TestMsg::Root(msg) => crate::update(msg, &mut model.model, &mut orders.proxy(TestMsg::Root)),
+        TestMsg::UrlRequested(url) => model.requested_urls.push(url),
+        TestMsg::Inspect(inspect) => {
+            orders.skip();
+            inspect(model);
+        }
+    }
+}
+
+fn app_view(model: &TestModel) -> Node<TestMsg> {
+    crate::view(&model.model).map_msg(TestMsg::Root)
+}
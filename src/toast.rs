@@ -0,0 +1,263 @@
+use crate::{Actions, Msg};
+use crate::styles::themes::Color;
+use seed::{prelude::*, *};
+use seed_styles::{rem, pc};
+use seed_styles::*;
+use seed_hooks::{*, topo::nested as view};
+use stremio_core::runtime::EnvError;
+use stremio_core::runtime::msg::{Msg as CoreMsg, Event};
+use stremio_core::types::api::APIError;
+use stremio_core::models::ctx::CtxError;
+
+/// The oldest toasts are dropped when a new one doesn't fit.
+const MAX_TOASTS: usize = 5;
+const INFO_TIMEOUT: u32 = 4000;
+const WARNING_TIMEOUT: u32 = 8000;
+
+pub type ToastId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Errors stay until they are dismissed.
+    fn timeout(self) -> Option<u32> {
+        match self {
+            Self::Info | Self::Success => Some(INFO_TIMEOUT),
+            Self::Warning => Some(WARNING_TIMEOUT),
+            Self::Error => None,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Info => Color::Accent3,
+            Self::Success => Color::Signal5,
+            Self::Warning => Color::Signal1,
+            Self::Error => Color::Signal2,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum ToastAction {
+    OpenUrl(Url),
+    // boxed, `Actions::ShowToast` contains the toast
+    Notify(Box<Actions>),
+    Reload,
+}
+
+#[derive(Clone)]
+pub struct ToastButton {
+    pub title: String,
+    pub action: ToastAction,
+}
+
+/// Shown by `Actions::ShowToast`.
+#[derive(Clone)]
+pub struct Toast {
+    pub severity: Severity,
+    pub message: String,
+    pub button: Option<ToastButton>,
+}
+
+impl Toast {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self { severity, message: message.into(), button: None }
+    }
+
+    pub fn with_button(mut self, title: impl Into<String>, action: ToastAction) -> Self {
+        self.button = Some(ToastButton { title: title.into(), action });
+        self
+    }
+
+    /// Default toasts for the library changes and the core errors.
+    /// Network failures aren't shown when offline, the offline banner is already there.
+    pub fn from_core_msg(core_msg: &CoreMsg, root_base_url: &Url, online: bool) -> Option<Self> {
+        match core_msg {
+            CoreMsg::Event(Event::LibraryItemAdded { .. }) => Some(
+                Self::new(Severity::Success, "Added to library")
+                    .with_button("Open library", ToastAction::OpenUrl(crate::Urls::new(root_base_url).library()))
+            ),
+            CoreMsg::Event(Event::LibraryItemRemoved { .. }) => Some(Self::new(Severity::Info, "Removed from library")),
+            CoreMsg::Event(Event::AddonInstalled { .. }) => Some(Self::new(Severity::Success, "Addon installed")),
+            CoreMsg::Event(Event::AddonUninstalled { .. }) => Some(Self::new(Severity::Info, "Addon uninstalled")),
+            // the login form shows its own errors
+            CoreMsg::Event(Event::Error { source, .. }) if matches!(source.as_ref(), Event::UserAuthenticated { .. }) => None,
+            CoreMsg::Event(Event::Error { error: CtxError::Env(EnvError::Fetch(_)), .. }) => {
+                online.then(|| Self::new(Severity::Warning, "A network request failed. Check your connection and try again."))
+            }
+            CoreMsg::Event(Event::Error { error, .. }) => Some(Self::new(Severity::Error, error_message(error))),
+            _ => None,
+        }
+    }
+}
+
+fn error_message(error: &CtxError) -> String {
+    match error {
+        CtxError::API(APIError { message, .. }) => message.clone(),
+        CtxError::Env(error) => error.message(),
+        CtxError::Other(error) => error.message(),
+    }
+}
+
+struct ToastEntry {
+    id: ToastId,
+    toast: Toast,
+}
+
+/// Toasts owned by `Context`, the newest one is the last.
+#[derive(Default)]
+pub struct Toasts {
+    entries: Vec<ToastEntry>,
+    next_id: ToastId,
+}
+
+impl Toasts {
+    /// Returns the id and the auto-dismiss timeout of the shown toast.
+    /// A toast with the same severity and message replaces the old one instead of stacking.
+    pub fn push(&mut self, toast: Toast) -> (ToastId, Option<u32>) {
+        self.entries.retain(|entry| entry.toast.severity != toast.severity || entry.toast.message != toast.message);
+        if self.entries.len() == MAX_TOASTS {
+            self.entries.remove(0);
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        let timeout = toast.severity.timeout();
+        self.entries.push(ToastEntry { id, toast });
+        (id, timeout)
+    }
+
+    pub fn dismiss(&mut self, id: ToastId) -> Option<Toast> {
+        let index = self.entries.iter().position(|entry| entry.id == id)?;
+        Some(self.entries.remove(index).toast)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+#[view]
+pub fn view(toasts: &Toasts) -> Node<Msg> {
+    if toasts.is_empty() {
+        return empty![]
+    }
+    div![
+        C!["toasts-container"],
+        s()
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Column)
+            .max_width(pc(100))
+            .position(CssPosition::Fixed)
+            .right(rem(1))
+            .top(rem(1))
+            .width(rem(24))
+            .z_index("3"),
+        toasts.entries.iter().map(toast),
+    ]
+}
+
+#[view]
+fn toast(entry: &ToastEntry) -> Node<Msg> {
+    let id = entry.id;
+    let severity = entry.toast.severity;
+    div![
+        C!["toast"],
+        s()
+            .align_items(CssAlignItems::Center)
+            .background_color(Color::BackgroundDark3)
+            .border_left("0.3rem solid")
+            .border_left_color(severity.color())
+            .color(Color::SurfaceLight5_90)
+            .display(CssDisplay::Flex)
+            .flex_direction(CssFlexDirection::Row)
+            .margin_bottom(rem(0.5))
+            .padding(rem(1)),
+        attrs!{
+            At::from("role") => if matches!(severity, Severity::Warning | Severity::Error) { "alert" } else { "status" },
+        },
+        div![
+            C!["message"],
+            s()
+                .flex("1")
+                .line_height(rem(1.5)),
+            &entry.toast.message,
+        ],
+        entry.toast.button.as_ref().map(|button| toast_button(&button.title, id, Msg::RunToastAction)),
+        toast_button("✕", id, Msg::DismissToast),
+    ]
+}
+
+#[view]
+fn toast_button(title: &str, id: ToastId, on_click: fn(ToastId) -> Msg) -> Node<Msg> {
+    div![
+        C!["button-container"],
+        s()
+            .cursor(CssCursor::Pointer)
+            .flex(CssFlex::None)
+            .margin_left(rem(1))
+            .text_decoration("underline"),
+        attrs!{
+            At::TabIndex => 0,
+            At::Title => title,
+        },
+        ev(Ev::Click, move |_| on_click(id)),
+        title,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toasts_are_stacked_up_to_the_limit() {
+        let mut toasts = Toasts::default();
+        let ids = (0..MAX_TOASTS + 2)
+            .map(|index| toasts.push(Toast::new(Severity::Info, index.to_string())).0)
+            .collect::<Vec<_>>();
+        assert_eq!(toasts.entries.len(), MAX_TOASTS);
+        assert!(toasts.dismiss(ids[1]).is_none());
+        assert_eq!(toasts.dismiss(ids[2]).unwrap().message, "2");
+    }
+
+    #[test]
+    fn repeated_toasts_replace_each_other() {
+        let mut toasts = Toasts::default();
+        let (first_id, timeout) = toasts.push(Toast::new(Severity::Warning, "offline"));
+        assert_eq!(timeout, Some(WARNING_TIMEOUT));
+        let (second_id, _) = toasts.push(Toast::new(Severity::Warning, "offline"));
+        toasts.push(Toast::new(Severity::Error, "offline"));
+        assert_eq!(toasts.entries.len(), 2);
+        assert!(toasts.dismiss(first_id).is_none());
+        assert!(toasts.dismiss(second_id).is_some());
+    }
+
+    #[test]
+    fn addon_changes_are_shown() {
+        let transport_url: url::Url = "https://addon.example.com/manifest.json".parse().unwrap();
+        let installed = CoreMsg::Event(Event::AddonInstalled {
+            transport_url: transport_url.clone(),
+            id: "com.example.addon".to_owned(),
+        });
+        let uninstalled = CoreMsg::Event(Event::AddonUninstalled {
+            transport_url,
+            id: "com.example.addon".to_owned(),
+        });
+
+        let toast = Toast::from_core_msg(&installed, &Url::new(), true).unwrap();
+        assert_eq!(toast.message, "Addon installed");
+        let toast = Toast::from_core_msg(&uninstalled, &Url::new(), true).unwrap();
+        assert_eq!(toast.message, "Addon uninstalled");
+    }
+}